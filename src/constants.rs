@@ -1,11 +1,17 @@
 pub const FACT_REGISTRY_CONTRACT_MAINNET: &str = "0xBE9a129909EbCb954bC065536D2bfAfBd170d27A";
 pub const FACT_REGISTRY_CONTRACT_ROPSTEN: &str = "0x8Fb814935f7E63DEB304B500180e19dF5167B50e";
+pub const FACT_REGISTRY_CONTRACT_GOERLI: &str = "0xc5061C08cF892C79DDB106B777138982433C8865";
+pub const FACT_REGISTRY_CONTRACT_TESTNET: &str = "0xCD828e691cA23b66291ae905491Bb89aEe3Abd82";
 pub const ASSET_USDC_CONTRACT_MAINNET: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
 pub const ASSET_USDC_CONTRACT_ROPSTEN: &str = "0x8707A5bf4C2842d46B31A405Ba41b858C0F876c4";
+pub const ASSET_USDC_CONTRACT_GOERLI: &str = "0xF7a2fa2c2025fFe64427dd40Dc190d47ecC8B36e";
+pub const ASSET_USDC_CONTRACT_TESTNET: &str = "0x7fC9C132268E0E414991449c003DbdB3E73E2059";
 pub const COLLATERAL_TOKEN_DECIMALS: u8 = 6;
 
 pub const MAINNET_API_URL: &str = "https://api.dydx.exchange";
 pub const TESTNET_API_URL: &str = "https://api.stage.dydx.exchange";
+pub const MAINNET_WS_URL: &str = "wss://api.dydx.exchange/v3/ws";
+pub const TESTNET_WS_URL: &str = "wss://api.stage.dydx.exchange/v3/ws";
 pub const TESTNET_NETWORK_ID: usize = 11155111; // Goerli
 
 // chrome test account
@@ -17,3 +23,86 @@ pub const TEST_STARK_PRIVATE_KEY: &str =
 pub const TEST_STARK_Y_COORDINATE: &str =
     "041128f350e96f6562cd5ba16ce15d2d95a6502c4fa5fa5e8ad6e90c8db9420b";
 pub const POSITION_ID: &str = "228";
+
+pub const NETWORK_ID_MAINNET: usize = 1;
+pub const NETWORK_ID_GOERLI: usize = 5;
+
+pub const COLLATERAL_ASSET: &str = "USDC";
+pub const COLLATERAL_ASSET_RESOLUTION: &str = "1e6";
+pub const COLLATERAL_ASSET_ID_MAINNET: &str =
+    "0x02893294412a4c8f915f75892b395ebbf6859ec246ec365c3b1f56f47c3a0a5d";
+pub const COLLATERAL_ASSET_ID_GOERLI: &str =
+    "0x03bda2b4764039f2df44a00a9cf1d1569a83f95406a983ce4beb95791c376008";
+pub const COLLATERAL_ASSET_ID_TESTNET: &str =
+    "0x01e70c509c4c6bfafe8b73d2fc1819444b2c0b435d4b82c0f24addff9565ce25";
+
+// mirrors SYNTHETIC_ASSET_MAP, SYNTHETIC_ASSET_ID_MAP and ASSET_RESOLUTION in dydx_constants.py
+pub struct SyntheticAsset {
+    pub market: &'static str,
+    pub asset: &'static str,
+    pub asset_id: &'static str,
+    pub resolution: &'static str,
+}
+
+pub const SYNTHETIC_ASSETS: &[SyntheticAsset] = &[
+    SyntheticAsset { market: "BTC-USD", asset: "BTC", asset_id: "0x4254432d3130000000000000000000", resolution: "1e10" },
+    SyntheticAsset { market: "ETH-USD", asset: "ETH", asset_id: "0x4554482d3900000000000000000000", resolution: "1e9" },
+    SyntheticAsset { market: "LINK-USD", asset: "LINK", asset_id: "0x4c494e4b2d37000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "AAVE-USD", asset: "AAVE", asset_id: "0x414156452d38000000000000000000", resolution: "1e8" },
+    SyntheticAsset { market: "UNI-USD", asset: "UNI", asset_id: "0x554e492d3700000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "SUSHI-USD", asset: "SUSHI", asset_id: "0x53555348492d370000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "SOL-USD", asset: "SOL", asset_id: "0x534f4c2d3700000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "YFI-USD", asset: "YFI", asset_id: "0x5946492d3130000000000000000000", resolution: "1e10" },
+    SyntheticAsset { market: "1INCH-USD", asset: "1INCH", asset_id: "0x31494e43482d370000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "AVAX-USD", asset: "AVAX", asset_id: "0x415641582d37000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "SNX-USD", asset: "SNX", asset_id: "0x534e582d3700000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "CRV-USD", asset: "CRV", asset_id: "0x4352562d3600000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "UMA-USD", asset: "UMA", asset_id: "0x554d412d3700000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "DOT-USD", asset: "DOT", asset_id: "0x444f542d3700000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "DOGE-USD", asset: "DOGE", asset_id: "0x444f47452d35000000000000000000", resolution: "1e5" },
+    SyntheticAsset { market: "MATIC-USD", asset: "MATIC", asset_id: "0x4d415449432d360000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "MKR-USD", asset: "MKR", asset_id: "0x4d4b522d3900000000000000000000", resolution: "1e9" },
+    SyntheticAsset { market: "FIL-USD", asset: "FIL", asset_id: "0x46494c2d3700000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "ADA-USD", asset: "ADA", asset_id: "0x4144412d3600000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "ATOM-USD", asset: "ATOM", asset_id: "0x41544f4d2d37000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "COMP-USD", asset: "COMP", asset_id: "0x434f4d502d38000000000000000000", resolution: "1e8" },
+    SyntheticAsset { market: "BCH-USD", asset: "BCH", asset_id: "0x4243482d3800000000000000000000", resolution: "1e8" },
+    SyntheticAsset { market: "LTC-USD", asset: "LTC", asset_id: "0x4c54432d3800000000000000000000", resolution: "1e8" },
+    SyntheticAsset { market: "EOS-USD", asset: "EOS", asset_id: "0x454f532d3600000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "ALGO-USD", asset: "ALGO", asset_id: "0x414c474f2d36000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "ZRX-USD", asset: "ZRX", asset_id: "0x5a52582d3600000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "XMR-USD", asset: "XMR", asset_id: "0x584d522d3800000000000000000000", resolution: "1e8" },
+    SyntheticAsset { market: "ZEC-USD", asset: "ZEC", asset_id: "0x5a45432d3800000000000000000000", resolution: "1e8" },
+    SyntheticAsset { market: "ENJ-USD", asset: "ENJ", asset_id: "0x454e4a2d3600000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "ETC-USD", asset: "ETC", asset_id: "0x4554432d3700000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "XLM-USD", asset: "XLM", asset_id: "0x584c4d2d3500000000000000000000", resolution: "1e5" },
+    SyntheticAsset { market: "TRX-USD", asset: "TRX", asset_id: "0x5452582d3400000000000000000000", resolution: "1e4" },
+    SyntheticAsset { market: "XTZ-USD", asset: "XTZ", asset_id: "0x58545a2d3600000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "HNT-USD", asset: "HNT", asset_id: "0x484e542d3700000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "ICP-USD", asset: "ICP", asset_id: "0x4943502d3700000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "RUNE-USD", asset: "RUNE", asset_id: "0x52554e452d36000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "LUNA-USD", asset: "LUNA", asset_id: "0x4c554e412d36000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "NEAR-USD", asset: "NEAR", asset_id: "0x4e4541522d36000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "AR-USD", asset: "AR", asset_id: "0x41522d370000000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "FLOW-USD", asset: "FLOW", asset_id: "0x464c4f572d37000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "PERP-USD", asset: "PERP", asset_id: "0x504552502d36000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "REN-USD", asset: "REN", asset_id: "0x52454e2d3500000000000000000000", resolution: "1e5" },
+    SyntheticAsset { market: "CELO-USD", asset: "CELO", asset_id: "0x43454c4f2d36000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "KSM-USD", asset: "KSM", asset_id: "0x4b534d2d3800000000000000000000", resolution: "1e8" },
+    SyntheticAsset { market: "BAL-USD", asset: "BAL", asset_id: "0x42414c2d3700000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "BNT-USD", asset: "BNT", asset_id: "0x424e542d3600000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "MIR-USD", asset: "MIR", asset_id: "0x4d49522d3600000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "SRM-USD", asset: "SRM", asset_id: "0x53524d2d3600000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "LON-USD", asset: "LON", asset_id: "0x4c4f4e2d3600000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "DODO-USD", asset: "DODO", asset_id: "0x444f444f2d36000000000000000000", resolution: "1e6" },
+    SyntheticAsset { market: "ALPHA-USD", asset: "ALPHA", asset_id: "0x414c5048412d350000000000000000", resolution: "1e5" },
+    SyntheticAsset { market: "WNXM-USD", asset: "WNXM", asset_id: "0x574e584d2d37000000000000000000", resolution: "1e7" },
+    SyntheticAsset { market: "XCH-USD", asset: "XCH", asset_id: "0x5843482d3800000000000000000000", resolution: "1e8" },
+];
+
+pub const SIGNATURE_TYPE_NO_PREPEND: u8 = 0;
+pub const SIGNATURE_TYPE_DECIMAL: u8 = 1;
+pub const SIGNATURE_TYPE_HEXADECIMAL: u8 = 2;
+
+pub const OFF_CHAIN_ONBOARDING_ACTION: &str = "dYdX Onboarding";
+pub const OFF_CHAIN_KEY_DERIVATION_ACTION: &str = "dYdX STARK Key";
@@ -7,15 +7,27 @@ use crate::modules::eth_private::EthPrivate;
 use crate::modules::onboarding::Onboarding;
 use crate::modules::private::Private;
 use crate::modules::public::Public;
+use crate::eth::signers::{self as eth_signers, EthSigner, SignWithKey};
+use crate::starkex::market_registry::MarketRegistry;
+use crate::starkex::signers::{self as stark_signers, StarkKeySigner, StarkSigner};
+use crate::starkex::signing_pool::SigningPool;
+use crate::modules::self_test::{self, SelfTestReport};
+use crate::error::SelfTestError;
 use crate::retry::{ErrorFn, ExponentialBuilderHelperGet, FallbackBackoffGetter, NoBackoffGetter};
 
-#[derive(Debug)]
+/// Options of DydxClient::new. Every field is optional; start from
+/// ClientOptions::default() and set the ones needed.
+#[derive(Debug, Default)]
 pub struct ClientOptions<'a> {
     pub network_id: Option<usize>,
     pub api_timeout: Option<u64>,
     pub api_key_credentials: Option<ApiKeyCredentials<'a>>,
     pub stark_private_key: Option<&'a str>,
+    pub stark_signer: Option<Arc<dyn StarkSigner>>,
+    pub market_registry: Option<Arc<MarketRegistry>>,
+    pub signing_pool: Option<Arc<SigningPool>>,
     pub eth_private_key: Option<&'a str>,
+    pub eth_signer: Option<Arc<dyn EthSigner>>,
     pub public_error_handler: Option<ErrorFn>, // Correct use of `dyn`
     pub private_error_handler: Option<ErrorFn>, // Correct use of `dyn`
     pub public_backoff_getter: Option<Arc<dyn ExponentialBuilderHelperGet>>,
@@ -34,10 +46,16 @@ pub struct DydxClient<'a> {
 }
 
 impl DydxClient<'_> {
+    /// A client with the given options. An invalid stark_private_key or eth_private_key
+    /// is reported by the first signature that needs it; use try_new to check them now.
     pub fn new<'a>(host: &'a str, mut _options: ClientOptions<'a>) -> DydxClient<'a> {
         let mut _options = _options;
         let network_id = _options.network_id.unwrap_or(1);
         let api_timeout = _options.api_timeout.unwrap_or(10);
+        let signing_pool = _options.signing_pool.unwrap_or_else(SigningPool::shared);
+        let eth_signer = _options.eth_signer.or_else(|| {
+            _options.eth_private_key.map(eth_signers::key_signer_or_failing)
+        });
         DydxClient {
             api_timeout: None,
 
@@ -48,25 +66,49 @@ impl DydxClient<'_> {
                     network_id,
                     api_timeout,
                     v,
-                    _options.stark_private_key,
+                    _options.stark_signer.or_else(|| {
+                        _options
+                            .stark_private_key
+                            .map(|k| stark_signers::key_signer_or_failing(k, signing_pool.clone()))
+                    }),
+                    _options.market_registry,
+                    signing_pool,
                     _options.private_error_handler,
                     _options.private_backoff_getter.unwrap_or(DydxClient::get_fallback_backoff_getter()),
                 )),
                 None => None,
             },
-            eth_private: match _options.eth_private_key {
-                Some(v) => Some(EthPrivate::new(
-                    host, network_id, api_timeout,v,
-                )),
-                None => None,
-            },
-            onboarding: match _options.eth_private_key {
-                Some(r) => Some(Onboarding::new(host, network_id, api_timeout, r)),
-                None => None,
-            },
+            eth_private: eth_signer
+                .clone()
+                .map(|signer| EthPrivate::with_signer(host, network_id, api_timeout, signer)),
+            onboarding: eth_signer.map(|signer| Onboarding::with_signer(host, network_id, api_timeout, signer)),
         }
     }
 
+    /// Like new, but checks everything that would otherwise fail later: invalid keys are
+    /// returned as StarkError/EthError here instead of on the first signature, and the
+    /// signing backend must pass self_test.
+    pub fn try_new<'a>(host: &'a str, mut options: ClientOptions<'a>) -> crate::Result<DydxClient<'a>> {
+        if let (None, Some(key)) = (&options.stark_signer, options.stark_private_key) {
+            let signing_pool = options.signing_pool.clone().unwrap_or_else(SigningPool::shared);
+            options.stark_signer = Some(Arc::new(StarkKeySigner::with_pool(key, signing_pool)?));
+        }
+        if let (None, Some(key)) = (&options.eth_signer, options.eth_private_key) {
+            options.eth_signer = Some(Arc::new(SignWithKey::new(key)?));
+        }
+        DydxClient::self_test()?;
+        Ok(DydxClient::new(host, options))
+    }
+
+    /// Sign and verify known test vectors of every signature kind with the backend of
+    /// stark_sign and eth_sign (native, or Python with only the python-signer feature).
+    /// The signers of the client itself are native in every build.
+    /// The error names the backend and the failing check; loading failures of the
+    /// Python modules are reported as BackendUnavailable.
+    pub fn self_test() -> Result<SelfTestReport, SelfTestError> {
+        self_test::run()
+    }
+
     pub fn get_fallback_backoff_getter() -> Arc<FallbackBackoffGetter> {
         Arc::new(FallbackBackoffGetter::default())
     }
@@ -22,3 +22,212 @@ impl Error for ResponseError {
         }
     }
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StarkError {
+    UnknownNetwork(usize),
+    /// A market registry of network actual was used to sign for network expected.
+    NetworkMismatch { expected: usize, actual: usize },
+    UnknownMarket(String),
+    UnknownAsset(String),
+    InvalidNumber(String),
+    InvalidHex(String),
+    /// Not a 20-byte Ethereum address.
+    InvalidAddress(String),
+    Quantums(QuantumsError),
+    OutOfRange { field: &'static str, value: String },
+    InvalidPrivateKey,
+    MessageNotSignable,
+    InvalidSignature(String),
+    SignerNotConfigured,
+    SignerRejected(String),
+    /// Signing panicked, e.g. in a worker thread of sign_orders.
+    SignerPanicked(String),
+}
+
+impl fmt::Display for StarkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StarkError::UnknownNetwork(network_id) => {
+                write!(f, "Unknown network ID or unknown collateral asset for network: {}", network_id)
+            }
+            StarkError::NetworkMismatch { expected, actual } => {
+                write!(f, "Market registry is of network {} but signing for network {}", actual, expected)
+            }
+            StarkError::UnknownMarket(market) => write!(f, "Unknown market: {}", market),
+            StarkError::UnknownAsset(asset) => write!(f, "Unknown asset: {}", asset),
+            StarkError::InvalidNumber(value) => write!(f, "Invalid number: {}", value),
+            StarkError::InvalidHex(value) => write!(f, "Invalid hex string: {}", value),
+            StarkError::InvalidAddress(value) => write!(f, "Invalid Ethereum address: {}", value),
+            StarkError::Quantums(error) => write!(f, "{}", error),
+            StarkError::OutOfRange { field, value } => {
+                write!(f, "Value {} is out of range for field {}", value, field)
+            }
+            StarkError::InvalidPrivateKey => write!(f, "Invalid STARK private key"),
+            StarkError::MessageNotSignable => write!(f, "Message not signable"),
+            StarkError::InvalidSignature(signature) => write!(f, "Invalid STARK signature: {}", signature),
+            StarkError::SignerNotConfigured => write!(f, "No STARK signer configured"),
+            StarkError::SignerRejected(reason) => write!(f, "STARK signer rejected request: {}", reason),
+            StarkError::SignerPanicked(reason) => write!(f, "STARK signing panicked: {}", reason),
+        }
+    }
+}
+
+impl Error for StarkError {}
+
+impl From<QuantumsError> for StarkError {
+    fn from(error: QuantumsError) -> StarkError {
+        StarkError::Quantums(error)
+    }
+}
+
+/// A human amount that cannot be converted to quantums, or is not a multiple of the
+/// step of its market.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuantumsError {
+    InvalidNumber { field: &'static str, value: String },
+    NotMultipleOfStep { field: &'static str, value: String, step: String },
+    NotMultipleOfQuantum { field: &'static str, value: String, quantum_size: String },
+    OutOfRange { field: &'static str, value: String },
+}
+
+impl fmt::Display for QuantumsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuantumsError::InvalidNumber { field, value } => write!(f, "{} {} is not a number", field, value),
+            QuantumsError::NotMultipleOfStep { field, value, step } => {
+                write!(f, "{} {} is not a multiple of step {}", field, value, step)
+            }
+            QuantumsError::NotMultipleOfQuantum { field, value, quantum_size } => write!(
+                f,
+                "{} {} is not a multiple of the quantum size {}",
+                field, value, quantum_size
+            ),
+            QuantumsError::OutOfRange { field, value } => write!(f, "{} {} is out of range", field, value),
+        }
+    }
+}
+
+impl Error for QuantumsError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EthError {
+    InvalidPrivateKey,
+    InvalidSignature(String),
+    SignerAddressMismatch { expected: String, actual: String },
+    NoSignerAddress,
+    SignerNotConfigured,
+    SignerRejected(String),
+}
+
+impl fmt::Display for EthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EthError::InvalidPrivateKey => write!(f, "Invalid Ethereum private key"),
+            EthError::InvalidSignature(signature) => write!(f, "Invalid signature: {}", signature),
+            EthError::SignerAddressMismatch { expected, actual } => write!(
+                f,
+                "signer_address is {} but Ethereum key corresponds to address {}",
+                expected, actual
+            ),
+            EthError::NoSignerAddress => write!(f, "Must set ethereum_address or a default account"),
+            EthError::SignerNotConfigured => write!(f, "No Ethereum signer configured"),
+            EthError::SignerRejected(reason) => write!(f, "Ethereum signer rejected request: {}", reason),
+        }
+    }
+}
+
+impl Error for EthError {}
+
+/// Failure to load the Python signing modules of the python-signer feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PythonSignerError {
+    ImportFailed { module: &'static str, reason: String },
+    MissingFunction { module: &'static str, function: &'static str },
+}
+
+impl fmt::Display for PythonSignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PythonSignerError::ImportFailed { module, reason } => {
+                write!(f, "Cannot import Python module {}: {}", module, reason)
+            }
+            PythonSignerError::MissingFunction { module, function } => {
+                write!(f, "Python module {} has no function {}", module, function)
+            }
+        }
+    }
+}
+
+impl Error for PythonSignerError {}
+
+/// A signature kind that failed DydxClient::self_test on the active signing backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelfTestError {
+    BackendUnavailable { backend: &'static str, reason: String },
+    SignFailed { backend: &'static str, check: &'static str, reason: String },
+    WrongSignature { backend: &'static str, check: &'static str, expected: String, actual: String },
+    VerifyFailed { backend: &'static str, check: &'static str, reason: String },
+}
+
+impl fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelfTestError::BackendUnavailable { backend, reason } => {
+                write!(f, "{} signing backend unavailable: {}", backend, reason)
+            }
+            SelfTestError::SignFailed { backend, check, reason } => {
+                write!(f, "{} signing backend failed {}: {}", backend, check, reason)
+            }
+            SelfTestError::WrongSignature { backend, check, expected, actual } => write!(
+                f,
+                "{} signing backend returned a wrong {} signature: expected {}, got {}",
+                backend, check, expected, actual
+            ),
+            SelfTestError::VerifyFailed { backend, check, reason } => {
+                write!(f, "{} signing backend {} signature does not verify: {}", backend, check, reason)
+            }
+        }
+    }
+}
+
+impl Error for SelfTestError {}
+
+/// A failure of the WebSocket connection of the ws module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WsError {
+    InvalidUrl(String),
+    Io(String),
+    Tls(String),
+    Handshake(String),
+    Protocol(String),
+    InvalidMessage { reason: String, message: String },
+    /// Nothing, not even a ping, was received for this long.
+    HeartbeatTimeout(std::time::Duration),
+    Closed,
+}
+
+impl fmt::Display for WsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WsError::InvalidUrl(url) => write!(f, "Invalid WebSocket URL: {}", url),
+            WsError::Io(reason) => write!(f, "WebSocket I/O error: {}", reason),
+            WsError::Tls(reason) => write!(f, "WebSocket TLS error: {}", reason),
+            WsError::Handshake(reason) => write!(f, "WebSocket handshake failed: {}", reason),
+            WsError::Protocol(reason) => write!(f, "WebSocket protocol error: {}", reason),
+            WsError::InvalidMessage { reason, message } => {
+                write!(f, "Invalid WebSocket message ({}): {}", reason, message)
+            }
+            WsError::HeartbeatTimeout(timeout) => write!(f, "No WebSocket frame received for {:?}", timeout),
+            WsError::Closed => write!(f, "WebSocket connection closed"),
+        }
+    }
+}
+
+impl Error for WsError {}
+
+impl From<std::io::Error> for WsError {
+    fn from(error: std::io::Error) -> WsError {
+        WsError::Io(error.to_string())
+    }
+}
@@ -0,0 +1,6 @@
+pub mod eth_private_action;
+pub mod key_derivation;
+pub mod onboarding_action;
+pub mod sign_off_chain_action;
+pub mod signers;
+pub mod util;
@@ -0,0 +1,107 @@
+// Ported from eth_signing/eth_prive_action.py.
+
+use super::sign_off_chain_action::SignOffChainAction;
+use super::signers::EthSigner;
+use super::util::*;
+use crate::error::EthError;
+use crate::Result;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub const EIP712_ETH_PRIVATE_ACTION_STRUCT_STRING: &str =
+    "dYdX(string method,string requestPath,string body,string timestamp)";
+
+pub struct SignEthPrivateAction {
+    signer: Option<Arc<dyn EthSigner>>,
+    network_id: usize,
+}
+
+impl SignOffChainAction for SignEthPrivateAction {
+    fn network_id(&self) -> usize {
+        self.network_id
+    }
+
+    fn get_eip712_struct(&self) -> Value {
+        json!([
+            {"type": "string", "name": "method"},
+            {"type": "string", "name": "requestPath"},
+            {"type": "string", "name": "body"},
+            {"type": "string", "name": "timestamp"},
+        ])
+    }
+}
+
+impl SignEthPrivateAction {
+    pub fn new(signer: Arc<dyn EthSigner>, network_id: usize) -> SignEthPrivateAction {
+        SignEthPrivateAction {
+            signer: Some(signer),
+            network_id,
+        }
+    }
+
+    /// An action that can only hash and verify messages, e.g. to have them
+    /// signed elsewhere.
+    pub fn without_signer(network_id: usize) -> SignEthPrivateAction {
+        SignEthPrivateAction {
+            signer: None,
+            network_id,
+        }
+    }
+
+    pub async fn sign(
+        &self,
+        signer_address: &str,
+        method: &str,
+        request_path: &str,
+        body: &str,
+        timestamp: &str,
+    ) -> Result<String> {
+        let eip712_message = self.get_eth_private_eip712_message(method, request_path, body, timestamp);
+        let message_hash = self.get_hash(method, request_path, body, timestamp);
+        let signer = self.signer.as_ref().ok_or(EthError::SignerNotConfigured)?;
+        signer.sign(&eip712_message, &message_hash, Some(signer_address)).await
+    }
+
+    pub fn get_eth_private_eip712_message(
+        &self,
+        method: &str,
+        request_path: &str,
+        body: &str,
+        timestamp: &str,
+    ) -> Value {
+        self.get_eip712_message(json!({
+            "method": method,
+            "requestPath": request_path,
+            "body": body,
+            "timestamp": timestamp,
+        }))
+    }
+
+    pub fn verify(
+        &self,
+        typed_signature: &str,
+        expected_signer_address: &str,
+        method: &str,
+        request_path: &str,
+        body: &str,
+        timestamp: &str,
+    ) -> std::result::Result<bool, EthError> {
+        let message_hash = self.get_hash(method, request_path, body, timestamp);
+        let signer = ec_recover_typed_signature(&message_hash, typed_signature)?;
+        Ok(addresses_are_equal(&signer, expected_signer_address))
+    }
+
+    pub fn get_hash(&self, method: &str, request_path: &str, body: &str, timestamp: &str) -> [u8; 32] {
+        let struct_hash = keccak256(
+            &[
+                hash_string(EIP712_ETH_PRIVATE_ACTION_STRUCT_STRING),
+                hash_string(method),
+                hash_string(request_path),
+                hash_string(body),
+                hash_string(timestamp),
+            ]
+            .concat(),
+        );
+        self.get_eip712_hash(&struct_hash)
+    }
+}
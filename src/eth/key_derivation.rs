@@ -0,0 +1,62 @@
+// Ported from the key derivation functions in eth_signing/eth_sign.py.
+
+use super::util::{keccak256, strip_hex_prefix};
+use crate::error::EthError;
+use num_bigint::BigUint;
+use num_traits::Num;
+
+/// keccak256 of a hex string packed as a solidity uint256, i.e.
+/// Web3.solidityKeccak(['uint256'], [int(value, 16)]).
+///
+/// Like web3, values wider than 256 bits (e.g. a full typed signature) are
+/// hashed over all of their bytes rather than rejected.
+fn solidity_keccak_uint256(value: &str) -> Result<[u8; 32], EthError> {
+    let value_int = BigUint::from_str_radix(strip_hex_prefix(value), 16)
+        .map_err(|_| EthError::InvalidSignature(value.to_string()))?;
+    let bytes = value_int.to_bytes_be();
+    let mut packed = vec![0u8; 32usize.saturating_sub(bytes.len())];
+    packed.extend_from_slice(&bytes);
+    Ok(keccak256(&packed))
+}
+
+/// Derive the STARK private key from a signature of the key derivation action.
+pub fn derive_stark_private_key(signature: &str) -> Result<String, EthError> {
+    let hashed_signature = solidity_keccak_uint256(signature)?;
+    let private_key_int = BigUint::from_bytes_be(&hashed_signature) >> 5u8;
+    Ok(format!("0x{}", private_key_int.to_str_radix(16)))
+}
+
+/// Derive the API secret from the r value of an onboarding signature.
+pub fn derive_secret(r_hex: &str) -> Result<String, EthError> {
+    let hashed_r_bytes = solidity_keccak_uint256(r_hex)?;
+    Ok(base64::encode_config(&hashed_r_bytes[..30], base64::URL_SAFE))
+}
+
+/// Derive the API passphrase from the s value of an onboarding signature.
+pub fn derive_passphrase(s_hex: &str) -> Result<String, EthError> {
+    let hashed_s_bytes = solidity_keccak_uint256(s_hex)?;
+    Ok(base64::encode_config(&hashed_s_bytes[16..31], base64::URL_SAFE))
+}
+
+/// Derive the API key, formatted as a UUID, from the s value of an onboarding signature.
+pub fn derive_key(s_hex: &str) -> Result<String, EthError> {
+    let hashed_s_bytes = solidity_keccak_uint256(s_hex)?;
+    let key_hex = hex::encode(&hashed_s_bytes[..16]);
+    Ok([
+        &key_hex[..8],
+        &key_hex[8..12],
+        &key_hex[12..16],
+        &key_hex[16..20],
+        &key_hex[20..],
+    ]
+    .join("-"))
+}
+
+/// Split a typed signature into its r and s hex values, without 0x prefix.
+pub fn signature_r_s(signature: &str) -> Result<(&str, &str), EthError> {
+    let stripped = strip_hex_prefix(signature);
+    if stripped.len() < 128 || !stripped.is_ascii() {
+        return Err(EthError::InvalidSignature(signature.to_string()));
+    }
+    Ok((&stripped[..64], &stripped[64..128]))
+}
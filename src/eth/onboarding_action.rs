@@ -0,0 +1,111 @@
+// Ported from eth_signing/onboarding_action.py.
+
+use super::sign_off_chain_action::SignOffChainAction;
+use super::signers::EthSigner;
+use super::util::*;
+use crate::constants::NETWORK_ID_MAINNET;
+use crate::error::EthError;
+use crate::Result;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+// On mainnet, include an extra onlySignOn parameter.
+pub const EIP712_ONBOARDING_ACTION_STRUCT_STRING: &str = "dYdX(string action,string onlySignOn)";
+pub const EIP712_ONBOARDING_ACTION_STRUCT_STRING_TESTNET: &str = "dYdX(string action)";
+
+pub const ONLY_SIGN_ON_DOMAIN_MAINNET: &str = "https://trade.dydx.exchange";
+
+pub struct SignOnboardingAction {
+    signer: Option<Arc<dyn EthSigner>>,
+    network_id: usize,
+}
+
+impl SignOffChainAction for SignOnboardingAction {
+    fn network_id(&self) -> usize {
+        self.network_id
+    }
+
+    fn get_eip712_struct(&self) -> Value {
+        // On mainnet, include an extra onlySignOn parameter.
+        if self.network_id == NETWORK_ID_MAINNET {
+            json!([
+                {"type": "string", "name": "action"},
+                {"type": "string", "name": "onlySignOn"},
+            ])
+        } else {
+            json!([
+                {"type": "string", "name": "action"},
+            ])
+        }
+    }
+}
+
+impl SignOnboardingAction {
+    pub fn new(signer: Arc<dyn EthSigner>, network_id: usize) -> SignOnboardingAction {
+        SignOnboardingAction {
+            signer: Some(signer),
+            network_id,
+        }
+    }
+
+    /// An action that can only hash and verify messages, e.g. to have them
+    /// signed elsewhere.
+    pub fn without_signer(network_id: usize) -> SignOnboardingAction {
+        SignOnboardingAction {
+            signer: None,
+            network_id,
+        }
+    }
+
+    pub async fn sign(&self, signer_address: &str, action: &str) -> Result<String> {
+        let eip712_message = self.get_onboarding_eip712_message(action);
+        let message_hash = self.get_hash(action);
+        let signer = self.signer.as_ref().ok_or(EthError::SignerNotConfigured)?;
+        signer.sign(&eip712_message, &message_hash, Some(signer_address)).await
+    }
+
+    pub fn get_onboarding_eip712_message(&self, action: &str) -> Value {
+        let mut message = json!({ "action": action });
+
+        // On mainnet, include an extra onlySignOn parameter.
+        if self.network_id == NETWORK_ID_MAINNET {
+            message["onlySignOn"] = json!(ONLY_SIGN_ON_DOMAIN_MAINNET);
+        }
+
+        self.get_eip712_message(message)
+    }
+
+    pub fn verify(
+        &self,
+        typed_signature: &str,
+        expected_signer_address: &str,
+        action: &str,
+    ) -> std::result::Result<bool, EthError> {
+        let message_hash = self.get_hash(action);
+        let signer = ec_recover_typed_signature(&message_hash, typed_signature)?;
+        Ok(addresses_are_equal(&signer, expected_signer_address))
+    }
+
+    pub fn get_hash(&self, action: &str) -> [u8; 32] {
+        // On mainnet, include an extra onlySignOn parameter.
+        let struct_hash = if self.network_id == NETWORK_ID_MAINNET {
+            keccak256(
+                &[
+                    hash_string(EIP712_ONBOARDING_ACTION_STRUCT_STRING),
+                    hash_string(action),
+                    hash_string(ONLY_SIGN_ON_DOMAIN_MAINNET),
+                ]
+                .concat(),
+            )
+        } else {
+            keccak256(
+                &[
+                    hash_string(EIP712_ONBOARDING_ACTION_STRUCT_STRING_TESTNET),
+                    hash_string(action),
+                ]
+                .concat(),
+            )
+        };
+        self.get_eip712_hash(&struct_hash)
+    }
+}
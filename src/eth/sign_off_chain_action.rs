@@ -0,0 +1,85 @@
+// Ported from eth_signing/sign_off_chain_action.py.
+
+use super::util::*;
+use crate::error::EthError;
+use num_bigint::BigUint;
+use serde_json::{json, Value};
+
+pub const DOMAIN: &str = "dYdX";
+pub const VERSION: &str = "1.0";
+pub const EIP712_DOMAIN_STRING_NO_CONTRACT: &str =
+    "EIP712Domain(string name,string version,uint256 chainId)";
+pub const EIP712_STRUCT_NAME: &str = "dYdX";
+
+pub trait SignOffChainAction {
+    fn network_id(&self) -> usize;
+
+    fn get_eip712_struct(&self) -> Value;
+
+    fn get_eip712_struct_name(&self) -> &'static str {
+        EIP712_STRUCT_NAME
+    }
+
+    fn get_eip712_message(&self, message: Value) -> Value {
+        let struct_name = self.get_eip712_struct_name();
+        json!({
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "version", "type": "string"},
+                    {"name": "chainId", "type": "uint256"},
+                ],
+                struct_name: self.get_eip712_struct(),
+            },
+            "domain": {
+                "name": DOMAIN,
+                "version": VERSION,
+                "chainId": self.network_id(),
+            },
+            "primaryType": struct_name,
+            "message": message,
+        })
+    }
+
+    /// Check that the typed signature of the message hash was produced by the
+    /// expected signer.
+    fn check_signature(
+        &self,
+        message_hash: &[u8; 32],
+        typed_signature: &str,
+        expected_signer_address: &str,
+    ) -> Result<(), EthError> {
+        let signer = ec_recover_typed_signature(message_hash, typed_signature)?;
+        if !addresses_are_equal(&signer, expected_signer_address) {
+            return Err(EthError::SignerAddressMismatch {
+                expected: expected_signer_address.to_string(),
+                actual: signer,
+            });
+        }
+        Ok(())
+    }
+
+    fn get_eip712_hash(&self, struct_hash: &[u8; 32]) -> [u8; 32] {
+        keccak256(&[&[0x19, 0x01][..], &self.get_domain_hash(), struct_hash].concat())
+    }
+
+    fn get_domain_hash(&self) -> [u8; 32] {
+        keccak256(
+            &[
+                hash_string(EIP712_DOMAIN_STRING_NO_CONTRACT),
+                hash_string(DOMAIN),
+                hash_string(VERSION),
+                uint256(&BigUint::from(self.network_id())),
+            ]
+            .concat(),
+        )
+    }
+}
+
+/// Left-pad an unsigned integer to 32 bytes, as solidityKeccak does for uint256.
+pub fn uint256(value: &BigUint) -> [u8; 32] {
+    let bytes = value.to_bytes_be();
+    let mut padded = [0u8; 32];
+    padded[32 - bytes.len()..].copy_from_slice(&bytes);
+    padded
+}
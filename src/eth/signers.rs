@@ -0,0 +1,209 @@
+use super::util::*;
+use crate::constants::SIGNATURE_TYPE_NO_PREPEND;
+use crate::error::EthError;
+use crate::Result;
+use k256::ecdsa::SigningKey;
+use serde_json::{json, Value};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub type TypedSignatureFuture<'a> = Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>>;
+
+pub trait EthSigner: Send + Sync + fmt::Debug {
+    /// Sign an EIP-712 message.
+    ///
+    /// Returns a "typed signature" whose last byte indicates whether the hash
+    /// was prepended before being signed.
+    fn sign<'a>(
+        &'a self,
+        eip712_message: &'a Value,
+        message_hash: &'a [u8; 32],
+        opt_signer_address: Option<&'a str>,
+    ) -> TypedSignatureFuture<'a>;
+}
+
+pub struct SignWithKey {
+    pub address: String,
+    private_key: SigningKey,
+}
+
+impl SignWithKey {
+    pub fn new(private_key: &str) -> std::result::Result<SignWithKey, EthError> {
+        let bytes = hex::decode(strip_hex_prefix(private_key)).map_err(|_| EthError::InvalidPrivateKey)?;
+        let private_key = SigningKey::from_slice(&bytes).map_err(|_| EthError::InvalidPrivateKey)?;
+        Ok(SignWithKey {
+            address: public_key_to_address(private_key.verifying_key()),
+            private_key,
+        })
+    }
+
+    /// Sign an EIP-712 message hash directly with the key.
+    pub fn sign_hash(
+        &self,
+        message_hash: &[u8; 32],
+        opt_signer_address: Option<&str>,
+    ) -> std::result::Result<String, EthError> {
+        if let Some(signer_address) = opt_signer_address {
+            if !addresses_are_equal(signer_address, &self.address) {
+                return Err(EthError::SignerAddressMismatch {
+                    expected: signer_address.to_string(),
+                    actual: self.address.clone(),
+                });
+            }
+        }
+        let (signature, recovery_id) = self
+            .private_key
+            .sign_prehash_recoverable(message_hash)
+            .map_err(|e| EthError::InvalidSignature(e.to_string()))?;
+        let raw_signature = format!(
+            "0x{}{:02x}",
+            hex::encode(signature.to_bytes()),
+            27 + recovery_id.to_byte()
+        );
+        create_typed_signature(&raw_signature, SIGNATURE_TYPE_NO_PREPEND)
+    }
+}
+
+/// A SignWithKey from private_key or, if the key is invalid, a signer failing every
+/// signature with the error, so that constructors that cannot fail report it on the
+/// first signature instead of panicking.
+pub(crate) fn key_signer_or_failing(private_key: &str) -> Arc<dyn EthSigner> {
+    match SignWithKey::new(private_key) {
+        Ok(signer) => Arc::new(signer),
+        Err(error) => Arc::new(FailingSigner(error)),
+    }
+}
+
+#[derive(Debug)]
+struct FailingSigner(EthError);
+
+impl EthSigner for FailingSigner {
+    fn sign<'a>(
+        &'a self,
+        _eip712_message: &'a Value,
+        _message_hash: &'a [u8; 32],
+        _opt_signer_address: Option<&'a str>,
+    ) -> TypedSignatureFuture<'a> {
+        Box::pin(async move { Err(self.0.clone().into()) })
+    }
+}
+
+// Never print the private key.
+impl fmt::Debug for SignWithKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignWithKey").field("address", &self.address).finish()
+    }
+}
+
+impl EthSigner for SignWithKey {
+    fn sign<'a>(
+        &'a self,
+        _eip712_message: &'a Value,
+        message_hash: &'a [u8; 32],
+        opt_signer_address: Option<&'a str>,
+    ) -> TypedSignatureFuture<'a> {
+        Box::pin(async move { Ok(self.sign_hash(message_hash, opt_signer_address)?) })
+    }
+}
+
+/// Signs through the eth_signTypedData_v4 method of a JSON-RPC endpoint,
+/// e.g. a wallet or a node holding unlocked accounts.
+#[derive(Debug, Clone)]
+pub struct SignWithJsonRpc {
+    url: String,
+    default_account: Option<String>,
+    client: reqwest::Client,
+}
+
+impl SignWithJsonRpc {
+    pub fn new(url: &str, default_account: Option<&str>) -> SignWithJsonRpc {
+        SignWithJsonRpc {
+            url: url.to_string(),
+            default_account: default_account.map(str::to_string),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn sign_typed_data(&self, signer_address: &str, eip712_message: &Value) -> Result<String> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_signTypedData_v4",
+            "params": [signer_address, eip712_message.to_string()],
+        });
+        let response: Value = self.client.post(&self.url).json(&request).send().await?.json().await?;
+        if let Some(error) = response.get("error") {
+            return Err(Box::new(EthError::SignerRejected(error.to_string())));
+        }
+        match response.get("result").and_then(Value::as_str) {
+            Some(raw_signature) => Ok(raw_signature.to_string()),
+            None => Err(Box::new(EthError::SignerRejected(response.to_string()))),
+        }
+    }
+}
+
+impl EthSigner for SignWithJsonRpc {
+    fn sign<'a>(
+        &'a self,
+        eip712_message: &'a Value,
+        _message_hash: &'a [u8; 32],
+        opt_signer_address: Option<&'a str>,
+    ) -> TypedSignatureFuture<'a> {
+        Box::pin(async move {
+            let signer_address = opt_signer_address
+                .or(self.default_account.as_deref())
+                .ok_or(EthError::NoSignerAddress)?;
+            let raw_signature = self.sign_typed_data(signer_address, eip712_message).await?;
+            Ok(create_typed_signature(&raw_signature, SIGNATURE_TYPE_NO_PREPEND)?)
+        })
+    }
+}
+
+/// What a SignWithCallback callback is asked to sign.
+#[derive(Debug, Clone)]
+pub struct EthSignRequest {
+    pub eip712_message: Value,
+    pub message_hash: [u8; 32],
+    pub signer_address: Option<String>,
+}
+
+pub type EthSignCallback =
+    Arc<dyn Fn(EthSignRequest) -> Pin<Box<dyn Future<Output = Result<String>> + Send>> + Send + Sync>;
+
+/// Signs through a caller-supplied async callback, which returns the raw
+/// 65-byte signature as hex, as wallets do for eth_signTypedData_v4.
+#[derive(Clone)]
+pub struct SignWithCallback {
+    callback: EthSignCallback,
+}
+
+impl SignWithCallback {
+    pub fn new(callback: EthSignCallback) -> SignWithCallback {
+        SignWithCallback { callback }
+    }
+}
+
+impl fmt::Debug for SignWithCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignWithCallback").finish_non_exhaustive()
+    }
+}
+
+impl EthSigner for SignWithCallback {
+    fn sign<'a>(
+        &'a self,
+        eip712_message: &'a Value,
+        message_hash: &'a [u8; 32],
+        opt_signer_address: Option<&'a str>,
+    ) -> TypedSignatureFuture<'a> {
+        let request = EthSignRequest {
+            eip712_message: eip712_message.clone(),
+            message_hash: *message_hash,
+            signer_address: opt_signer_address.map(str::to_string),
+        };
+        let signature = (self.callback)(request);
+        Box::pin(async move { Ok(create_typed_signature(&signature.await?, SIGNATURE_TYPE_NO_PREPEND)?) })
+    }
+}
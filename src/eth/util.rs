@@ -0,0 +1,122 @@
+use crate::constants::*;
+use crate::error::EthError;
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use sha3::{Digest, Keccak256};
+
+const PREPEND_DEC: &str = "\x19Ethereum Signed Message:\n32";
+const PREPEND_HEX: &str = "\x19Ethereum Signed Message:\n\x20";
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+pub fn hash_string(input: &str) -> [u8; 32] {
+    keccak256(input.as_bytes())
+}
+
+pub fn is_valid_sig_type(sig_type: u8) -> bool {
+    matches!(
+        sig_type,
+        SIGNATURE_TYPE_DECIMAL | SIGNATURE_TYPE_HEXADECIMAL | SIGNATURE_TYPE_NO_PREPEND
+    )
+}
+
+pub fn create_typed_signature(signature: &str, sig_type: u8) -> Result<String, EthError> {
+    if !is_valid_sig_type(sig_type) {
+        return Err(EthError::InvalidSignature(format!("Invalid signature type: {}", sig_type)));
+    }
+    Ok(format!("{}0{}", fix_raw_signature(signature)?, sig_type))
+}
+
+/// Accept either a typed signature or a raw 65-byte signature, as returned
+/// by wallets for eth_signTypedData_v4, and return the typed signature.
+pub fn to_typed_signature(signature: &str) -> Result<String, EthError> {
+    if !is_hex(strip_hex_prefix(signature)) {
+        return Err(EthError::InvalidSignature(signature.to_string()));
+    }
+    match strip_hex_prefix(signature).len() {
+        130 => create_typed_signature(signature, SIGNATURE_TYPE_NO_PREPEND),
+        132 => Ok(format!("0x{}", strip_hex_prefix(signature).to_lowercase())),
+        _ => Err(EthError::InvalidSignature(signature.to_string())),
+    }
+}
+
+pub fn fix_raw_signature(signature: &str) -> Result<String, EthError> {
+    let stripped = strip_hex_prefix(signature);
+
+    // Checked before slicing, which would panic inside a multi-byte character.
+    if stripped.len() != 130 || !is_hex(stripped) {
+        return Err(EthError::InvalidSignature(signature.to_string()));
+    }
+
+    let rs = &stripped[..128];
+    match &stripped[128..130] {
+        "00" => Ok(format!("0x{}1b", rs)),
+        "01" => Ok(format!("0x{}1c", rs)),
+        "1b" | "1c" => Ok(format!("0x{}", stripped)),
+        _ => Err(EthError::InvalidSignature(signature.to_string())),
+    }
+}
+
+fn is_hex(value: &str) -> bool {
+    value.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+pub fn ec_recover_typed_signature(hash_val: &[u8; 32], typed_signature: &str) -> Result<String, EthError> {
+    let invalid = || EthError::InvalidSignature(typed_signature.to_string());
+    let stripped = strip_hex_prefix(typed_signature);
+    if stripped.len() != 66 * 2 {
+        return Err(invalid());
+    }
+    let bytes = hex::decode(stripped).map_err(|_| invalid())?;
+
+    let prepended_hash = match bytes[65] {
+        SIGNATURE_TYPE_NO_PREPEND => *hash_val,
+        SIGNATURE_TYPE_DECIMAL => keccak256(&[PREPEND_DEC.as_bytes(), hash_val].concat()),
+        SIGNATURE_TYPE_HEXADECIMAL => keccak256(&[PREPEND_HEX.as_bytes(), hash_val].concat()),
+        _ => return Err(invalid()),
+    };
+
+    let signature = Signature::from_slice(&bytes[..64]).map_err(|_| invalid())?;
+    let v = match bytes[64] {
+        27 | 28 => bytes[64] - 27,
+        0 | 1 => bytes[64],
+        _ => return Err(invalid()),
+    };
+    let recovery_id = RecoveryId::from_byte(v).ok_or_else(invalid)?;
+    let verifying_key = VerifyingKey::recover_from_prehash(&prepended_hash, &signature, recovery_id)
+        .map_err(|_| invalid())?;
+    Ok(public_key_to_address(&verifying_key))
+}
+
+/// The EIP-55 checksummed address of a secp256k1 public key.
+pub fn public_key_to_address(verifying_key: &VerifyingKey) -> String {
+    let encoded = verifying_key.to_encoded_point(false);
+    let hash = keccak256(&encoded.as_bytes()[1..]);
+    to_checksum_address(&hex::encode(&hash[12..]))
+}
+
+pub fn to_checksum_address(address: &str) -> String {
+    let lower = strip_hex_prefix(address).to_lowercase();
+    let hash = hex::encode(keccak256(lower.as_bytes()));
+    let checksummed: String = lower
+        .chars()
+        .zip(hash.chars())
+        .map(|(c, h)| if h.to_digit(16).unwrap() >= 8 { c.to_ascii_uppercase() } else { c })
+        .collect();
+    format!("0x{}", checksummed)
+}
+
+// ============ Byte Helpers ============
+
+pub fn strip_hex_prefix(input: &str) -> &str {
+    input.strip_prefix("0x").unwrap_or(input)
+}
+
+pub fn addresses_are_equal(address_one: &str, address_two: &str) -> bool {
+    if address_one.is_empty() || address_two.is_empty() {
+        return false;
+    }
+
+    strip_hex_prefix(address_one).to_lowercase() == strip_hex_prefix(address_two).to_lowercase()
+}
@@ -1,12 +1,24 @@
 pub mod constants;
 pub mod dydx_client;
 pub mod error;
+pub mod eth;
 pub mod helper;
 pub mod modules;
+pub mod starkex;
 pub mod types;
 pub mod retry;
+pub mod ws;
 
 pub use dydx_client::ClientOptions;
 pub use dydx_client::DydxClient;
 pub use error::ResponseError;
+pub use error::EthError;
+pub use error::PythonSignerError;
+pub use error::QuantumsError;
+pub use error::SelfTestError;
+pub use error::StarkError;
+pub use error::WsError;
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[cfg(not(any(feature = "native-signer", feature = "python-signer")))]
+compile_error!("either feature \"native-signer\" or \"python-signer\" must be enabled");
@@ -1,6 +1,15 @@
+pub mod api_key_signer;
 pub mod eth_private;
 pub mod eth_sign;
+#[cfg(feature = "python-signer")]
+pub mod eth_sign_py;
 pub mod onboarding;
 pub mod private;
 pub mod public;
+pub mod self_test;
+pub mod signed_request;
+#[cfg(feature = "python-signer")]
+pub mod python_signer;
 pub mod stark_sign;
+#[cfg(feature = "python-signer")]
+pub mod stark_sign_py;
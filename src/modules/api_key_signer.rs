@@ -0,0 +1,62 @@
+//! Authentication of private endpoints with an API key: an HMAC-SHA256, keyed with the
+//! API secret, over timestamp + method + request path + body.
+
+use super::super::types::ApiKeyCredentials;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+#[derive(Debug, Clone)]
+pub struct ApiKeySigner<'a> {
+    credentials: ApiKeyCredentials<'a>,
+}
+
+impl<'a> ApiKeySigner<'a> {
+    pub fn new(credentials: ApiKeyCredentials<'a>) -> ApiKeySigner<'a> {
+        ApiKeySigner { credentials }
+    }
+
+    /// The DYDX-SIGNATURE of a request. request_path includes the query, e.g.
+    /// "/v3/orders?market=BTC-USD"; a body of None or "{}" is not signed.
+    ///
+    /// Panics if the API secret is not URL-safe base64.
+    pub fn sign(&self, request_path: &str, method: &str, iso_timestamp: &str, body: Option<&str>) -> String {
+        let mut message = String::from(iso_timestamp) + method + request_path;
+
+        if let Some(local_var) = body {
+            if local_var != "{}" {
+                message.push_str(local_var);
+            }
+        }
+
+        let secret = base64::decode_config(self.credentials.secret, base64::URL_SAFE)
+            .expect("API secret is not URL-safe base64");
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(&secret).unwrap();
+        mac.update(message.as_bytes());
+        let code = mac.finalize().into_bytes();
+        base64::encode(code)
+    }
+
+    /// DYDX-SIGNATURE, DYDX-TIMESTAMP, DYDX-API-KEY and DYDX-PASSPHRASE of a request sent now.
+    pub fn headers(&self, method: &str, request_path: &str, body: Option<&str>) -> Vec<(&'static str, String)> {
+        let iso_timestamp = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+        self.headers_at(method, request_path, body, &iso_timestamp)
+    }
+
+    /// Like headers, for a request timestamped iso_timestamp, e.g. "2023-01-01T00:00:00.000Z".
+    pub fn headers_at(
+        &self,
+        method: &str,
+        request_path: &str,
+        body: Option<&str>,
+        iso_timestamp: &str,
+    ) -> Vec<(&'static str, String)> {
+        vec![
+            ("DYDX-SIGNATURE", self.sign(request_path, method, iso_timestamp, body)),
+            ("DYDX-TIMESTAMP", iso_timestamp.to_string()),
+            ("DYDX-API-KEY", self.credentials.key.to_string()),
+            ("DYDX-PASSPHRASE", self.credentials.passphrase.to_string()),
+        ]
+    }
+}
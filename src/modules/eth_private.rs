@@ -1,9 +1,13 @@
 pub use super::super::types::*;
 use super::super::{ResponseError, Result};
-use super::eth_sign::*;
+use crate::eth::eth_private_action::SignEthPrivateAction;
+use crate::eth::sign_off_chain_action::SignOffChainAction;
+use crate::eth::signers::{key_signer_or_failing, EthSigner};
+use crate::eth::util::to_typed_signature;
 use chrono::Utc;
 use http::{Method, StatusCode};
 use serde::Deserialize;
+use std::sync::Arc;
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
@@ -11,11 +15,26 @@ pub struct EthPrivate<'a> {
     client: reqwest::Client,
     host: &'a str,
     network_id: usize,
-    eth_private_key: &'a str,
+    signer: Option<Arc<dyn EthSigner>>,
 }
 
 impl EthPrivate<'_> {
+    /// An invalid eth_private_key is reported by the first signature.
     pub fn new<'a>(host: &'a str, network_id: usize, api_timeout: u64, eth_private_key: &'a str) -> EthPrivate<'a> {
+        EthPrivate::with_signer(host, network_id, api_timeout, key_signer_or_failing(eth_private_key))
+    }
+
+    pub fn with_signer(host: &str, network_id: usize, api_timeout: u64, signer: Arc<dyn EthSigner>) -> EthPrivate<'_> {
+        EthPrivate::build(host, network_id, api_timeout, Some(signer))
+    }
+
+    /// EthPrivate without an Ethereum signer, for signatures produced elsewhere
+    /// (e.g. a browser wallet) from the typed data of a signing request.
+    pub fn without_signer(host: &str, network_id: usize, api_timeout: u64) -> EthPrivate<'_> {
+        EthPrivate::build(host, network_id, api_timeout, None)
+    }
+
+    fn build(host: &str, network_id: usize, api_timeout: u64, signer: Option<Arc<dyn EthSigner>>) -> EthPrivate<'_> {
         EthPrivate {
             client: reqwest::ClientBuilder::new()
                 .timeout(Duration::from_secs(api_timeout))
@@ -23,13 +42,15 @@ impl EthPrivate<'_> {
                 .expect("Client::new()"),
             host,
             network_id,
-            eth_private_key,
+            signer,
         }
     }
 
     pub async fn recovery(&self, ethereum_address: &str) -> Result<RecoveryResponse> {
+        let signing_request = self.get_recovery_signing_request();
+        let signature = self.sign_request(ethereum_address, &signing_request).await?;
         let response = self
-            .request("recovery", Method::GET, ethereum_address)
+            .request("recovery", Method::GET, ethereum_address, &signing_request, &signature)
             .await;
         response
     }
@@ -38,8 +59,25 @@ impl EthPrivate<'_> {
         &self,
         ethereum_address: &str,
     ) -> Result<ApiKeyCredentialsResponse> {
+        let signing_request = self.get_create_api_key_signing_request();
+        let signature = self.sign_request(ethereum_address, &signing_request).await?;
+        let response = self
+            .request("api-keys", Method::POST, ethereum_address, &signing_request, &signature)
+            .await;
+        response
+    }
+
+    /// Like create_api_key, with a signature of the typed data of
+    /// get_create_api_key_signing_request.
+    pub async fn create_api_key_with_signature(
+        &self,
+        ethereum_address: &str,
+        signing_request: &EthPrivateSigningRequest,
+        signature: &str,
+    ) -> Result<ApiKeyCredentialsResponse> {
+        let signature = self.check_signature(ethereum_address, signing_request, signature)?;
         let response = self
-            .request("api-keys", Method::POST, ethereum_address)
+            .request("api-keys", Method::POST, ethereum_address, signing_request, &signature)
             .await;
         response
     }
@@ -49,32 +87,106 @@ impl EthPrivate<'_> {
         api_key: &str,
         ethereum_address: &str,
     ) -> Result<StatusCode> {
+        let signing_request = self.get_delete_api_key_signing_request(api_key);
+        let signature = self.sign_request(ethereum_address, &signing_request).await?;
         let parameter = vec![("apiKey", api_key)];
-        let response = self.delete("api-keys", ethereum_address, parameter).await;
+        let response = self
+            .delete("api-keys", ethereum_address, parameter, &signing_request, &signature)
+            .await;
+        response
+    }
+
+    /// Like delete_api_key, with a signature of the typed data of
+    /// get_delete_api_key_signing_request.
+    pub async fn delete_api_key_with_signature(
+        &self,
+        api_key: &str,
+        ethereum_address: &str,
+        signing_request: &EthPrivateSigningRequest,
+        signature: &str,
+    ) -> Result<StatusCode> {
+        let signature = self.check_signature(ethereum_address, signing_request, signature)?;
+        let parameter = vec![("apiKey", api_key)];
+        let response = self
+            .delete("api-keys", ethereum_address, parameter, signing_request, &signature)
+            .await;
         response
     }
 
+    pub fn get_recovery_signing_request(&self) -> EthPrivateSigningRequest {
+        self.signing_request(Method::GET, "/v3/recovery".to_string())
+    }
+
+    pub fn get_create_api_key_signing_request(&self) -> EthPrivateSigningRequest {
+        self.signing_request(Method::POST, "/v3/api-keys".to_string())
+    }
+
+    pub fn get_delete_api_key_signing_request(&self, api_key: &str) -> EthPrivateSigningRequest {
+        let parameters = vec![("apiKey", api_key)];
+        self.signing_request(Method::DELETE, request_path_with_query("api-keys", &parameters))
+    }
+
+    fn signing_request(&self, method: Method, request_path: String) -> EthPrivateSigningRequest {
+        let timestamp = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+        let body = "{}".to_string();
+        let typed_data =
+            self.action()
+                .get_eth_private_eip712_message(method.as_str(), &request_path, &body, &timestamp);
+        EthPrivateSigningRequest {
+            method: method.to_string(),
+            request_path,
+            body,
+            timestamp,
+            typed_data,
+        }
+    }
+
+    fn action(&self) -> SignEthPrivateAction {
+        match &self.signer {
+            Some(signer) => SignEthPrivateAction::new(signer.clone(), self.network_id),
+            None => SignEthPrivateAction::without_signer(self.network_id),
+        }
+    }
+
+    async fn sign_request(&self, ethereum_address: &str, signing_request: &EthPrivateSigningRequest) -> Result<String> {
+        self.action()
+            .sign(
+                ethereum_address,
+                &signing_request.method,
+                &signing_request.request_path,
+                &signing_request.body,
+                &signing_request.timestamp,
+            )
+            .await
+    }
+
+    /// Normalize an externally produced signature and check it was made by ethereum_address.
+    fn check_signature(
+        &self,
+        ethereum_address: &str,
+        signing_request: &EthPrivateSigningRequest,
+        signature: &str,
+    ) -> Result<String> {
+        let signature = to_typed_signature(signature)?;
+        let action = self.action();
+        let message_hash = action.get_hash(
+            &signing_request.method,
+            &signing_request.request_path,
+            &signing_request.body,
+            &signing_request.timestamp,
+        );
+        action.check_signature(&message_hash, &signature, ethereum_address)?;
+        Ok(signature)
+    }
+
     async fn request<T: for<'de> Deserialize<'de>>(
         &self,
         path: &str,
         method: Method,
         ethereum_address: &str,
+        signing_request: &EthPrivateSigningRequest,
+        signature: &str,
     ) -> Result<T> {
-        let iso_timestamp = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-
-        let request_path = format!("/v3/{}", path);
-
-        let signature = sign_private(
-            self.network_id,
-            ethereum_address,
-            method.as_str(),
-            request_path.as_str(),
-            "{}",
-            &iso_timestamp,
-            self.eth_private_key,
-        )
-        .unwrap();
-
         let url = format!("{}/v3/{}", &self.host, path);
 
         let req_builder = match method {
@@ -84,8 +196,8 @@ impl EthPrivate<'_> {
         };
 
         let req_builder = req_builder
-            .header("DYDX-SIGNATURE", signature.as_str())
-            .header("DYDX-TIMESTAMP", iso_timestamp.as_str())
+            .header("DYDX-SIGNATURE", signature)
+            .header("DYDX-TIMESTAMP", signing_request.timestamp.as_str())
             .header("DYDX-ETHEREUM-ADDRESS", ethereum_address);
 
         let response = req_builder.send().await;
@@ -119,34 +231,16 @@ impl EthPrivate<'_> {
         path: &str,
         ethereum_address: &str,
         parameters: Vec<(&str, &str)>,
+        signing_request: &EthPrivateSigningRequest,
+        signature: &str,
     ) -> Result<StatusCode> {
-        let request_path = if parameters.len() == 0 {
-            format!("/v3/{}", &path)
-        } else {
-            let request_path = format!("/v3/{}", &path);
-            let dummy_url = reqwest::Url::parse_with_params("https://example.net", &parameters);
-            format!("{}?{}", request_path, dummy_url.unwrap().query().unwrap())
-        };
-        let iso_timestamp = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-
-        let signature = sign_private(
-            self.network_id,
-            ethereum_address,
-            "DELETE",
-            request_path.as_str(),
-            "{}",
-            &iso_timestamp,
-            self.eth_private_key,
-        )
-        .unwrap();
-
         let url = format!("{}/v3/{}", &self.host, path);
 
         let req_builder = self.client.delete(url);
 
         let req_builder = req_builder
-            .header("DYDX-SIGNATURE", signature.as_str())
-            .header("DYDX-TIMESTAMP", iso_timestamp.as_str())
+            .header("DYDX-SIGNATURE", signature)
+            .header("DYDX-TIMESTAMP", signing_request.timestamp.as_str())
             .header("DYDX-ETHEREUM-ADDRESS", ethereum_address)
             .query(&parameters);
 
@@ -155,3 +249,13 @@ impl EthPrivate<'_> {
         Ok(response.unwrap().status())
     }
 }
+
+fn request_path_with_query(path: &str, parameters: &[(&str, &str)]) -> String {
+    if parameters.is_empty() {
+        format!("/v3/{}", &path)
+    } else {
+        let request_path = format!("/v3/{}", &path);
+        let dummy_url = reqwest::Url::parse_with_params("https://example.net", parameters);
+        format!("{}?{}", request_path, dummy_url.unwrap().query().unwrap())
+    }
+}
@@ -1,18 +1,15 @@
 pub use super::super::types::*;
-use pyo3::prelude::*;
-use pyo3::types::PyList;
-use std::fs;
-use std::path::{Path, PathBuf};
+use super::super::Result;
+use crate::eth::key_derivation;
 
-pub fn eth_path_fix() -> String {
-    let compile_time_default_base = env!("CARGO_MANIFEST_DIR");
-    let compile_time_default = PathBuf::from(compile_time_default_base).join("src").join("eth_signing").to_string_lossy().parse().unwrap();
-    match option_env!("ITAY_PY_RUST_ROOT") {
-        Some(path) => PathBuf::from(path).join("src").join("eth_signing").to_string_lossy().parse().unwrap(),
-        None => match std::env::var("ITAY_PY_RUST_ROOT") {
-            Ok(path) => PathBuf::from(path).join("src").join("eth_signing").to_string_lossy().parse().unwrap(),
-            Err(_) => compile_time_default
-        }
+cfg_if::cfg_if! {
+    if #[cfg(feature = "native-signer")] {
+        use crate::eth::eth_private_action::SignEthPrivateAction;
+        use crate::eth::onboarding_action::SignOnboardingAction;
+        use crate::eth::signers::SignWithKey;
+        use std::sync::Arc;
+    } else {
+        use super::eth_sign_py;
     }
 }
 
@@ -22,33 +19,27 @@ pub fn sign_private(
     method: &str,
     request_path: &str,
     body: &str,
-    expiration_epoch_seconds: &str,
+    timestamp: &str,
     private_key: &str,
-) -> PyResult<String> {
-    let binding = eth_path_fix();
-    let path = Path::new(&binding);
-    let py_app = fs::read_to_string(path.join("eth_sign.py"))?;
-    let from_python = Python::with_gil(|py| -> PyResult<Py<PyAny>> {
-        let syspath: &PyList = py.import("sys")?.getattr("path")?.downcast::<PyList>()?;
-        syspath.insert(0, &path)?;
-        let app: Py<PyAny> = PyModule::from_code(py, &py_app, "", "")?
-            .getattr("sign_private")?
-            .into();
-        app.call1(
-            py,
-            (
+) -> Result<String> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "native-signer")] {
+            let eth_signer = Arc::new(SignWithKey::new(private_key)?);
+            let signer = SignEthPrivateAction::new(eth_signer.clone(), network_id);
+            let message_hash = signer.get_hash(method, request_path, body, timestamp);
+            Ok(eth_signer.sign_hash(&message_hash, Some(ethereum_address))?)
+        } else {
+            eth_sign_py::sign_private(
                 network_id,
                 ethereum_address,
                 method,
                 request_path,
                 body,
-                expiration_epoch_seconds,
+                timestamp,
                 private_key,
-            ),
-        )
-    });
-
-    Ok(from_python.unwrap().to_string())
+            )
+        }
+    }
 }
 
 pub fn sign_onboarding(
@@ -56,82 +47,30 @@ pub fn sign_onboarding(
     ethereum_address: &str,
     action: &str,
     private_key: &str,
-) -> PyResult<String> {
-    let binding = eth_path_fix();
-    let path = Path::new(&binding);
-    let py_app = fs::read_to_string(path.join("eth_sign.py"))?;
-    let from_python = Python::with_gil(|py| -> PyResult<Py<PyAny>> {
-        let syspath: &PyList = py.import("sys")?.getattr("path")?.downcast::<PyList>()?;
-        syspath.insert(0, &path)?;
-        let app: Py<PyAny> = PyModule::from_code(py, &py_app, "", "")?
-            .getattr("sign_onboarding")?
-            .into();
-        app.call1(py, (network_id, ethereum_address, action, private_key))
-    });
-
-    Ok(from_python.unwrap().to_string())
+) -> Result<String> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "native-signer")] {
+            let eth_signer = Arc::new(SignWithKey::new(private_key)?);
+            let signer = SignOnboardingAction::new(eth_signer.clone(), network_id);
+            Ok(eth_signer.sign_hash(&signer.get_hash(action), Some(ethereum_address))?)
+        } else {
+            eth_sign_py::sign_onboarding(network_id, ethereum_address, action, private_key)
+        }
+    }
 }
 
-pub fn derive_stark_private_key(signature: String) -> PyResult<String> {
-    let binding = eth_path_fix();
-    let path = Path::new(&binding);
-    let py_app = fs::read_to_string(path.join("eth_sign.py"))?;
-    let from_python = Python::with_gil(|py| -> PyResult<Py<PyAny>> {
-        let syspath: &PyList = py.import("sys")?.getattr("path")?.downcast::<PyList>()?;
-        syspath.insert(0, &path)?;
-        let app: Py<PyAny> = PyModule::from_code(py, &py_app, "", "")?
-            .getattr("derive_stark_private_key")?
-            .into();
-        app.call1(py, (signature,))
-    });
-
-    Ok(from_python.unwrap().to_string())
+pub fn derive_stark_private_key(signature: &str) -> Result<String> {
+    Ok(key_derivation::derive_stark_private_key(signature)?)
 }
 
-pub fn derive_secret(hex_value: String) -> PyResult<String> {
-    let binding = eth_path_fix();
-    let path = Path::new(&binding);
-    let py_app = fs::read_to_string(path.join("eth_sign.py"))?;
-    let from_python = Python::with_gil(|py| -> PyResult<Py<PyAny>> {
-        let syspath: &PyList = py.import("sys")?.getattr("path")?.downcast::<PyList>()?;
-        syspath.insert(0, &path)?;
-        let app: Py<PyAny> = PyModule::from_code(py, &py_app, "", "")?
-            .getattr("derive_secret")?
-            .into();
-        app.call1(py, (hex_value,))
-    });
-
-    Ok(from_python.unwrap().to_string())
+pub fn derive_secret(r_hex: &str) -> Result<String> {
+    Ok(key_derivation::derive_secret(r_hex)?)
 }
 
-pub fn derive_passphrase(hex_value: String) -> PyResult<String> {
-    let binding = eth_path_fix();
-    let path = Path::new(&binding);
-    let py_app = fs::read_to_string(path.join("eth_sign.py"))?;
-    let from_python = Python::with_gil(|py| -> PyResult<Py<PyAny>> {
-        let syspath: &PyList = py.import("sys")?.getattr("path")?.downcast::<PyList>()?;
-        syspath.insert(0, &path)?;
-        let app: Py<PyAny> = PyModule::from_code(py, &py_app, "", "")?
-            .getattr("derive_passphrase")?
-            .into();
-        app.call1(py, (hex_value,))
-    });
-
-    Ok(from_python.unwrap().to_string())
+pub fn derive_passphrase(s_hex: &str) -> Result<String> {
+    Ok(key_derivation::derive_passphrase(s_hex)?)
 }
 
-pub fn derive_key(hex_value: String) -> PyResult<String> {
-    let binding = eth_path_fix();
-    let path = Path::new(&binding);
-    let py_app = fs::read_to_string(path.join("eth_sign.py"))?;
-    let from_python = Python::with_gil(|py| -> PyResult<Py<PyAny>> {
-        let syspath: &PyList = py.import("sys")?.getattr("path")?.downcast::<PyList>()?;
-        syspath.insert(0, &path)?;
-        let app: Py<PyAny> = PyModule::from_code(py, &py_app, "", "")?
-            .getattr("derive_key")?
-            .into();
-        app.call1(py, (hex_value,))
-    });
-
-    Ok(from_python.unwrap().to_string())
+pub fn derive_key(s_hex: &str) -> Result<String> {
+    Ok(key_derivation::derive_key(s_hex)?)
 }
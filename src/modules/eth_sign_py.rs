@@ -0,0 +1,51 @@
+pub use super::super::types::*;
+use super::super::Result;
+use super::python_signer::PythonSigner;
+
+pub fn sign_private(
+    network_id: usize,
+    ethereum_address: &str,
+    method: &str,
+    request_path: &str,
+    body: &str,
+    expiration_epoch_seconds: &str,
+    private_key: &str,
+) -> Result<String> {
+    Ok(PythonSigner::get()?.call(
+        "sign_private",
+        (
+            network_id,
+            ethereum_address,
+            method,
+            request_path,
+            body,
+            expiration_epoch_seconds,
+            private_key,
+        ),
+    )?)
+}
+
+pub fn sign_onboarding(
+    network_id: usize,
+    ethereum_address: &str,
+    action: &str,
+    private_key: &str,
+) -> Result<String> {
+    Ok(PythonSigner::get()?.call("sign_onboarding", (network_id, ethereum_address, action, private_key))?)
+}
+
+pub fn derive_stark_private_key(signature: String) -> Result<String> {
+    Ok(PythonSigner::get()?.call("derive_stark_private_key", (signature,))?)
+}
+
+pub fn derive_secret(hex_value: String) -> Result<String> {
+    Ok(PythonSigner::get()?.call("derive_secret", (hex_value,))?)
+}
+
+pub fn derive_passphrase(hex_value: String) -> Result<String> {
+    Ok(PythonSigner::get()?.call("derive_passphrase", (hex_value,))?)
+}
+
+pub fn derive_key(hex_value: String) -> Result<String> {
+    Ok(PythonSigner::get()?.call("derive_key", (hex_value,))?)
+}
@@ -1,9 +1,18 @@
 pub use super::super::types::*;
 use super::super::{ResponseError, Result};
-use super::eth_sign::*;
+use super::eth_sign::{derive_key, derive_passphrase, derive_secret, derive_stark_private_key};
+use crate::eth::onboarding_action::SignOnboardingAction;
+use crate::eth::signers::{key_signer_or_failing, EthSigner};
+use crate::constants::{OFF_CHAIN_KEY_DERIVATION_ACTION, OFF_CHAIN_ONBOARDING_ACTION};
+use crate::eth::key_derivation::signature_r_s;
+use crate::eth::sign_off_chain_action::SignOffChainAction;
+use crate::eth::util::to_typed_signature;
+use crate::starkex::helpers::private_key_to_public_key_pair_hex;
 use http::StatusCode;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
@@ -11,11 +20,26 @@ pub struct Onboarding<'a> {
     client: reqwest::Client,
     host: &'a str,
     network_id: usize,
-    eth_private_key: &'a str,
+    signer: Option<Arc<dyn EthSigner>>,
 }
 
 impl Onboarding<'_> {
+    /// An invalid eth_private_key is reported by the first signature.
     pub fn new<'a>(host: &'a str, network_id: usize, api_timeout: u64, eth_private_key: &'a str) -> Onboarding<'a> {
+        Onboarding::with_signer(host, network_id, api_timeout, key_signer_or_failing(eth_private_key))
+    }
+
+    pub fn with_signer(host: &str, network_id: usize, api_timeout: u64, signer: Arc<dyn EthSigner>) -> Onboarding<'_> {
+        Onboarding::build(host, network_id, api_timeout, Some(signer))
+    }
+
+    /// Onboarding without an Ethereum signer, for signatures produced elsewhere
+    /// (e.g. a browser wallet) from the typed data this returns.
+    pub fn without_signer(host: &str, network_id: usize, api_timeout: u64) -> Onboarding<'_> {
+        Onboarding::build(host, network_id, api_timeout, None)
+    }
+
+    fn build(host: &str, network_id: usize, api_timeout: u64, signer: Option<Arc<dyn EthSigner>>) -> Onboarding<'_> {
         Onboarding {
             client: reqwest::ClientBuilder::new()
                 .timeout(Duration::from_secs(api_timeout))
@@ -23,83 +47,126 @@ impl Onboarding<'_> {
                 .expect("Client::new()"),
             host,
             network_id,
-            eth_private_key,
+            signer,
         }
     }
 
+    /// The EIP-712 typed data signed for create_user and
+    /// recover_default_api_key_credentials.
+    pub fn get_onboarding_typed_data(&self) -> Value {
+        self.action().get_onboarding_eip712_message(OFF_CHAIN_ONBOARDING_ACTION)
+    }
+
+    /// The EIP-712 typed data signed for derive_stark_key.
+    pub fn get_stark_key_typed_data(&self) -> Value {
+        self.action().get_onboarding_eip712_message(OFF_CHAIN_KEY_DERIVATION_ACTION)
+    }
+
     pub async fn create_user(
         &self,
         params: CreateUserParams<'_>,
         ethereum_address: &str,
     ) -> Result<CreateUserResponse> {
+        let signature = self.sign_onboarding(ethereum_address, OFF_CHAIN_ONBOARDING_ACTION).await?;
+        self.create_user_with_signature(params, ethereum_address, &signature).await
+    }
+
+    /// Like create_user, with a signature of get_onboarding_typed_data.
+    pub async fn create_user_with_signature(
+        &self,
+        params: CreateUserParams<'_>,
+        ethereum_address: &str,
+        signature: &str,
+    ) -> Result<CreateUserResponse> {
+        let signature = self.check_signature(ethereum_address, OFF_CHAIN_ONBOARDING_ACTION, signature)?;
         let path = "onboarding";
-        let response = self.request(path, ethereum_address, params).await;
+        let response = self.request(path, ethereum_address, &signature, params).await;
         response
     }
 
-    pub fn derive_stark_key(&self, ethereum_address: &str) -> Result<String> {
-        let action = "dYdX STARK Key";
-        let signature = sign_onboarding(
-            self.network_id,
-            ethereum_address,
-            action,
-            self.eth_private_key,
-        )
-        .unwrap();
-        let stark_private_key = derive_stark_private_key(signature).unwrap();
-        Ok(stark_private_key)
+    pub async fn derive_stark_key(&self, ethereum_address: &str) -> Result<String> {
+        let signature = self.sign_onboarding(ethereum_address, OFF_CHAIN_KEY_DERIVATION_ACTION).await?;
+        self.derive_stark_key_from_signature(ethereum_address, &signature)
+    }
+
+    /// Like derive_stark_key, with a signature of get_stark_key_typed_data.
+    pub fn derive_stark_key_from_signature(&self, ethereum_address: &str, signature: &str) -> Result<String> {
+        let signature = self.check_signature(ethereum_address, OFF_CHAIN_KEY_DERIVATION_ACTION, signature)?;
+        derive_stark_private_key(&signature)
+    }
+
+    pub async fn derive_stark_key_pair(&self, ethereum_address: &str) -> Result<KeyPairWithYCoordinate> {
+        let private_key = self.derive_stark_key(ethereum_address).await?;
+        stark_key_pair(private_key)
     }
 
-    pub fn recover_default_api_key_credentials(
+    /// Like derive_stark_key_pair, with a signature of get_stark_key_typed_data.
+    pub fn derive_stark_key_pair_from_signature(
         &self,
         ethereum_address: &str,
+        signature: &str,
+    ) -> Result<KeyPairWithYCoordinate> {
+        stark_key_pair(self.derive_stark_key_from_signature(ethereum_address, signature)?)
+    }
+
+    pub async fn recover_default_api_key_credentials(
+        &self,
+        ethereum_address: &str,
+    ) -> Result<ApiKeyCredentialsResponseObject> {
+        let signature = self.sign_onboarding(ethereum_address, OFF_CHAIN_ONBOARDING_ACTION).await?;
+        self.recover_default_api_key_credentials_from_signature(ethereum_address, &signature)
+    }
+
+    /// Like recover_default_api_key_credentials, with a signature of
+    /// get_onboarding_typed_data.
+    pub fn recover_default_api_key_credentials_from_signature(
+        &self,
+        ethereum_address: &str,
+        signature: &str,
     ) -> Result<ApiKeyCredentialsResponseObject> {
-        let action = "dYdX Onboarding";
-        let signature = sign_onboarding(
-            self.network_id,
-            ethereum_address,
-            action,
-            self.eth_private_key,
-        )
-        .unwrap();
-        let sig_str = signature.as_str();
-        let r_hex = &sig_str[2..66];
-
-        let secret = derive_secret(r_hex.to_string()).unwrap();
-        let s_hex = &sig_str[66..130];
-        let passphrase = derive_passphrase(s_hex.to_string()).unwrap();
-        let key = derive_key(s_hex.to_string()).unwrap();
+        let signature = self.check_signature(ethereum_address, OFF_CHAIN_ONBOARDING_ACTION, signature)?;
+        let (r_hex, s_hex) = signature_r_s(&signature)?;
 
         let api_key = ApiKeyCredentialsResponseObject {
-            key: key,
-            secret: secret,
-            passphrase: passphrase,
+            key: derive_key(s_hex)?,
+            secret: derive_secret(r_hex)?,
+            passphrase: derive_passphrase(s_hex)?,
         };
         Ok(api_key)
     }
 
+    fn action(&self) -> SignOnboardingAction {
+        match &self.signer {
+            Some(signer) => SignOnboardingAction::new(signer.clone(), self.network_id),
+            None => SignOnboardingAction::without_signer(self.network_id),
+        }
+    }
+
+    async fn sign_onboarding(&self, ethereum_address: &str, action: &str) -> Result<String> {
+        self.action().sign(ethereum_address, action).await
+    }
+
+    /// Normalize an externally produced signature and check it was made by ethereum_address.
+    fn check_signature(&self, ethereum_address: &str, action: &str, signature: &str) -> Result<String> {
+        let signature = to_typed_signature(signature)?;
+        let onboarding_action = self.action();
+        onboarding_action.check_signature(&onboarding_action.get_hash(action), &signature, ethereum_address)?;
+        Ok(signature)
+    }
+
     async fn request<T: for<'de> Deserialize<'de>, V: Serialize>(
         &self,
         path: &str,
         ethereum_address: &str,
+        signature: &str,
         data: V,
     ) -> Result<T> {
-        let action = "dYdX Onboarding";
-
-        let signature = sign_onboarding(
-            self.network_id,
-            ethereum_address,
-            action,
-            self.eth_private_key,
-        )
-        .unwrap();
-
         let url = format!("{}/v3/{}", &self.host, path);
 
         let req_builder = self.client.post(url);
 
         let req_builder = req_builder
-            .header("DYDX-SIGNATURE", signature.as_str())
+            .header("DYDX-SIGNATURE", signature)
             .header("DYDX-ETHEREUM-ADDRESS", ethereum_address)
             .json(&data);
 
@@ -129,3 +196,12 @@ impl Onboarding<'_> {
         };
     }
 }
+
+fn stark_key_pair(private_key: String) -> Result<KeyPairWithYCoordinate> {
+    let (public_key, public_key_y_coordinate) = private_key_to_public_key_pair_hex(&private_key)?;
+    Ok(KeyPairWithYCoordinate {
+        public_key,
+        public_key_y_coordinate,
+        private_key,
+    })
+}
@@ -1,39 +1,73 @@
 use std::fmt::Debug;
 use std::sync::Arc;
-use super::super::constants::*;
 use super::super::helper::*;
 use super::super::types::*;
 use super::super::{ResponseError, Result};
-use super::stark_sign::*;
-use chrono::prelude::*;
-use hmac::{Hmac, Mac};
+use super::api_key_signer::ApiKeySigner;
+use super::signed_request::{RequestSigner, SignedRequest};
+use crate::starkex::market_registry::MarketRegistry;
+use crate::starkex::order::SignableOrder;
+use crate::starkex::signers::StarkSigner;
+use crate::starkex::signing_pool::SigningPool;
+use futures_util::future::join_all;
+use futures_util::stream::{self, StreamExt};
 use http::{Method, StatusCode};
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::*;
-use sha2::Sha256;
 use std::time::Duration;
 use backon::Retryable;
 use crate::retry::{ExponentialBuilderHelperGet, ErrorFn};
 
+/// A request as Private sends it, for dry runs: prepare_create_order and the other
+/// prepare_* methods return one without sending it, and send sends it. The API-key
+/// signature covers the DYDX-TIMESTAMP of preparation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedRequest {
+    pub method: Method,
+    pub url: String,
+    /// The path with query, e.g. "/v3/orders?market=BTC-USD", as covered by DYDX-SIGNATURE.
+    pub request_path: String,
+    /// The JSON body, if any.
+    pub body: Option<String>,
+    /// DYDX-SIGNATURE, DYDX-TIMESTAMP, DYDX-API-KEY and DYDX-PASSPHRASE.
+    pub headers: Vec<(&'static str, String)>,
+    /// The STARK signature of an order, transfer or withdrawal.
+    pub stark_signature: Option<String>,
+}
+
+impl PreparedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Private<'a> {
     client: reqwest::Client,
     host: &'a str,
     network_id: usize,
     api_key_credentials: ApiKeyCredentials<'a>,
-    stark_private_key: Option<&'a str>,
+    stark_signer: Option<Arc<dyn StarkSigner>>,
+    market_registry: Option<Arc<MarketRegistry>>,
+    signing_pool: Arc<SigningPool>,
     error_handler: Option<ErrorFn>,
     retry_backoff_getter: Arc<dyn ExponentialBuilderHelperGet>
 }
 
 impl<'a> Private<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         host: &'a str,
         network_id: usize,
         api_timeout: u64,
         api_key_credentials: ApiKeyCredentials<'a>,
-        stark_private_key: Option<&'a str>,
+        stark_signer: Option<Arc<dyn StarkSigner>>,
+        market_registry: Option<Arc<MarketRegistry>>,
+        signing_pool: Arc<SigningPool>,
         error_handler: Option<ErrorFn>,
         retry_backoff_getter: Arc<dyn ExponentialBuilderHelperGet>,
     ) -> Arc<Private<'a>> {
@@ -45,7 +79,9 @@ impl<'a> Private<'a> {
             host,
             network_id,
             api_key_credentials,
-            stark_private_key,
+            stark_signer,
+            market_registry,
+            signing_pool,
             error_handler,
             retry_backoff_getter,
         })
@@ -171,7 +207,7 @@ impl<'a> Private<'a> {
         response
     }
 
-    pub fn test_sign_order(&self) -> Result<()> {
+    pub async fn test_sign_order(&self) -> Result<()> {
         let market = "BTC-USD";
         let side = "BUY";
         let position_id = "123";
@@ -181,8 +217,10 @@ impl<'a> Private<'a> {
         let client_id = "123";
         let expiration = 1610000000;
 
-        let signature = sign_order(
-            self.network_id,
+        let signer = self.request_signer();
+        let registry = signer.market_registry()?;
+        let order = SignableOrder::with_registry(
+            &registry,
             market,
             side,
             position_id,
@@ -191,189 +229,103 @@ impl<'a> Private<'a> {
             limit_fee,
             client_id,
             expiration,
-            self.stark_private_key.unwrap(),
         )?;
+        let signature = signer.stark_sign(order).await?;
 
         println!("Signature: {:?}", signature);
         Ok(())
     }
 
     pub async fn create_order(&self, user_params: ApiOrderParams<'_>) -> Result<OrderResponse> {
-        let client_id = if user_params.client_id.is_some() {
-            user_params.client_id.unwrap().to_owned()
-        } else {
-            generate_random_client_id()
-        };
-
-        cfg_if::cfg_if! {
-        if #[cfg(feature = "dummy_signature")] {
-            let signature = "blabla".to_string();
-        } else {
-                let signature = sign_order(
-                    self.network_id,
-                    user_params.market,
-                    user_params.side,
-                    user_params.position_id,
-                    user_params.size,
-                    user_params.price,
-                    user_params.limit_fee,
-                    client_id.as_str(),
-                    user_params.expiration,
-                    self.stark_private_key.unwrap(),
-                )
-                .unwrap();
-            }
-        }
+        self.send(&self.prepare_create_order(user_params).await?).await
+    }
 
-        let naive = NaiveDateTime::from_timestamp(user_params.expiration, 0);
-        let datetime: DateTime<Utc> = DateTime::from_utc(naive, Utc);
-        let expiration_second = datetime.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-
-        let parameters = ApiOrder {
-            market: user_params.market,
-            side: user_params.side,
-            type_field: user_params.type_field,
-            size: user_params.size,
-            price: user_params.price,
-            time_in_force: user_params.time_in_force,
-            post_only: user_params.post_only,
-            limit_fee: user_params.limit_fee,
-            cancel_id: user_params.cancel_id,
-            trigger_price: user_params.trigger_price,
-            trailing_percent: user_params.trailing_percent,
-            expiration: expiration_second.as_str(),
-            client_id: client_id.as_str(),
-            signature: signature.as_str(),
-        };
+    /// The request create_order would send, without sending it.
+    pub async fn prepare_create_order(&self, user_params: ApiOrderParams<'_>) -> Result<PreparedRequest> {
+        let request = self.request_signer().order(&user_params).await?;
+        self.prepare_signed(&request)
+    }
 
-        let response = self
-            .request("orders", Method::POST, Vec::new(), parameters)
-            .await;
-        response
+    /// Create many orders: every order is signed first, on the signing pool, and the
+    /// signed orders are then sent with at most max_concurrent_requests requests in
+    /// flight. Results are in the order of orders, and one failure does not stop the others.
+    pub async fn create_orders(
+        &self,
+        orders: &[ApiOrderParams<'_>],
+        max_concurrent_requests: usize,
+    ) -> Vec<Result<OrderResponse>> {
+        let signer = self.request_signer();
+        let signed = join_all(orders.iter().map(|order| signer.order(order))).await;
+        stream::iter(signed)
+            .map(|request| async move { self.submit_signed(request?).await })
+            .buffered(max_concurrent_requests.max(1))
+            .collect()
+            .await
     }
 
     pub async fn create_transfer(
         &self,
         user_params: TransferParams<'_>,
     ) -> Result<TransferResponse> {
-        let client_id = generate_random_client_id();
-
-        let signature = sign_transfer(
-            self.network_id,
-            user_params.position_id,
-            user_params.receiver_position_id,
-            user_params.receiver_public_key,
-            user_params.amount,
-            &client_id,
-            user_params.expiration,
-            self.stark_private_key.unwrap(),
-        )
-        .unwrap();
-
-        let naive = NaiveDateTime::from_timestamp(user_params.expiration, 0);
-        let datetime: DateTime<Utc> = DateTime::from_utc(naive, Utc);
-        let expiration_second = datetime.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-
-        let parameters = ApiTransfer {
-            amount: user_params.amount,
-            receiver_account_id: user_params.receiver_account_id,
-            expiration: expiration_second.as_str(),
-            client_id: client_id.as_str(),
-            signature: signature.as_str(),
-        };
+        self.send(&self.prepare_create_transfer(user_params).await?).await
+    }
 
-        let response = self
-            .request("transfers", Method::POST, Vec::new(), parameters)
-            .await;
-        response
+    /// The request create_transfer would send, without sending it.
+    pub async fn prepare_create_transfer(&self, user_params: TransferParams<'_>) -> Result<PreparedRequest> {
+        let request = self.request_signer().transfer(&user_params).await?;
+        self.prepare_signed(&request)
     }
 
     pub async fn create_withdraw(
         &self,
         user_params: ApiWithdrawParams<'_>,
     ) -> Result<WithdrawalResponse> {
-        let client_id = generate_random_client_id();
-
-        let signature = sign_withdraw(
-            self.network_id,
-            user_params.position_id,
-            user_params.amount,
-            &client_id,
-            user_params.expiration,
-            self.stark_private_key.unwrap(),
-        )
-        .unwrap();
-
-        let naive = NaiveDateTime::from_timestamp(user_params.expiration, 0);
-        let datetime: DateTime<Utc> = DateTime::from_utc(naive, Utc);
-        let expiration_second = datetime.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-
-        let parameters = ApiWithdraw {
-            amount: user_params.amount,
-            asset: user_params.asset,
-            expiration: expiration_second.as_str(),
-            client_id: client_id.as_str(),
-            signature: signature.as_str(),
-        };
+        self.send(&self.prepare_create_withdraw(user_params).await?).await
+    }
 
-        let response = self
-            .request("withdrawals", Method::POST, Vec::new(), parameters)
-            .await;
-        response
+    /// The request create_withdraw would send, without sending it.
+    pub async fn prepare_create_withdraw(&self, user_params: ApiWithdrawParams<'_>) -> Result<PreparedRequest> {
+        let request = self.request_signer().withdraw(&user_params).await?;
+        self.prepare_signed(&request)
     }
 
     pub async fn create_fast_withdraw(
         &self,
         user_params: ApiFastWithdrawalParams<'_>,
     ) -> Result<WithdrawalResponse> {
-        let client_id = generate_random_client_id();
+        self.send(&self.prepare_create_fast_withdraw(user_params).await?).await
+    }
 
-        let fact_address = if self.network_id == 1 {
-            FACT_REGISTRY_CONTRACT_MAINNET
-        } else {
-            FACT_REGISTRY_CONTRACT_ROPSTEN
-        };
-        let token_address = if self.network_id == 1 {
-            ASSET_USDC_CONTRACT_MAINNET
-        } else {
-            FACT_REGISTRY_CONTRACT_ROPSTEN
-        };
+    /// The request create_fast_withdraw would send, without sending it.
+    pub async fn prepare_create_fast_withdraw(
+        &self,
+        user_params: ApiFastWithdrawalParams<'_>,
+    ) -> Result<PreparedRequest> {
+        let request = self.request_signer().fast_withdraw(&user_params).await?;
+        self.prepare_signed(&request)
+    }
 
-        let signature = sign_fast_withdraw(
+    /// Send a request signed with a RequestSigner, possibly on another machine. Only the
+    /// API-key headers are computed here. T is the response of its kind: OrderResponse,
+    /// TransferResponse or WithdrawalResponse.
+    pub async fn submit_signed<T: for<'de> Deserialize<'de>>(&self, request: SignedRequest) -> Result<T> {
+        self.send(&self.prepare_signed(&request)?).await
+    }
+
+    fn prepare_signed(&self, request: &SignedRequest) -> Result<PreparedRequest> {
+        let mut prepared = self.prepare(request.kind.path(), Method::POST, Vec::new(), &request.body);
+        prepared.stark_signature = Some(request.signature()?);
+        Ok(prepared)
+    }
+
+    /// A signer with the STARK signer, markets and signing pool of this client.
+    pub fn request_signer(&self) -> RequestSigner {
+        RequestSigner::from_parts(
             self.network_id,
-            user_params.position_id,
-            user_params.lp_position_id,
-            user_params.lp_stark_key,
-            fact_address,
-            user_params.to_address,
-            COLLATERAL_TOKEN_DECIMALS,
-            user_params.credit_amount,
-            token_address,
-            &client_id,
-            user_params.expiration,
-            self.stark_private_key.unwrap(),
+            self.stark_signer.clone(),
+            self.market_registry.clone(),
+            self.signing_pool.clone(),
         )
-        .unwrap();
-
-        let naive = NaiveDateTime::from_timestamp(user_params.expiration, 0);
-        let datetime: DateTime<Utc> = DateTime::from_utc(naive, Utc);
-        let expiration_second = datetime.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-
-        let parameters = ApiFastWithdrawal {
-            credit_asset: user_params.credit_asset,
-            credit_amount: user_params.credit_amount,
-            debit_amount: user_params.debit_amount,
-            to_address: user_params.to_address,
-            lp_position_id: user_params.lp_position_id,
-            expiration: expiration_second.as_str(),
-            client_id: &client_id,
-            signature: signature.as_str(),
-        };
-
-        let response = self
-            .request("fast-withdrawals", Method::POST, Vec::new(), parameters)
-            .await;
-        response
     }
 
     pub async fn get_transfers(
@@ -396,22 +348,26 @@ impl<'a> Private<'a> {
     }
 
     pub async fn cancel_order(&self, order_id: &str) -> Result<CancelOrderResponse> {
+        self.send(&self.prepare_cancel_order(order_id)).await
+    }
+
+    /// The request cancel_order would send, without sending it.
+    pub fn prepare_cancel_order(&self, order_id: &str) -> PreparedRequest {
         let path = format!("orders/{}", order_id);
-        let response = self
-            .request(path.as_str(), Method::DELETE, Vec::new(), json!({}))
-            .await;
-        response
+        self.prepare(path.as_str(), Method::DELETE, Vec::new(), json!({}))
     }
 
     pub async fn cancel_all_orders(&self, market: Option<&str>) -> Result<CancelOrdersResponse> {
+        self.send(&self.prepare_cancel_all_orders(market)).await
+    }
+
+    /// The request cancel_all_orders would send, without sending it.
+    pub fn prepare_cancel_all_orders(&self, market: Option<&str>) -> PreparedRequest {
         let mut parameters = Vec::new();
         if let Some(local_var) = market {
             parameters.push(("market", local_var));
         }
-        let response = self
-            .request("orders", Method::DELETE, parameters, json!({}))
-            .await;
-        response
+        self.prepare("orders", Method::DELETE, parameters, json!({}))
     }
 
     pub async fn get_orders(
@@ -441,13 +397,13 @@ impl<'a> Private<'a> {
             parameters.push(("limit", local_var));
         }
         if let Some(local_var) = created_before_or_at {
-            parameters.push(("created_before_or_at", local_var));
+            parameters.push(("createdBeforeOrAt", local_var));
         }
         if let Some(local_var) = return_latest_orders {
-            parameters.push(("return_latest_orders", local_var));
+            parameters.push(("returnLatestOrders", local_var));
         }
         let response = self
-            .retry_wrapper("orders", Vec::new(), json!({}), Some("get_orders"))
+            .retry_wrapper("orders", parameters, json!({}), Some("get_orders"))
             .await;
         response
     }
@@ -658,6 +614,16 @@ impl<'a> Private<'a> {
         parameters: Vec<(&str, &str)>,
         data: V,
     ) -> Result<T> {
+        self.send(&self.prepare(path, method, parameters, data)).await
+    }
+
+    fn prepare<V: Serialize>(
+        &self,
+        path: &str,
+        method: Method,
+        parameters: Vec<(&str, &str)>,
+        data: V,
+    ) -> PreparedRequest {
         let request_path = if parameters.len() == 0 {
             format!("/v3/{}", &path)
         } else {
@@ -666,37 +632,53 @@ impl<'a> Private<'a> {
             format!("{}?{}", request_path, dummy_url.unwrap().query().unwrap())
         };
 
-        let iso_timestamp = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
         let json = to_string(&data).unwrap();
+        let body = if json != "{}" { Some(json) } else { None };
+        let headers = self
+            .api_key_signer()
+            .headers(method.as_str(), &request_path, body.as_deref());
 
-        let signature = self.sign(
-            request_path.as_str(),
-            method.as_str(),
-            &iso_timestamp,
-            Some(json.as_str()),
-        );
+        PreparedRequest {
+            method,
+            url: format!("{}{}", &self.host, request_path),
+            request_path,
+            body,
+            headers,
+            stark_signature: None,
+        }
+    }
 
-        let url = format!("{}/v3/{}", &self.host, path);
+    /// Send an authenticated request to any endpoint, e.g. one this client does not wrap
+    /// yet. path is relative to /v3/, and data is the JSON body; json!({}) sends none.
+    pub async fn request_raw<T: for<'de> Deserialize<'de>, V: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        parameters: Vec<(&str, &str)>,
+        data: V,
+    ) -> Result<T> {
+        self.request(path, method, parameters, data).await
+    }
 
-        let req_builder = match method {
-            Method::GET => self.client.get(url),
-            Method::POST => self.client.post(url),
-            Method::PUT => self.client.put(url),
-            Method::DELETE => self.client.delete(url),
-            _ => self.client.get(url),
-        };
+    /// The signer of the API-key headers of this client's requests.
+    pub fn api_key_signer(&self) -> ApiKeySigner<'a> {
+        ApiKeySigner::new(self.api_key_credentials.clone())
+    }
 
-        let req_builder = req_builder
-            .header("DYDX-SIGNATURE", signature.as_str())
-            .header("DYDX-TIMESTAMP", iso_timestamp.as_str())
-            .header("DYDX-API-KEY", self.api_key_credentials.key)
-            .header("DYDX-PASSPHRASE", self.api_key_credentials.passphrase)
-            .query(&parameters);
+    /// Send a request returned by one of the prepare_* methods.
+    pub async fn send<T: for<'de> Deserialize<'de>>(&self, prepared: &PreparedRequest) -> Result<T> {
+        let req_builder = self.client.request(prepared.method.clone(), prepared.url.as_str());
 
-        let req_builder = if json != "{}" {
-            req_builder.json(&data)
-        } else {
-            req_builder
+        let req_builder = prepared
+            .headers
+            .iter()
+            .fold(req_builder, |req_builder, (name, value)| req_builder.header(*name, value));
+
+        let req_builder = match &prepared.body {
+            Some(body) => req_builder
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(body.clone()),
+            None => req_builder,
         };
         let response = req_builder.send().await;
 
@@ -723,28 +705,4 @@ impl<'a> Private<'a> {
             }
         };
     }
-
-    fn sign(
-        &self,
-        request_path: &str,
-        method: &str,
-        iso_timestamp: &String,
-        data: Option<&str>,
-    ) -> String {
-        let mut message = String::from(iso_timestamp) + method + request_path;
-
-        if let Some(local_var) = data {
-            if local_var != "{}" {
-                message.push_str(local_var);
-            }
-        }
-
-        let secret = self.api_key_credentials.secret;
-        let secret = base64::decode_config(secret, base64::URL_SAFE).unwrap();
-
-        let mut mac = Hmac::<Sha256>::new_from_slice(&*secret).unwrap();
-        mac.update(message.as_bytes());
-        let code = mac.finalize().into_bytes();
-        base64::encode(&code)
-    }
 }
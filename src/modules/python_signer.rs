@@ -0,0 +1,207 @@
+//! Process-wide handles to the functions of stark_sign.py and eth_sign.py.
+//!
+//! The Python sources of src/stark and src/eth_signing are compiled into the crate and
+//! imported through an in-memory importer, so the signer does not depend on where the
+//! crate was built or installed. The modules are loaded once, on first use. A load
+//! failure is kept too, so every later call reports the same error.
+
+use crate::error::PythonSignerError;
+use once_cell::sync::OnceCell;
+use pyo3::prelude::*;
+use num_bigint::BigUint;
+use pyo3::types::{PyDict, PyTuple};
+use std::collections::HashMap;
+
+const STARK_SIGN_FUNCTIONS: &[&str] = &["sign_order", "sign_withdraw", "sign_fast_withdraw", "sign_transfer"];
+const ETH_SIGN_FUNCTIONS: &[&str] = &[
+    "sign_private",
+    "sign_onboarding",
+    "derive_stark_private_key",
+    "derive_secret",
+    "derive_passphrase",
+    "derive_key",
+];
+
+/// Prefix of the file names reported for embedded modules, e.g. in tracebacks.
+const EMBEDDED_ROOT: &str = "<dydx-v3-rust>";
+
+macro_rules! embed {
+    ($module:literal, $path:literal) => {
+        ($module, $path, include_str!(concat!("../", $path)))
+    };
+}
+
+/// (module name, path under src/, source) of every embedded module. Both trees used to
+/// be put on sys.path, where the dydx_constants of src/stark, a superset of the one in
+/// src/eth_signing, was always imported first; only that one is embedded.
+const EMBEDDED_MODULES: &[(&str, &str, &str)] = &[
+    embed!("stark_sign", "stark/stark_sign.py"),
+    embed!("dydx_constants", "stark/dydx_constants.py"),
+    embed!("starkex", "stark/starkex/__init__.py"),
+    embed!("starkex.conditional_transfer", "stark/starkex/conditional_transfer.py"),
+    embed!("starkex.constants", "stark/starkex/constants.py"),
+    embed!("starkex.helpers", "stark/starkex/helpers.py"),
+    embed!("starkex.order", "stark/starkex/order.py"),
+    embed!("starkex.signable", "stark/starkex/signable.py"),
+    embed!("starkex.transfer", "stark/starkex/transfer.py"),
+    embed!("starkex.withdrawal", "stark/starkex/withdrawal.py"),
+    embed!("starkex.starkex_resources", "stark/starkex/starkex_resources/__init__.py"),
+    embed!("starkex.starkex_resources.cpp_signature", "stark/starkex/starkex_resources/cpp_signature.py"),
+    embed!("starkex.starkex_resources.math_utils", "stark/starkex/starkex_resources/math_utils.py"),
+    embed!("starkex.starkex_resources.proxy", "stark/starkex/starkex_resources/proxy.py"),
+    embed!("starkex.starkex_resources.python_signature", "stark/starkex/starkex_resources/python_signature.py"),
+    embed!("eth_sign", "eth_signing/eth_sign.py"),
+    embed!("eth_prive_action", "eth_signing/eth_prive_action.py"),
+    embed!("onboarding_action", "eth_signing/onboarding_action.py"),
+    embed!("sign_off_chain_action", "eth_signing/sign_off_chain_action.py"),
+    embed!("signers", "eth_signing/signers.py"),
+    embed!("util", "eth_signing/util.py"),
+];
+
+/// (path under src/, contents) of the data files read with pkgutil.get_data.
+const EMBEDDED_RESOURCES: &[(&str, &str)] = &[(
+    "stark/starkex/starkex_resources/pedersen_params.json",
+    include_str!("../stark/starkex/starkex_resources/pedersen_params.json"),
+)];
+
+const EMBEDDED_IMPORTER: &str = r#"
+import importlib.abc
+import importlib.util
+import sys
+
+
+class EmbeddedImporter(importlib.abc.MetaPathFinder, importlib.abc.Loader):
+    is_dydx_v3_rust_importer = True
+
+    def __init__(self, modules, files):
+        self.modules = modules
+        self.files = files
+
+    def find_spec(self, fullname, path=None, target=None):
+        if fullname not in self.modules:
+            return None
+        origin, is_package = self.modules[fullname]
+        spec = importlib.util.spec_from_loader(fullname, self, origin=origin, is_package=is_package)
+        spec.has_location = True
+        return spec
+
+    def create_module(self, spec):
+        return None
+
+    def exec_module(self, module):
+        origin = module.__spec__.origin
+        exec(compile(self.files[origin], origin, 'exec'), module.__dict__)
+
+    def get_source(self, fullname):
+        return self.files[self.modules[fullname][0]]
+
+    def get_data(self, path):
+        if path not in self.files:
+            raise OSError(path)
+        return self.files[path].encode()
+
+
+def install(modules, files):
+    if not any(getattr(finder, 'is_dydx_v3_rust_importer', False) for finder in sys.meta_path):
+        sys.meta_path.insert(0, EmbeddedImporter(modules, files))
+"#;
+
+static PYTHON_SIGNER: OnceCell<Result<PythonSigner, PythonSignerError>> = OnceCell::new();
+
+pub struct PythonSigner {
+    functions: HashMap<&'static str, Py<PyAny>>,
+}
+
+impl PythonSigner {
+    /// The loaded signing functions, loading them on the first call.
+    pub fn get() -> Result<&'static PythonSigner, PythonSignerError> {
+        PYTHON_SIGNER.get_or_init(PythonSigner::load).as_ref().map_err(Clone::clone)
+    }
+
+    /// Call one of the functions of stark_sign.py or eth_sign.py and return str() of its result.
+    pub fn call(&self, function: &str, args: impl IntoPy<Py<PyTuple>>) -> PyResult<String> {
+        let function = &self.functions[function];
+        let from_python = Python::with_gil(|py| function.call1(py, args));
+        Ok(from_python?.to_string())
+    }
+
+    /// Like call, also passing seed as the seed keyword argument of the stark_sign.py
+    /// functions.
+    pub fn call_with_seed(
+        &self,
+        function: &str,
+        args: impl IntoPy<Py<PyTuple>>,
+        seed: Option<&BigUint>,
+    ) -> PyResult<String> {
+        let function = &self.functions[function];
+        let from_python = Python::with_gil(|py| {
+            let kwargs = PyDict::new(py);
+            if let Some(seed) = seed {
+                let seed = py.import("builtins")?.getattr("int")?.call1((seed.to_str_radix(16), 16))?;
+                kwargs.set_item("seed", seed)?;
+            }
+            function.call(py, args, Some(kwargs))
+        });
+        Ok(from_python?.to_string())
+    }
+
+    fn load() -> Result<PythonSigner, PythonSignerError> {
+        let mut functions = HashMap::new();
+        Python::with_gil(|py| {
+            install_importer(py).map_err(|e| PythonSignerError::ImportFailed {
+                module: "embedded_importer",
+                reason: e.to_string(),
+            })?;
+            load_module(py, "stark_sign", STARK_SIGN_FUNCTIONS, &mut functions)?;
+            load_module(py, "eth_sign", ETH_SIGN_FUNCTIONS, &mut functions)
+        })?;
+        Ok(PythonSigner { functions })
+    }
+}
+
+/// Make the embedded modules (stark_sign, eth_sign, the starkex package, ...) importable
+/// in this interpreter. They take precedence over modules of the same name on sys.path.
+/// Installing more than once has no effect.
+pub fn install_importer(py: Python) -> PyResult<()> {
+    let modules: HashMap<&str, (String, bool)> = EMBEDDED_MODULES
+        .iter()
+        .map(|(module, path, _)| (*module, (embedded_path(path), path.ends_with("__init__.py"))))
+        .collect();
+    let files: HashMap<String, &str> = EMBEDDED_MODULES
+        .iter()
+        .map(|(_, path, source)| (*path, *source))
+        .chain(EMBEDDED_RESOURCES.iter().copied())
+        .map(|(path, contents)| (embedded_path(path), contents))
+        .collect();
+    let importer = PyModule::from_code(
+        py,
+        EMBEDDED_IMPORTER,
+        &embedded_path("embedded_importer.py"),
+        "dydx_v3_rust_embedded_importer",
+    )?;
+    importer.getattr("install")?.call1((modules, files))?;
+    Ok(())
+}
+
+fn embedded_path(path: &str) -> String {
+    format!("{}/{}", EMBEDDED_ROOT, path)
+}
+
+fn load_module(
+    py: Python,
+    module: &'static str,
+    function_names: &[&'static str],
+    functions: &mut HashMap<&'static str, Py<PyAny>>,
+) -> Result<(), PythonSignerError> {
+    let module_object = py.import(module).map_err(|e| PythonSignerError::ImportFailed {
+        module,
+        reason: e.to_string(),
+    })?;
+    for &function in function_names {
+        let handle = module_object
+            .getattr(function)
+            .map_err(|_| PythonSignerError::MissingFunction { module, function })?;
+        functions.insert(function, handle.into());
+    }
+    Ok(())
+}
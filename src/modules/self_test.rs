@@ -0,0 +1,141 @@
+//! Known-answer checks of the backend behind stark_sign and eth_sign.
+//!
+//! Each signature kind is signed with the test keys of constants, verified natively,
+//! and compared with the signature produced by the Python reference implementation.
+
+use super::eth_sign;
+use super::stark_sign;
+use crate::constants::*;
+use crate::error::SelfTestError;
+use crate::eth::eth_private_action::SignEthPrivateAction;
+use crate::eth::onboarding_action::SignOnboardingAction;
+use crate::eth::signers::SignWithKey;
+use crate::starkex::helpers::private_key_to_public_hex;
+use crate::Result;
+use once_cell::sync::Lazy;
+use std::sync::Arc;
+
+#[cfg(not(feature = "native-signer"))]
+use super::python_signer::PythonSigner;
+
+/// The backend stark_sign and eth_sign sign with: "native", or "python" when only the
+/// python-signer feature is enabled.
+pub const BACKEND: &str = if cfg!(feature = "native-signer") { "native" } else { "python" };
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    pub backend: &'static str,
+    /// The signature kinds that were signed and verified.
+    pub checks: Vec<&'static str>,
+}
+
+struct Check {
+    name: &'static str,
+    expected: &'static str,
+    sign: fn() -> Result<String>,
+    verify: fn(&str) -> Result<bool>,
+}
+
+static STARK_PUBLIC_KEY: Lazy<String> =
+    Lazy::new(|| private_key_to_public_hex(TEST_STARK_PRIVATE_KEY).expect("invalid TEST_STARK_PRIVATE_KEY"));
+
+const TRANSFER_RECEIVER: &str = "04a9ecd28a67407c3cff8937f329ca24fd631b1d9ca2b9f2df47c7ebf72bf0b0";
+const ETH_TIMESTAMP: &str = "2022-05-10T08:11:03.123Z";
+
+const CHECKS: &[Check] = &[
+    Check {
+        name: "sign_order",
+        expected: "0714e9e3ca6541f22e4160dc8fe007375b94d519984181d92379cf8b550875e20238b4a330f0cc89d042e13b52b9d33c55cdc516406cfbea04adb64746244ead",
+        sign: || {
+            stark_sign::sign_order(NETWORK_ID_MAINNET, "BTC-USD", "SELL", "228", "0.01", "100000.123456789", "0.0015", "123", 1700000000, TEST_STARK_PRIVATE_KEY)
+        },
+        verify: |signature| {
+            stark_sign::verify_order(NETWORK_ID_MAINNET, "BTC-USD", "SELL", "228", "0.01", "100000.123456789", "0.0015", "123", 1700000000, signature, &STARK_PUBLIC_KEY)
+        },
+    },
+    Check {
+        name: "sign_withdraw",
+        expected: "07b43d3cb577505f81d33cf98d7ac490da62612857538c9da4c5c0b9eb7117fe0335f4b4e727dd81158f7b46343a6260b5626b6322829dd39711b9bbaca1fa0a",
+        sign: || stark_sign::sign_withdraw(NETWORK_ID_MAINNET, "228", "3", "456", 1700000001, TEST_STARK_PRIVATE_KEY),
+        verify: |signature| {
+            stark_sign::verify_withdraw(NETWORK_ID_MAINNET, "228", "3", "456", 1700000001, signature, &STARK_PUBLIC_KEY)
+        },
+    },
+    Check {
+        name: "sign_fast_withdraw",
+        expected: "03c7bfb54807dc4ba1045da0aaef546df4bfaf0b5d1d89bbc19b8ac306a55c9000d615bf03c4e1374f938ea0093d1fa2e7e5ca965cb560b28034bd9c746857e1",
+        sign: || {
+            stark_sign::sign_fast_withdraw(NETWORK_ID_MAINNET, "228", "2", TRANSFER_RECEIVER, FACT_REGISTRY_CONTRACT_MAINNET, TEST_ADDRESS, COLLATERAL_TOKEN_DECIMALS, "10", ASSET_USDC_CONTRACT_MAINNET, "999", 1700000000, TEST_STARK_PRIVATE_KEY)
+        },
+        verify: |signature| {
+            stark_sign::verify_fast_withdraw(NETWORK_ID_MAINNET, "228", "2", TRANSFER_RECEIVER, FACT_REGISTRY_CONTRACT_MAINNET, TEST_ADDRESS, COLLATERAL_TOKEN_DECIMALS, "10", ASSET_USDC_CONTRACT_MAINNET, "999", 1700000000, signature, &STARK_PUBLIC_KEY)
+        },
+    },
+    Check {
+        name: "sign_transfer",
+        expected: "02e74bf2cc7c1843371f9c2ab4207a8a9f5120f2c78863ff2431b77746953bf20221dc52a74b55aae5121dbd0ef1ab5bcbd7bb6fb7ca75d2e89e7326ecd66606",
+        sign: || stark_sign::sign_transfer(NETWORK_ID_GOERLI, "228", "2", TRANSFER_RECEIVER, "10.5", "789", 1700003600, TEST_STARK_PRIVATE_KEY),
+        verify: |signature| {
+            stark_sign::verify_transfer(NETWORK_ID_GOERLI, "228", "2", TRANSFER_RECEIVER, "10.5", "789", 1700003600, signature, &STARK_PUBLIC_KEY)
+        },
+    },
+    Check {
+        name: "sign_private",
+        expected: "0xee21686b11922016b781760882d21afc58a831f234d3b84204b38d311396edc27a5de63aba530baa9119a66cdd080f84ce37c9bb1d56671aed85a97703026f011b00",
+        sign: || eth_sign::sign_private(NETWORK_ID_MAINNET, TEST_ADDRESS, "DELETE", "/v3/api-keys?apiKey=abc", "{}", ETH_TIMESTAMP, TEST_PRIVATE_KEY),
+        verify: |signature| {
+            let signer = SignEthPrivateAction::new(Arc::new(SignWithKey::new(TEST_PRIVATE_KEY)?), NETWORK_ID_MAINNET);
+            Ok(signer.verify(signature, TEST_ADDRESS, "DELETE", "/v3/api-keys?apiKey=abc", "{}", ETH_TIMESTAMP)?)
+        },
+    },
+    Check {
+        name: "sign_onboarding",
+        expected: "0x1472e6a01fdab7dec20a3c2baa100a8de12b4bd48fb0aca834a419563e0754177122be864887751fbbb362afdd07c0e24c0b737e42a72e42cecbfdfa756637091b00",
+        sign: || eth_sign::sign_onboarding(NETWORK_ID_MAINNET, TEST_ADDRESS, OFF_CHAIN_ONBOARDING_ACTION, TEST_PRIVATE_KEY),
+        verify: |signature| {
+            let signer = SignOnboardingAction::new(Arc::new(SignWithKey::new(TEST_PRIVATE_KEY)?), NETWORK_ID_MAINNET);
+            Ok(signer.verify(signature, TEST_ADDRESS, OFF_CHAIN_ONBOARDING_ACTION)?)
+        },
+    },
+];
+
+/// Sign and verify every signature kind with the active backend.
+pub fn run() -> std::result::Result<SelfTestReport, SelfTestError> {
+    #[cfg(not(feature = "native-signer"))]
+    PythonSigner::get().map_err(|e| SelfTestError::BackendUnavailable {
+        backend: BACKEND,
+        reason: e.to_string(),
+    })?;
+
+    for check in CHECKS {
+        let signature = (check.sign)().map_err(|e| SelfTestError::SignFailed {
+            backend: BACKEND,
+            check: check.name,
+            reason: e.to_string(),
+        })?;
+        let verify_failed = |reason: String| SelfTestError::VerifyFailed {
+            backend: BACKEND,
+            check: check.name,
+            reason,
+        };
+        match (check.verify)(&signature) {
+            Ok(true) => {}
+            Ok(false) => return Err(verify_failed("not signed by the test key".to_string())),
+            Err(e) => return Err(verify_failed(e.to_string())),
+        }
+        // A valid but different signature means the backend does not derive k like
+        // the reference implementation.
+        if signature != check.expected {
+            return Err(SelfTestError::WrongSignature {
+                backend: BACKEND,
+                check: check.name,
+                expected: check.expected.to_string(),
+                actual: signature,
+            });
+        }
+    }
+    Ok(SelfTestReport {
+        backend: BACKEND,
+        checks: CHECKS.iter().map(|check| check.name).collect(),
+    })
+}
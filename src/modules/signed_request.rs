@@ -0,0 +1,310 @@
+//! STARK-signed write requests that can be produced offline and submitted later.
+//!
+//! A `RequestSigner` needs only a STARK signer and the market data of its network, so it
+//! can run on an air-gapped machine. The `SignedRequest` it returns serializes to JSON and
+//! holds the exact body `Private` would send; `Private::submit_signed` adds the API-key
+//! headers at send time.
+
+use super::super::constants::*;
+use super::super::helper::generate_random_client_id;
+use super::super::types::*;
+use super::super::Result;
+use crate::starkex::conditional_transfer::SignableConditionalTransfer;
+use crate::starkex::helpers::{get_transfer_erc20_fact, nonce_from_client_id, serialize_signature};
+use crate::starkex::market_registry::MarketRegistry;
+#[cfg(not(feature = "dummy_signature"))]
+use crate::starkex::{order::SignableOrder, quantums::check_order};
+use crate::starkex::signable::Signable;
+use crate::starkex::signers::StarkSigner;
+use crate::starkex::signing_pool::SigningPool;
+use crate::starkex::transfer::SignableTransfer;
+use crate::starkex::withdrawal::SignableWithdrawal;
+use crate::StarkError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignedRequestKind {
+    Order,
+    Transfer,
+    Withdraw,
+    FastWithdrawal,
+}
+
+impl SignedRequestKind {
+    /// The endpoint the request is posted to, relative to /v3/.
+    pub fn path(self) -> &'static str {
+        match self {
+            SignedRequestKind::Order => "orders",
+            SignedRequestKind::Transfer => "transfers",
+            SignedRequestKind::Withdraw => "withdrawals",
+            SignedRequestKind::FastWithdrawal => "fast-withdrawals",
+        }
+    }
+}
+
+/// The body of an ApiOrder, ApiTransfer, ApiWithdraw or ApiFastWithdrawal request, with
+/// its client id and STARK signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedRequest {
+    pub kind: SignedRequestKind,
+    /// Kept as the JSON text that was signed, so it is sent unchanged.
+    pub body: Box<RawValue>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedFields {
+    client_id: String,
+    signature: String,
+}
+
+impl SignedRequest {
+    pub fn new(kind: SignedRequestKind, body: &impl Serialize) -> Result<SignedRequest> {
+        Ok(SignedRequest {
+            kind,
+            body: serde_json::value::to_raw_value(body)?,
+        })
+    }
+
+    pub fn client_id(&self) -> Result<String> {
+        Ok(self.fields()?.client_id)
+    }
+
+    pub fn signature(&self) -> Result<String> {
+        Ok(self.fields()?.signature)
+    }
+
+    fn fields(&self) -> Result<SignedFields> {
+        Ok(serde_json::from_str(self.body.get())?)
+    }
+}
+
+/// Signs orders, transfers and withdrawals without network access.
+#[derive(Debug, Clone)]
+pub struct RequestSigner {
+    network_id: usize,
+    stark_signer: Option<Arc<dyn StarkSigner>>,
+    market_registry: Option<Arc<MarketRegistry>>,
+    signing_pool: Arc<SigningPool>,
+}
+
+impl RequestSigner {
+    /// A signer for network_id, with the markets of constants and the shared signing pool.
+    pub fn new(network_id: usize, stark_signer: Arc<dyn StarkSigner>) -> RequestSigner {
+        RequestSigner {
+            network_id,
+            stark_signer: Some(stark_signer),
+            market_registry: None,
+            signing_pool: SigningPool::shared(),
+        }
+    }
+
+    pub(crate) fn from_parts(
+        network_id: usize,
+        stark_signer: Option<Arc<dyn StarkSigner>>,
+        market_registry: Option<Arc<MarketRegistry>>,
+        signing_pool: Arc<SigningPool>,
+    ) -> RequestSigner {
+        RequestSigner {
+            network_id,
+            stark_signer,
+            market_registry,
+            signing_pool,
+        }
+    }
+
+    /// Take market and asset ids from registry, e.g. one saved with MarketRegistry::save
+    /// on an online machine.
+    pub fn with_market_registry(mut self, registry: Arc<MarketRegistry>) -> RequestSigner {
+        self.market_registry = Some(registry);
+        self
+    }
+
+    pub fn with_signing_pool(mut self, pool: Arc<SigningPool>) -> RequestSigner {
+        self.signing_pool = pool;
+        self
+    }
+
+    pub async fn order(&self, user_params: &ApiOrderParams<'_>) -> Result<SignedRequest> {
+        let client_id = match user_params.client_id {
+            Some(client_id) => client_id.to_owned(),
+            None => generate_random_client_id(),
+        };
+
+        cfg_if::cfg_if! {
+        if #[cfg(feature = "dummy_signature")] {
+            let signature = "blabla".to_string();
+        } else {
+                let registry = self.market_registry()?;
+                check_order(&registry.market(user_params.market)?, user_params.size, user_params.price)?;
+                let order = SignableOrder::with_registry(
+                    &registry,
+                    user_params.market,
+                    user_params.side,
+                    user_params.position_id,
+                    user_params.size,
+                    user_params.price,
+                    user_params.limit_fee,
+                    client_id.as_str(),
+                    user_params.expiration,
+                )?;
+                let signature = self.stark_sign(order).await?;
+            }
+        }
+
+        let expiration = iso_expiration(user_params.expiration)?;
+        let body = ApiOrder {
+            market: user_params.market,
+            side: user_params.side,
+            type_field: user_params.type_field,
+            size: user_params.size,
+            price: user_params.price,
+            time_in_force: user_params.time_in_force,
+            post_only: user_params.post_only,
+            limit_fee: user_params.limit_fee,
+            cancel_id: user_params.cancel_id,
+            trigger_price: user_params.trigger_price,
+            trailing_percent: user_params.trailing_percent,
+            expiration: expiration.as_str(),
+            client_id: client_id.as_str(),
+            signature: signature.as_str(),
+        };
+        SignedRequest::new(SignedRequestKind::Order, &body)
+    }
+
+    pub async fn transfer(&self, user_params: &TransferParams<'_>) -> Result<SignedRequest> {
+        let client_id = match user_params.client_id {
+            Some(client_id) => client_id.to_owned(),
+            None => generate_random_client_id(),
+        };
+
+        let registry = self.market_registry()?;
+        let transfer = SignableTransfer::with_registry(
+            &registry,
+            user_params.position_id,
+            user_params.receiver_position_id,
+            user_params.receiver_public_key,
+            user_params.amount,
+            &client_id,
+            user_params.expiration,
+        )?;
+        let signature = self.stark_sign(transfer).await?;
+
+        let expiration = iso_expiration(user_params.expiration)?;
+        let body = ApiTransfer {
+            amount: user_params.amount,
+            receiver_account_id: user_params.receiver_account_id,
+            expiration: expiration.as_str(),
+            client_id: client_id.as_str(),
+            signature: signature.as_str(),
+        };
+        SignedRequest::new(SignedRequestKind::Transfer, &body)
+    }
+
+    pub async fn withdraw(&self, user_params: &ApiWithdrawParams<'_>) -> Result<SignedRequest> {
+        let client_id = match user_params.client_id {
+            Some(client_id) => client_id.to_owned(),
+            None => generate_random_client_id(),
+        };
+
+        let registry = self.market_registry()?;
+        let withdraw = SignableWithdrawal::with_registry(
+            &registry,
+            user_params.position_id,
+            user_params.amount,
+            &client_id,
+            user_params.expiration,
+        )?;
+        let signature = self.stark_sign(withdraw).await?;
+
+        let expiration = iso_expiration(user_params.expiration)?;
+        let body = ApiWithdraw {
+            amount: user_params.amount,
+            asset: user_params.asset,
+            expiration: expiration.as_str(),
+            client_id: client_id.as_str(),
+            signature: signature.as_str(),
+        };
+        SignedRequest::new(SignedRequestKind::Withdraw, &body)
+    }
+
+    pub async fn fast_withdraw(&self, user_params: &ApiFastWithdrawalParams<'_>) -> Result<SignedRequest> {
+        let client_id = match user_params.client_id {
+            Some(client_id) => client_id.to_owned(),
+            None => generate_random_client_id(),
+        };
+
+        let (fact_address, token_address) = match self.network_id {
+            NETWORK_ID_MAINNET => (FACT_REGISTRY_CONTRACT_MAINNET, ASSET_USDC_CONTRACT_MAINNET),
+            NETWORK_ID_GOERLI => (FACT_REGISTRY_CONTRACT_GOERLI, ASSET_USDC_CONTRACT_GOERLI),
+            TESTNET_NETWORK_ID => (FACT_REGISTRY_CONTRACT_TESTNET, ASSET_USDC_CONTRACT_TESTNET),
+            network_id => return Err(Box::new(StarkError::UnknownNetwork(network_id))),
+        };
+
+        let fact = get_transfer_erc20_fact(
+            user_params.to_address,
+            COLLATERAL_TOKEN_DECIMALS,
+            user_params.credit_amount,
+            token_address,
+            nonce_from_client_id(&client_id),
+        )?;
+        let registry = self.market_registry()?;
+        let withdraw = SignableConditionalTransfer::with_registry(
+            &registry,
+            user_params.position_id,
+            user_params.lp_position_id,
+            user_params.lp_stark_key,
+            fact_address,
+            &fact,
+            user_params.credit_amount,
+            &client_id,
+            user_params.expiration,
+        )?;
+        let signature = self.stark_sign(withdraw).await?;
+
+        let expiration = iso_expiration(user_params.expiration)?;
+        let body = ApiFastWithdrawal {
+            credit_asset: user_params.credit_asset,
+            credit_amount: user_params.credit_amount,
+            debit_amount: user_params.debit_amount,
+            to_address: user_params.to_address,
+            lp_position_id: user_params.lp_position_id,
+            expiration: expiration.as_str(),
+            client_id: &client_id,
+            signature: signature.as_str(),
+        };
+        SignedRequest::new(SignedRequestKind::FastWithdrawal, &body)
+    }
+
+    pub(crate) fn market_registry(&self) -> Result<Arc<MarketRegistry>> {
+        match &self.market_registry {
+            Some(registry) if registry.network_id() != self.network_id => Err(Box::new(StarkError::NetworkMismatch {
+                expected: self.network_id,
+                actual: registry.network_id(),
+            })),
+            Some(registry) => Ok(registry.clone()),
+            None => Ok(MarketRegistry::constants(self.network_id)?),
+        }
+    }
+
+    // Hashing runs on the signing pool, like StarkKeySigner signatures.
+    pub(crate) async fn stark_sign(&self, message: impl Signable + Send + 'static) -> Result<String> {
+        let signer = self.stark_signer.as_ref().ok_or(StarkError::SignerNotConfigured)?;
+        let message_hash = self.signing_pool.run(move || message.calculate_hash()).await??;
+        let (r, s) = signer.sign(&message_hash).await?;
+        Ok(serialize_signature(&r, &s)?)
+    }
+}
+
+/// The expiration of a request body, e.g. "2023-11-14T22:13:20.000Z".
+fn iso_expiration(epoch_seconds: i64) -> Result<String> {
+    let datetime: DateTime<Utc> = DateTime::from_timestamp(epoch_seconds, 0).ok_or(StarkError::OutOfRange {
+        field: "expiration",
+        value: epoch_seconds.to_string(),
+    })?;
+    Ok(datetime.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+}
@@ -1,22 +1,24 @@
-use pyo3::prelude::*;
-use pyo3::types::PyList;
-use std::fs;
-use std::path::{Path, PathBuf};
-// use pyo3::Python;
-use crate::modules::eth_sign::eth_path_fix;
-
-pub fn stark_path_fix() -> String {
-    let compile_time_default_base = env!("CARGO_MANIFEST_DIR");
-    let compile_time_default = PathBuf::from(compile_time_default_base).join("src").join("stark").to_string_lossy().parse().unwrap();
-    match option_env!("ITAY_PY_RUST_ROOT") {
-        Some(path) => PathBuf::from(path).join("src").join("stark").to_string_lossy().parse().unwrap(),
-        None => match std::env::var("ITAY_PY_RUST_ROOT") {
-            Ok(path) => PathBuf::from(path).join("src").join("stark").to_string_lossy().parse().unwrap(),
-            Err(_) => compile_time_default
-        }
-    }
-}
+use super::super::Result;
+use num_bigint::BigUint;
+use crate::starkex::conditional_transfer::SignableConditionalTransfer;
+use crate::starkex::helpers::{get_transfer_erc20_fact, nonce_from_client_id};
+use crate::starkex::order::SignableOrder;
+#[cfg(feature = "native-signer")]
+use crate::starkex::{
+    helpers::{hex_to_biguint, serialize_signature},
+    market_registry::MarketRegistry,
+    signature,
+};
+#[cfg(feature = "native-signer")]
+use crate::StarkError;
+use crate::starkex::signable::Signable;
+use crate::starkex::transfer::SignableTransfer;
+use crate::starkex::withdrawal::SignableWithdrawal;
 
+#[cfg(not(feature = "native-signer"))]
+use super::stark_sign_py;
+
+#[allow(clippy::too_many_arguments)]
 pub fn sign_order(
     network_id: usize,
     market: &str,
@@ -28,43 +30,53 @@ pub fn sign_order(
     client_id: &str,
     expiration_epoch_seconds: i64,
     private_key: &str,
-) -> PyResult<String> {
-    let binding = stark_path_fix();
-    let path = Path::new(&binding);
-    println!("sign_order path: {:?}", path);
-    // let py_args = format!(
-    //     "sign_order({}, '{}', '{}', '{}', '{}', '{}', '{}', '{}', {}, '{}')",
-    //     network_id,
-    //     market,
-    //     side,
-    //     position_id,
-    //     human_size,
-    //     human_price,
-    //     limit_fee,
-    //     client_id,
-    //     expiration_epoch_seconds,
-    //     private_key
-    // );
-    // println!("sign_order py_args: {py_args}");
-    // Python::with_gil(|py| {
-    //     // PyO3 supports Python 3.7 and up.
-    //     println!("py.version_info(): {:?}", py.version_info());
-    //     assert!(py.version_info() == (3, 9, 9));
-    //     // assert!(py.version_info() >= (3, 7, 0));
-    // });
-    // let gil = Python::acquire_gil();
-    // let py = gil.python();
-    // println!("Python path: {:?}", py.import("sys").unwrap().getattr("path").unwrap());
-    let py_app = fs::read_to_string(path.join("stark_sign.py"))?;
-    let from_python = Python::with_gil(|py| -> PyResult<Py<PyAny>> {
-        let syspath: &PyList = py.import("sys")?.getattr("path")?.downcast::<PyList>()?;
-        syspath.insert(0, &path)?;
-        let app: Py<PyAny> = PyModule::from_code(py, &py_app, "", "")?
-            .getattr("sign_order")?
-            .into();
-        app.call1(
-            py,
-            (
+) -> Result<String> {
+    sign_order_with_seed(
+        network_id,
+        market,
+        side,
+        position_id,
+        human_size,
+        human_price,
+        limit_fee,
+        client_id,
+        expiration_epoch_seconds,
+        private_key,
+        None,
+    )
+}
+
+/// Like sign_order, with seed as extra entropy for k, like the seed of StarkWare's sign.
+#[allow(clippy::too_many_arguments)]
+pub fn sign_order_with_seed(
+    network_id: usize,
+    market: &str,
+    side: &str,
+    position_id: &str,
+    human_size: &str,
+    human_price: &str,
+    limit_fee: &str,
+    client_id: &str,
+    expiration_epoch_seconds: i64,
+    private_key: &str,
+    seed: Option<&BigUint>,
+) -> Result<String> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "native-signer")] {
+            let order = SignableOrder::new(
+                network_id,
+                market,
+                side,
+                position_id,
+                human_size,
+                human_price,
+                limit_fee,
+                client_id,
+                expiration_epoch_seconds,
+            )?;
+            Ok(order.sign_with_seed(private_key, seed)?)
+        } else {
+            stark_sign_py::sign_order(
                 network_id,
                 market,
                 side,
@@ -75,11 +87,106 @@ pub fn sign_order(
                 client_id,
                 expiration_epoch_seconds,
                 private_key,
-            ),
-        )
-    });
+                seed,
+            )
+        }
+    }
+}
+
+/// The arguments of sign_order for one order of sign_orders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderToSign<'a> {
+    pub market: &'a str,
+    pub side: &'a str,
+    pub position_id: &'a str,
+    pub human_size: &'a str,
+    pub human_price: &'a str,
+    pub limit_fee: &'a str,
+    pub client_id: &'a str,
+    pub expiration_epoch_seconds: i64,
+}
+
+/// Sign many orders with one key, giving the same signatures as sign_order, in the order of
+/// orders. The key and asset ids are parsed once, and the native backend spreads the orders
+/// over all cores. An order that cannot be signed only fails its own entry, and a panic fails
+/// the entries of its worker with StarkError::SignerPanicked.
+///
+/// This blocks the calling thread until every order is signed; from async code, call it
+/// through SigningPool::run or tokio::task::spawn_blocking.
+pub fn sign_orders(network_id: usize, orders: &[OrderToSign], private_key: &str) -> Vec<Result<String>> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "native-signer")] {
+            let shared = MarketRegistry::constants(network_id)
+                .and_then(|registry| Ok((registry, hex_to_biguint(private_key)?)));
+            let (registry, private_key) = match shared {
+                Ok(shared) => shared,
+                Err(e) => return orders.iter().map(|_| Err(e.clone().into())).collect(),
+            };
+            let sign = |order: &OrderToSign| -> std::result::Result<String, StarkError> {
+                let hash = SignableOrder::with_registry(
+                    &registry,
+                    order.market,
+                    order.side,
+                    order.position_id,
+                    order.human_size,
+                    order.human_price,
+                    order.limit_fee,
+                    order.client_id,
+                    order.expiration_epoch_seconds,
+                )?
+                .calculate_hash()?;
+                let (r, s) = signature::sign(&hash, &private_key, None)?;
+                serialize_signature(&r, &s)
+            };
+
+            let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+            let chunk_size = orders.len().div_ceil(threads).max(1);
+            let signatures: Vec<std::result::Result<String, StarkError>> = std::thread::scope(|scope| {
+                let workers: Vec<_> = orders
+                    .chunks(chunk_size)
+                    .map(|chunk| scope.spawn(|| chunk.iter().map(sign).collect::<Vec<_>>()))
+                    .collect();
+                workers
+                    .into_iter()
+                    .zip(orders.chunks(chunk_size))
+                    .flat_map(|(worker, chunk)| {
+                        worker.join().unwrap_or_else(|panic| {
+                            let reason = panic_message(panic.as_ref());
+                            chunk.iter().map(|_| Err(StarkError::SignerPanicked(reason.clone()))).collect()
+                        })
+                    })
+                    .collect()
+            });
+            signatures.into_iter().map(|signature| Ok(signature?)).collect()
+        } else {
+            orders
+                .iter()
+                .map(|order| {
+                    sign_order(
+                        network_id,
+                        order.market,
+                        order.side,
+                        order.position_id,
+                        order.human_size,
+                        order.human_price,
+                        order.limit_fee,
+                        order.client_id,
+                        order.expiration_epoch_seconds,
+                        private_key,
+                    )
+                })
+                .collect()
+        }
+    }
+}
 
-    Ok(from_python.unwrap().to_string())
+#[cfg(feature = "native-signer")]
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    match (panic.downcast_ref::<&str>(), panic.downcast_ref::<String>()) {
+        (Some(message), _) => message.to_string(),
+        (_, Some(message)) => message.clone(),
+        _ => "unknown panic".into(),
+    }
 }
 
 pub fn sign_withdraw(
@@ -89,32 +196,45 @@ pub fn sign_withdraw(
     client_id: &str,
     expiration_epoch_seconds: i64,
     private_key: &str,
-) -> PyResult<String> {
-    let binding = stark_path_fix();
-    let path = Path::new(&binding);
-    let py_app = fs::read_to_string(path.join("stark_sign.py"))?;
-    let from_python = Python::with_gil(|py| -> PyResult<Py<PyAny>> {
-        let syspath: &PyList = py.import("sys")?.getattr("path")?.downcast::<PyList>()?;
-        syspath.insert(0, &path)?;
-        let app: Py<PyAny> = PyModule::from_code(py, &py_app, "", "")?
-            .getattr("sign_withdraw")?
-            .into();
-        app.call1(
-            py,
-            (
+) -> Result<String> {
+    sign_withdraw_with_seed(network_id, position_id, amount, client_id, expiration_epoch_seconds, private_key, None)
+}
+
+/// Like sign_withdraw, with seed as extra entropy for k.
+pub fn sign_withdraw_with_seed(
+    network_id: usize,
+    position_id: &str,
+    amount: &str,
+    client_id: &str,
+    expiration_epoch_seconds: i64,
+    private_key: &str,
+    seed: Option<&BigUint>,
+) -> Result<String> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "native-signer")] {
+            let withdraw = SignableWithdrawal::new(
+                network_id,
+                position_id,
+                amount,
+                client_id,
+                expiration_epoch_seconds,
+            )?;
+            Ok(withdraw.sign_with_seed(private_key, seed)?)
+        } else {
+            stark_sign_py::sign_withdraw(
                 network_id,
                 position_id,
                 amount,
                 client_id,
                 expiration_epoch_seconds,
                 private_key,
-            ),
-        )
-    });
-
-    Ok(from_python.unwrap().to_string())
+                seed,
+            )
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn sign_fast_withdraw(
     network_id: usize,
     sender_position_id: &str,
@@ -125,23 +245,67 @@ pub fn sign_fast_withdraw(
     token_decimals: u8,
     human_amount: &str,
     token_address: &str,
-    // salt: usize,
     client_id: &str,
     expiration_epoch_seconds: i64,
     private_key: &str,
-) -> PyResult<String> {
-    let binding = stark_path_fix();
-    let path = Path::new(&binding);
-    let py_app = fs::read_to_string(path.join("stark_sign.py"))?;
-    let from_python = Python::with_gil(|py| -> PyResult<Py<PyAny>> {
-        let syspath: &PyList = py.import("sys")?.getattr("path")?.downcast::<PyList>()?;
-        syspath.insert(0, &path)?;
-        let app: Py<PyAny> = PyModule::from_code(py, &py_app, "", "")?
-            .getattr("sign_fast_withdraw")?
-            .into();
-        app.call1(
-            py,
-            (
+) -> Result<String> {
+    sign_fast_withdraw_with_seed(
+        network_id,
+        sender_position_id,
+        receiver_position_id,
+        receiver_public_key,
+        fact_registry_address,
+        recipient,
+        token_decimals,
+        human_amount,
+        token_address,
+        client_id,
+        expiration_epoch_seconds,
+        private_key,
+        None,
+    )
+}
+
+/// Like sign_fast_withdraw, with seed as extra entropy for k.
+#[allow(clippy::too_many_arguments)]
+pub fn sign_fast_withdraw_with_seed(
+    network_id: usize,
+    sender_position_id: &str,
+    receiver_position_id: &str,
+    receiver_public_key: &str,
+    fact_registry_address: &str,
+    recipient: &str,
+    token_decimals: u8,
+    human_amount: &str,
+    token_address: &str,
+    client_id: &str,
+    expiration_epoch_seconds: i64,
+    private_key: &str,
+    seed: Option<&BigUint>,
+) -> Result<String> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "native-signer")] {
+            let fact = get_transfer_erc20_fact(
+                recipient,
+                token_decimals,
+                human_amount,
+                token_address,
+                nonce_from_client_id(client_id),
+            )?;
+            let withdraw = SignableConditionalTransfer::new(
+                network_id,
+                sender_position_id,
+                receiver_position_id,
+                receiver_public_key,
+                fact_registry_address,
+                &fact,
+                human_amount,
+                client_id,
+                expiration_epoch_seconds,
+            )?;
+            Ok(withdraw.sign_with_seed(private_key, seed)?)
+        } else {
+            stark_sign_py::sign_fast_withdraw(
                 network_id,
                 sender_position_id,
                 receiver_position_id,
@@ -151,17 +315,16 @@ pub fn sign_fast_withdraw(
                 token_decimals,
                 human_amount,
                 token_address,
-                // salt,
                 client_id,
                 expiration_epoch_seconds,
                 private_key,
-            ),
-        )
-    });
-
-    Ok(from_python.unwrap().to_string())
+                seed,
+            )
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn sign_transfer(
     network_id: usize,
     sender_position_id: &str,
@@ -171,19 +334,47 @@ pub fn sign_transfer(
     client_id: &str,
     expiration_epoch_seconds: i64,
     private_key: &str,
-) -> PyResult<String> {
-    let binding = stark_path_fix();
-    let path = Path::new(&binding);
-    let py_app = fs::read_to_string(path.join("stark_sign.py"))?;
-    let from_python = Python::with_gil(|py| -> PyResult<Py<PyAny>> {
-        let syspath: &PyList = py.import("sys")?.getattr("path")?.downcast::<PyList>()?;
-        syspath.insert(0, &path)?;
-        let app: Py<PyAny> = PyModule::from_code(py, &py_app, "", "")?
-            .getattr("sign_transfer")?
-            .into();
-        app.call1(
-            py,
-            (
+) -> Result<String> {
+    sign_transfer_with_seed(
+        network_id,
+        sender_position_id,
+        receiver_position_id,
+        receiver_public_key,
+        human_amount,
+        client_id,
+        expiration_epoch_seconds,
+        private_key,
+        None,
+    )
+}
+
+/// Like sign_transfer, with seed as extra entropy for k.
+#[allow(clippy::too_many_arguments)]
+pub fn sign_transfer_with_seed(
+    network_id: usize,
+    sender_position_id: &str,
+    receiver_position_id: &str,
+    receiver_public_key: &str,
+    human_amount: &str,
+    client_id: &str,
+    expiration_epoch_seconds: i64,
+    private_key: &str,
+    seed: Option<&BigUint>,
+) -> Result<String> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "native-signer")] {
+            let transfer = SignableTransfer::new(
+                network_id,
+                sender_position_id,
+                receiver_position_id,
+                receiver_public_key,
+                human_amount,
+                client_id,
+                expiration_epoch_seconds,
+            )?;
+            Ok(transfer.sign_with_seed(private_key, seed)?)
+        } else {
+            stark_sign_py::sign_transfer(
                 network_id,
                 sender_position_id,
                 receiver_position_id,
@@ -192,9 +383,123 @@ pub fn sign_transfer(
                 client_id,
                 expiration_epoch_seconds,
                 private_key,
-            ),
-        )
-    });
+                seed,
+            )
+        }
+    }
+}
+
+// Verification is always native: it only recomputes the hash and checks (r, s)
+// against the public key, so it does not depend on the signing backend.
+
+/// Verify a signature produced by sign_order against a STARK public key.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_order(
+    network_id: usize,
+    market: &str,
+    side: &str,
+    position_id: &str,
+    human_size: &str,
+    human_price: &str,
+    limit_fee: &str,
+    client_id: &str,
+    expiration_epoch_seconds: i64,
+    signature: &str,
+    public_key: &str,
+) -> Result<bool> {
+    let order = SignableOrder::new(
+        network_id,
+        market,
+        side,
+        position_id,
+        human_size,
+        human_price,
+        limit_fee,
+        client_id,
+        expiration_epoch_seconds,
+    )?;
+    Ok(order.verify(signature, public_key)?)
+}
+
+/// Verify a signature produced by sign_withdraw against a STARK public key.
+pub fn verify_withdraw(
+    network_id: usize,
+    position_id: &str,
+    amount: &str,
+    client_id: &str,
+    expiration_epoch_seconds: i64,
+    signature: &str,
+    public_key: &str,
+) -> Result<bool> {
+    let withdraw = SignableWithdrawal::new(
+        network_id,
+        position_id,
+        amount,
+        client_id,
+        expiration_epoch_seconds,
+    )?;
+    Ok(withdraw.verify(signature, public_key)?)
+}
 
-    Ok(from_python.unwrap().to_string())
+/// Verify a signature produced by sign_fast_withdraw against a STARK public key.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_fast_withdraw(
+    network_id: usize,
+    sender_position_id: &str,
+    receiver_position_id: &str,
+    receiver_public_key: &str,
+    fact_registry_address: &str,
+    recipient: &str,
+    token_decimals: u8,
+    human_amount: &str,
+    token_address: &str,
+    client_id: &str,
+    expiration_epoch_seconds: i64,
+    signature: &str,
+    public_key: &str,
+) -> Result<bool> {
+    let fact = get_transfer_erc20_fact(
+        recipient,
+        token_decimals,
+        human_amount,
+        token_address,
+        nonce_from_client_id(client_id),
+    )?;
+    let withdraw = SignableConditionalTransfer::new(
+        network_id,
+        sender_position_id,
+        receiver_position_id,
+        receiver_public_key,
+        fact_registry_address,
+        &fact,
+        human_amount,
+        client_id,
+        expiration_epoch_seconds,
+    )?;
+    Ok(withdraw.verify(signature, public_key)?)
+}
+
+/// Verify a signature produced by sign_transfer against a STARK public key.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_transfer(
+    network_id: usize,
+    sender_position_id: &str,
+    receiver_position_id: &str,
+    receiver_public_key: &str,
+    human_amount: &str,
+    client_id: &str,
+    expiration_epoch_seconds: i64,
+    signature: &str,
+    public_key: &str,
+) -> Result<bool> {
+    let transfer = SignableTransfer::new(
+        network_id,
+        sender_position_id,
+        receiver_position_id,
+        receiver_public_key,
+        human_amount,
+        client_id,
+        expiration_epoch_seconds,
+    )?;
+    Ok(transfer.verify(signature, public_key)?)
 }
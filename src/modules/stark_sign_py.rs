@@ -0,0 +1,125 @@
+use super::super::Result;
+use super::python_signer::PythonSigner;
+use num_bigint::BigUint;
+
+#[allow(clippy::too_many_arguments)]
+pub fn sign_order(
+    network_id: usize,
+    market: &str,
+    side: &str,
+    position_id: &str,
+    human_size: &str,
+    human_price: &str,
+    limit_fee: &str,
+    client_id: &str,
+    expiration_epoch_seconds: i64,
+    private_key: &str,
+    seed: Option<&BigUint>,
+) -> Result<String> {
+    Ok(PythonSigner::get()?.call_with_seed(
+        "sign_order",
+        (
+            network_id,
+            market,
+            side,
+            position_id,
+            human_size,
+            human_price,
+            limit_fee,
+            client_id,
+            expiration_epoch_seconds,
+            private_key,
+        ),
+        seed,
+    )?)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn sign_withdraw(
+    network_id: usize,
+    position_id: &str,
+    amount: &str,
+    client_id: &str,
+    expiration_epoch_seconds: i64,
+    private_key: &str,
+    seed: Option<&BigUint>,
+) -> Result<String> {
+    Ok(PythonSigner::get()?.call_with_seed(
+        "sign_withdraw",
+        (
+            network_id,
+            position_id,
+            amount,
+            client_id,
+            expiration_epoch_seconds,
+            private_key,
+        ),
+        seed,
+    )?)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn sign_fast_withdraw(
+    network_id: usize,
+    sender_position_id: &str,
+    receiver_position_id: &str,
+    receiver_public_key: &str,
+    fact_registry_address: &str,
+    recipient: &str,
+    token_decimals: u8,
+    human_amount: &str,
+    token_address: &str,
+    // salt: usize,
+    client_id: &str,
+    expiration_epoch_seconds: i64,
+    private_key: &str,
+    seed: Option<&BigUint>,
+) -> Result<String> {
+    Ok(PythonSigner::get()?.call_with_seed(
+        "sign_fast_withdraw",
+        (
+            network_id,
+            sender_position_id,
+            receiver_position_id,
+            receiver_public_key,
+            fact_registry_address,
+            recipient,
+            token_decimals,
+            human_amount,
+            token_address,
+            // salt,
+            client_id,
+            expiration_epoch_seconds,
+            private_key,
+        ),
+        seed,
+    )?)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn sign_transfer(
+    network_id: usize,
+    sender_position_id: &str,
+    receiver_position_id: &str,
+    receiver_public_key: &str,
+    human_amount: &str,
+    client_id: &str,
+    expiration_epoch_seconds: i64,
+    private_key: &str,
+    seed: Option<&BigUint>,
+) -> Result<String> {
+    Ok(PythonSigner::get()?.call_with_seed(
+        "sign_transfer",
+        (
+            network_id,
+            sender_position_id,
+            receiver_position_id,
+            receiver_public_key,
+            human_amount,
+            client_id,
+            expiration_epoch_seconds,
+            private_key,
+        ),
+        seed,
+    )?)
+}
@@ -5,25 +5,25 @@ from starkex.transfer import SignableTransfer
 from web3 import Web3
 from starkex.helpers import nonce_from_client_id
 
-def sign_order(network_id, maraket, side, position_id, human_size, human_price, limit_fee, client_id, expiration_epoch_seconds, private_key):
+def sign_order(network_id, maraket, side, position_id, human_size, human_price, limit_fee, client_id, expiration_epoch_seconds, private_key, seed=None):
     order = SignableOrder(network_id, maraket, side, position_id, human_size, human_price, limit_fee, client_id, expiration_epoch_seconds)
-    signature = order.sign(private_key)
+    signature = order.sign(private_key, seed)
     return signature
 
-def sign_withdraw(network_id, position_id, human_size, client_id, expiration_epoch_seconds, private_key):
+def sign_withdraw(network_id, position_id, human_size, client_id, expiration_epoch_seconds, private_key, seed=None):
     withdraw = SignableWithdrawal(network_id, position_id, human_size, client_id, expiration_epoch_seconds)
-    signature = withdraw.sign(private_key)
+    signature = withdraw.sign(private_key, seed)
     return signature
 
-def sign_fast_withdraw(network_id, sender_position_id, receiver_position_id, receiver_public_key, fact_registry_address, recipient, token_decimals, human_amount, token_address, client_id, expiration_epoch_seconds, private_key):
+def sign_fast_withdraw(network_id, sender_position_id, receiver_position_id, receiver_public_key, fact_registry_address, recipient, token_decimals, human_amount, token_address, client_id, expiration_epoch_seconds, private_key, seed=None):
     fact = get_transfer_erc20_fact(recipient, token_decimals, human_amount, token_address, nonce_from_client_id(client_id),)
     withdraw = SignableConditionalTransfer(network_id, sender_position_id, receiver_position_id, receiver_public_key, fact_registry_address, fact, human_amount, client_id, expiration_epoch_seconds)
-    signature = withdraw.sign(private_key)
+    signature = withdraw.sign(private_key, seed)
     return signature
 
-def sign_transfer(network_id, sender_position_id, receiver_position_id, receiver_public_key,  human_amount, client_id, expiration_epoch_seconds, private_key):
+def sign_transfer(network_id, sender_position_id, receiver_position_id, receiver_public_key,  human_amount, client_id, expiration_epoch_seconds, private_key, seed=None):
     transfer = SignableTransfer(network_id, sender_position_id, receiver_position_id, receiver_public_key, human_amount, client_id, expiration_epoch_seconds)
-    signature = transfer.sign(private_key)
+    signature = transfer.sign(private_key, seed)
     return signature
 
 
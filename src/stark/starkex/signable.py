@@ -27,9 +27,9 @@ class Signable(object):
             self._hash = self._calculate_hash()
         return self._hash
 
-    def sign(self, private_key_hex):
+    def sign(self, private_key_hex, seed=None):
         """Sign the hash of the object using the given private key."""
-        r, s = sign(self.hash, int(private_key_hex, 16))
+        r, s = sign(self.hash, int(private_key_hex, 16), seed)
         return serialize_signature(r, s)
 
     def verify_signature(self, signature_hex, public_key_hex):
@@ -1,6 +1,7 @@
 import ctypes
 import secrets
 import os
+import pkgutil
 from typing import Optional, Tuple
 import json
 import math
@@ -8,9 +9,7 @@ from starkex.starkex_resources.python_signature import (
     inv_mod_curve_size,
 )
 
-PEDERSEN_HASH_POINT_FILENAME = os.path.join(
-    os.path.dirname(__file__), 'pedersen_params.json')
-PEDERSEN_PARAMS = json.load(open(PEDERSEN_HASH_POINT_FILENAME))
+PEDERSEN_PARAMS = json.loads(pkgutil.get_data(__package__, 'pedersen_params.json'))
 
 EC_ORDER = PEDERSEN_PARAMS['EC_ORDER']
 
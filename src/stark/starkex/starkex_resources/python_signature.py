@@ -17,7 +17,7 @@
 import hashlib
 import json
 import math
-import os
+import pkgutil
 import random
 from typing import Optional, Tuple, Union
 
@@ -25,9 +25,7 @@ from ecdsa.rfc6979 import generate_k
 
 from .math_utils import ECPoint, div_mod, ec_add, ec_double, ec_mult, is_quad_residue, sqrt_mod
 
-PEDERSEN_HASH_POINT_FILENAME = os.path.join(
-    os.path.dirname(__file__), 'pedersen_params.json')
-PEDERSEN_PARAMS = json.load(open(PEDERSEN_HASH_POINT_FILENAME))
+PEDERSEN_PARAMS = json.loads(pkgutil.get_data(__package__, 'pedersen_params.json'))
 
 FIELD_PRIME = PEDERSEN_PARAMS['FIELD_PRIME']
 FIELD_GEN = PEDERSEN_PARAMS['FIELD_GEN']
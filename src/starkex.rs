@@ -0,0 +1,14 @@
+pub mod conditional_transfer;
+pub mod constants;
+pub mod helpers;
+pub mod market_registry;
+pub mod math_utils;
+pub mod order;
+pub mod quantums;
+pub mod remote_signer;
+pub mod signable;
+pub mod signature;
+pub mod signers;
+pub mod signing_pool;
+pub mod transfer;
+pub mod withdrawal;
@@ -0,0 +1,149 @@
+use super::constants::*;
+use super::helpers::*;
+use super::market_registry::MarketRegistry;
+use super::signable::Signable;
+use super::signature::pedersen_hash;
+use crate::constants::COLLATERAL_ASSET;
+use crate::error::StarkError;
+use num_bigint::BigUint;
+use std::fmt;
+
+/// A conditional transfer (e.g. a fast withdrawal) as hashed for the STARK signature,
+/// with the amount in quantums.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarkwareConditionalTransfer {
+    pub asset_id_collateral: BigUint,
+    pub sender_position_id: u64,
+    pub receiver_position_id: u64,
+    pub receiver_public_key: BigUint,
+    pub condition: BigUint,
+    pub quantums_amount: u64,
+    pub nonce: u64,
+    pub expiration_epoch_hours: i64,
+}
+
+impl StarkwareConditionalTransfer {
+    /// Calculate the hash of the conditional transfer, the message signed with the STARK key.
+    pub fn hash(&self) -> Result<BigUint, StarkError> {
+        // The transfer asset and fee asset are always the collateral asset.
+        // Fees are not supported for conditional transfers.
+        let asset_ids = pedersen_hash(&[
+            &self.asset_id_collateral,
+            &BigUint::from(CONDITIONAL_TRANSFER_FEE_ASSET_ID),
+        ])?;
+
+        let part_1 = pedersen_hash(&[
+            &pedersen_hash(&[&asset_ids, &self.receiver_public_key])?,
+            &self.condition,
+        ])?;
+
+        let mut part_2 = BigUint::from(self.sender_position_id);
+        part_2 <<= CONDITIONAL_TRANSFER_FIELD_BIT_LENGTHS.position_id;
+        part_2 += self.receiver_position_id;
+        part_2 <<= CONDITIONAL_TRANSFER_FIELD_BIT_LENGTHS.position_id;
+        part_2 += self.sender_position_id;
+        part_2 <<= CONDITIONAL_TRANSFER_FIELD_BIT_LENGTHS.nonce;
+        part_2 += self.nonce;
+
+        let mut part_3 = BigUint::from(CONDITIONAL_TRANSFER_PREFIX);
+        part_3 <<= CONDITIONAL_TRANSFER_FIELD_BIT_LENGTHS.quantums_amount;
+        part_3 += self.quantums_amount;
+        part_3 <<= CONDITIONAL_TRANSFER_FIELD_BIT_LENGTHS.quantums_amount;
+        part_3 += CONDITIONAL_TRANSFER_MAX_AMOUNT_FEE;
+        part_3 <<= CONDITIONAL_TRANSFER_FIELD_BIT_LENGTHS.expiration_epoch_hours;
+        part_3 += packed_epoch_hours(
+            self.expiration_epoch_hours,
+            CONDITIONAL_TRANSFER_FIELD_BIT_LENGTHS.expiration_epoch_hours,
+        )?;
+        part_3 <<= CONDITIONAL_TRANSFER_PADDING_BITS;
+
+        pedersen_hash(&[&pedersen_hash(&[&part_1, &part_2])?, &part_3])
+    }
+}
+
+impl fmt::Display for StarkwareConditionalTransfer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "StarkwareConditionalTransfer")?;
+        writeln!(f, "  asset_id_collateral: {:#x}", self.asset_id_collateral)?;
+        writeln!(f, "  sender_position_id: {}", self.sender_position_id)?;
+        writeln!(f, "  receiver_position_id: {}", self.receiver_position_id)?;
+        writeln!(f, "  receiver_public_key: {:#x}", self.receiver_public_key)?;
+        writeln!(f, "  condition: {:#x}", self.condition)?;
+        writeln!(f, "  quantums_amount: {}", self.quantums_amount)?;
+        writeln!(f, "  nonce: {}", self.nonce)?;
+        write!(f, "  expiration_epoch_hours: {}", display_epoch_hours(self.expiration_epoch_hours))
+    }
+}
+
+/// Wrapper object to convert a conditional transfer, and hash and sign it.
+#[derive(Debug, Clone)]
+pub struct SignableConditionalTransfer {
+    message: StarkwareConditionalTransfer,
+}
+
+impl SignableConditionalTransfer {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        network_id: usize,
+        sender_position_id: &str,
+        receiver_position_id: &str,
+        receiver_public_key: &str,
+        fact_registry_address: &str,
+        fact: &[u8],
+        human_amount: &str,
+        client_id: &str,
+        expiration_epoch_seconds: i64,
+    ) -> Result<SignableConditionalTransfer, StarkError> {
+        SignableConditionalTransfer::with_registry(
+            &*MarketRegistry::constants(network_id)?,
+            sender_position_id,
+            receiver_position_id,
+            receiver_public_key,
+            fact_registry_address,
+            fact,
+            human_amount,
+            client_id,
+            expiration_epoch_seconds,
+        )
+    }
+
+    /// Like new, with the collateral asset id taken from registry.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_registry(
+        registry: &MarketRegistry,
+        sender_position_id: &str,
+        receiver_position_id: &str,
+        receiver_public_key: &str,
+        fact_registry_address: &str,
+        fact: &[u8],
+        human_amount: &str,
+        client_id: &str,
+        expiration_epoch_seconds: i64,
+    ) -> Result<SignableConditionalTransfer, StarkError> {
+        let quantums_amount = to_quantums_exact(&parse_decimal(human_amount)?, COLLATERAL_ASSET)?;
+
+        Ok(SignableConditionalTransfer {
+            message: StarkwareConditionalTransfer {
+                asset_id_collateral: registry.collateral_asset_id()?,
+                sender_position_id: parse_position_id(sender_position_id)?,
+                receiver_position_id: parse_position_id(receiver_position_id)?,
+                receiver_public_key: parse_field_element(receiver_public_key, "receiver_public_key")?,
+                condition: fact_to_condition(fact_registry_address, fact)?,
+                quantums_amount: to_u64(&quantums_amount, "quantums_amount")?,
+                nonce: nonce_from_client_id(client_id),
+                expiration_epoch_hours: expiration_epoch_hours(expiration_epoch_seconds),
+            },
+        })
+    }
+
+    /// The quantized conditional transfer that is hashed and signed.
+    pub fn message(&self) -> &StarkwareConditionalTransfer {
+        &self.message
+    }
+}
+
+impl Signable for SignableConditionalTransfer {
+    fn calculate_hash(&self) -> Result<BigUint, StarkError> {
+        self.message.hash()
+    }
+}
@@ -0,0 +1,94 @@
+//! Constants related to creating hashes of Starkware objects.
+
+pub const ONE_HOUR_IN_SECONDS: i64 = 60 * 60;
+pub const ORDER_SIGNATURE_EXPIRATION_BUFFER_HOURS: i64 = 24 * 7; // Seven days.
+
+pub const TRANSFER_PREFIX: u64 = 4;
+pub const TRANSFER_PADDING_BITS: usize = 81;
+pub const CONDITIONAL_TRANSFER_PADDING_BITS: usize = 81;
+pub const CONDITIONAL_TRANSFER_PREFIX: u64 = 5;
+pub const ORDER_PREFIX: u64 = 3;
+pub const ORDER_PADDING_BITS: usize = 17;
+pub const WITHDRAWAL_PADDING_BITS: usize = 49;
+pub const WITHDRAWAL_PREFIX: u64 = 6;
+
+// Note: Fees are not supported for conditional transfers or transfers.
+pub const TRANSFER_FEE_ASSET_ID: u64 = 0;
+pub const TRANSFER_MAX_AMOUNT_FEE: u64 = 0;
+pub const CONDITIONAL_TRANSFER_FEE_ASSET_ID: u64 = 0;
+pub const CONDITIONAL_TRANSFER_MAX_AMOUNT_FEE: u64 = 0;
+
+pub struct TransferFieldBitLengths {
+    pub asset_id: usize,
+    pub receiver_public_key: usize,
+    pub position_id: usize,
+    pub quantums_amount: usize,
+    pub nonce: usize,
+    pub expiration_epoch_hours: usize,
+}
+
+pub const TRANSFER_FIELD_BIT_LENGTHS: TransferFieldBitLengths = TransferFieldBitLengths {
+    asset_id: 250,
+    receiver_public_key: 251,
+    position_id: 64,
+    quantums_amount: 64,
+    nonce: 32,
+    expiration_epoch_hours: 32,
+};
+
+pub struct ConditionalTransferFieldBitLengths {
+    pub asset_id: usize,
+    pub receiver_public_key: usize,
+    pub position_id: usize,
+    pub condition: usize,
+    pub quantums_amount: usize,
+    pub nonce: usize,
+    pub expiration_epoch_hours: usize,
+}
+
+pub const CONDITIONAL_TRANSFER_FIELD_BIT_LENGTHS: ConditionalTransferFieldBitLengths =
+    ConditionalTransferFieldBitLengths {
+        asset_id: 250,
+        receiver_public_key: 251,
+        position_id: 64,
+        condition: 251,
+        quantums_amount: 64,
+        nonce: 32,
+        expiration_epoch_hours: 32,
+    };
+
+pub struct OrderFieldBitLengths {
+    pub asset_id_synthetic: usize,
+    pub asset_id_collateral: usize,
+    pub asset_id_fee: usize,
+    pub quantums_amount: usize,
+    pub nonce: usize,
+    pub position_id: usize,
+    pub expiration_epoch_hours: usize,
+}
+
+pub const ORDER_FIELD_BIT_LENGTHS: OrderFieldBitLengths = OrderFieldBitLengths {
+    asset_id_synthetic: 128,
+    asset_id_collateral: 250,
+    asset_id_fee: 250,
+    quantums_amount: 64,
+    nonce: 32,
+    position_id: 64,
+    expiration_epoch_hours: 32,
+};
+
+pub struct WithdrawalFieldBitLengths {
+    pub asset_id: usize,
+    pub position_id: usize,
+    pub nonce: usize,
+    pub quantums_amount: usize,
+    pub expiration_epoch_hours: usize,
+}
+
+pub const WITHDRAWAL_FIELD_BIT_LENGTHS: WithdrawalFieldBitLengths = WithdrawalFieldBitLengths {
+    asset_id: 250,
+    position_id: 64,
+    nonce: 32,
+    quantums_amount: 64,
+    expiration_epoch_hours: 32,
+};
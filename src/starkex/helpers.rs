@@ -0,0 +1,266 @@
+use super::constants::ORDER_FIELD_BIT_LENGTHS;
+use super::quantums::{to_quantums, Rounding};
+use super::signature::{private_key_to_ec_point_on_stark_curve, private_to_stark_key, PEDERSEN_PARAMS};
+use crate::constants::*;
+use crate::error::{QuantumsError, StarkError};
+use bigdecimal::{BigDecimal, RoundingMode};
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{Num, One, ToPrimitive};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+use std::num::NonZeroU64;
+use std::str::FromStr;
+
+// Precision of the default Python decimal context used by starkex/helpers.py.
+const DECIMAL_PRECISION: u64 = 28;
+
+pub fn strip_hex_prefix(input: &str) -> &str {
+    input.strip_prefix("0x").unwrap_or(input)
+}
+
+pub fn hex_to_biguint(input: &str) -> Result<BigUint, StarkError> {
+    BigUint::from_str_radix(strip_hex_prefix(input), 16)
+        .map_err(|_| StarkError::InvalidHex(input.to_string()))
+}
+
+/// Normalize to a 32-byte hex string without 0x prefix.
+pub fn int_to_hex_32(x: &BigUint) -> Result<String, StarkError> {
+    let padded_hex = format!("{:0>64}", x.to_str_radix(16));
+    if padded_hex.len() != 64 {
+        return Err(StarkError::OutOfRange {
+            field: "int_to_hex_32",
+            value: x.to_string(),
+        });
+    }
+    Ok(padded_hex)
+}
+
+/// Convert a signature from an r, s pair to a 32-byte hex string.
+pub fn serialize_signature(r: &BigUint, s: &BigUint) -> Result<String, StarkError> {
+    Ok(int_to_hex_32(r)? + &int_to_hex_32(s)?)
+}
+
+/// Split a serialized signature (32-byte r followed by 32-byte s, in hex) into (r, s).
+pub fn deserialize_signature(signature: &str) -> Result<(BigUint, BigUint), StarkError> {
+    let hex = strip_hex_prefix(signature);
+    if hex.len() != 128 || !hex.is_ascii() {
+        return Err(StarkError::InvalidSignature(signature.to_string()));
+    }
+    let (r, s) = hex.split_at(64);
+    let invalid = |_| StarkError::InvalidSignature(signature.to_string());
+    Ok((
+        BigUint::from_str_radix(r, 16).map_err(invalid)?,
+        BigUint::from_str_radix(s, 16).map_err(invalid)?,
+    ))
+}
+
+pub fn collateral_asset_id(network_id: usize) -> Result<BigUint, StarkError> {
+    let asset_id = match network_id {
+        NETWORK_ID_MAINNET => COLLATERAL_ASSET_ID_MAINNET,
+        NETWORK_ID_GOERLI => COLLATERAL_ASSET_ID_GOERLI,
+        TESTNET_NETWORK_ID => COLLATERAL_ASSET_ID_TESTNET,
+        _ => return Err(StarkError::UnknownNetwork(network_id)),
+    };
+    hex_to_biguint(asset_id)
+}
+
+pub fn synthetic_asset(market: &str) -> Result<&'static SyntheticAsset, StarkError> {
+    SYNTHETIC_ASSETS
+        .iter()
+        .find(|asset| asset.market == market)
+        .ok_or_else(|| StarkError::UnknownMarket(market.to_string()))
+}
+
+pub fn asset_resolution(asset: &str) -> Result<&'static str, StarkError> {
+    if asset == COLLATERAL_ASSET {
+        return Ok(COLLATERAL_ASSET_RESOLUTION);
+    }
+    SYNTHETIC_ASSETS
+        .iter()
+        .find(|synthetic| synthetic.asset == asset)
+        .map(|synthetic| synthetic.resolution)
+        .ok_or_else(|| StarkError::UnknownAsset(asset.to_string()))
+}
+
+pub fn parse_decimal(value: &str) -> Result<BigDecimal, StarkError> {
+    BigDecimal::from_str(value.trim()).map_err(|_| StarkError::InvalidNumber(value.to_string()))
+}
+
+/// Rounds to the precision of the Python decimal context. Returns None if rounding was
+/// needed but no rounding mode was given, i.e. the value is inexact.
+pub(crate) fn round_to_context(value: &BigDecimal, rounding: Option<RoundingMode>) -> Option<BigDecimal> {
+    if value.digits() <= DECIMAL_PRECISION {
+        return Some(value.clone());
+    }
+    let precision = NonZeroU64::new(DECIMAL_PRECISION).unwrap();
+    match rounding {
+        Some(mode) => Some(value.with_precision_round(precision, mode)),
+        None => {
+            let rounded = value.with_precision_round(precision, RoundingMode::HalfEven);
+            if &rounded == value {
+                Some(rounded)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Convert a human-readable amount to an integer amount of quantums.
+///
+/// If the provided human_amount is not a multiple of the quantum size,
+/// an error will be returned.
+pub fn to_quantums_exact(human_amount: &BigDecimal, asset: &str) -> Result<BigUint, StarkError> {
+    Ok(to_quantums("amount", human_amount, asset_resolution(asset)?, Rounding::Exact)?)
+}
+
+/// Convert a human-readable amount to an integer amount of quantums.
+///
+/// If the provided human_amount is not a multiple of the quantum size,
+/// the result will be rounded down to the nearest integer.
+pub fn to_quantums_round_down(human_amount: &BigDecimal, asset: &str) -> Result<BigUint, StarkError> {
+    Ok(to_quantums("amount", human_amount, asset_resolution(asset)?, Rounding::Down)?)
+}
+
+/// Convert a human-readable amount to an integer amount of quantums.
+///
+/// If the provided human_amount is not a multiple of the quantum size,
+/// the result will be rounded up to the nearest integer.
+pub fn to_quantums_round_up(human_amount: &BigDecimal, asset: &str) -> Result<BigUint, StarkError> {
+    Ok(to_quantums("amount", human_amount, asset_resolution(asset)?, Rounding::Up)?)
+}
+
+pub(crate) fn to_u64(value: &BigUint, field: &'static str) -> Result<u64, StarkError> {
+    value.to_u64().ok_or(StarkError::OutOfRange {
+        field,
+        value: value.to_string(),
+    })
+}
+
+/// Parse a hex string as an element of the STARK field, e.g. a public key.
+pub(crate) fn parse_field_element(value: &str, field: &'static str) -> Result<BigUint, StarkError> {
+    let element = hex_to_biguint(value)?;
+    if element >= PEDERSEN_PARAMS.field_prime {
+        return Err(StarkError::OutOfRange {
+            field,
+            value: value.to_string(),
+        });
+    }
+    Ok(element)
+}
+
+pub(crate) fn parse_position_id(position_id: &str) -> Result<u64, StarkError> {
+    position_id
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| StarkError::InvalidNumber(position_id.to_string()))
+}
+
+/// Convert to a Unix timestamp (in hours), rounding up like math.ceil(float(seconds) / 3600).
+pub(crate) fn expiration_epoch_hours(expiration_epoch_seconds: i64) -> i64 {
+    (expiration_epoch_seconds as f64 / super::constants::ONE_HOUR_IN_SECONDS as f64).ceil() as i64
+}
+
+/// expiration_epoch_hours as packed into a message, which must fit in bits bits.
+pub(crate) fn packed_epoch_hours(expiration_epoch_hours: i64, bits: usize) -> Result<u64, StarkError> {
+    u64::try_from(expiration_epoch_hours)
+        .ok()
+        .filter(|hours| hours >> bits == 0)
+        .ok_or(StarkError::OutOfRange {
+            field: "expiration_epoch_hours",
+            value: expiration_epoch_hours.to_string(),
+        })
+}
+
+/// Epoch hours with the UTC time they stand for, e.g. "472392 (2023-11-22T00:00:00Z)".
+pub(crate) fn display_epoch_hours(expiration_epoch_hours: i64) -> String {
+    let time = expiration_epoch_hours
+        .checked_mul(super::constants::ONE_HOUR_IN_SECONDS)
+        .and_then(|seconds| chrono::DateTime::from_timestamp(seconds, 0));
+    match time {
+        Some(time) => format!("{} ({})", expiration_epoch_hours, time.format("%Y-%m-%dT%H:%M:%SZ")),
+        None => expiration_epoch_hours.to_string(),
+    }
+}
+
+/// Generate a nonce deterministically from an arbitrary string.
+pub fn nonce_from_client_id(client_id: &str) -> u64 {
+    let digest = Sha256::digest(client_id.as_bytes());
+    let nonce = BigUint::from_bytes_be(&digest) % (BigUint::one() << ORDER_FIELD_BIT_LENGTHS.nonce);
+    nonce.to_u64().unwrap()
+}
+
+// The 20 bytes of an Ethereum address, which Solidity packs as they are.
+fn decode_address(value: &str) -> Result<Vec<u8>, StarkError> {
+    let bytes = hex::decode(strip_hex_prefix(value)).map_err(|_| StarkError::InvalidHex(value.to_string()))?;
+    if bytes.len() != 20 {
+        return Err(StarkError::InvalidAddress(value.to_string()));
+    }
+    Ok(bytes)
+}
+
+/// Generate the condition, signed as part of a conditional transfer.
+pub fn fact_to_condition(fact_registry_address: &str, fact: &[u8]) -> Result<BigUint, StarkError> {
+    let mut data = decode_address(fact_registry_address)?;
+    data.extend_from_slice(fact);
+    let hash = BigUint::from_bytes_be(&Keccak256::digest(&data));
+    Ok(hash & ((BigUint::one() << 250u32) - 1u8))
+}
+
+/// The fact signed by a fast withdrawal: keccak256 over the packed
+/// (recipient, token amount, token address, salt) tuple.
+pub fn get_transfer_erc20_fact(
+    recipient: &str,
+    token_decimals: u8,
+    human_amount: &str,
+    token_address: &str,
+    salt: u64,
+) -> Result<Vec<u8>, StarkError> {
+    // Like the Python implementation, the token amount goes through a float.
+    let token_amount = human_amount
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| StarkError::InvalidNumber(human_amount.to_string()))?
+        * 10f64.powi(token_decimals as i32);
+    if token_amount.fract() != 0.0 || token_amount < 0.0 {
+        return Err(QuantumsError::NotMultipleOfQuantum {
+            field: "amount",
+            value: human_amount.to_string(),
+            quantum_size: format!("1e-{}", token_decimals),
+        }
+        .into());
+    }
+    let token_amount = BigInt::from_str(&format!("{:.0}", token_amount)).unwrap();
+
+    let uint256 = |value: &BigInt| {
+        let (_, bytes) = value.to_bytes_be();
+        if bytes.len() > 32 {
+            return Err(QuantumsError::OutOfRange {
+                field: "amount",
+                value: human_amount.to_string(),
+            });
+        }
+        let mut padded = vec![0u8; 32 - bytes.len()];
+        padded.extend_from_slice(&bytes);
+        Ok(padded)
+    };
+
+    let mut packed = decode_address(recipient)?;
+    packed.extend(uint256(&token_amount)?);
+    packed.extend(decode_address(token_address)?);
+    packed.extend(uint256(&BigInt::from_biguint(Sign::Plus, BigUint::from(salt)))?);
+    Ok(Keccak256::digest(&packed).to_vec())
+}
+
+/// Given private key as hex string, return the public key as hex string.
+pub fn private_key_to_public_hex(private_key_hex: &str) -> Result<String, StarkError> {
+    let private_key_int = hex_to_biguint(private_key_hex)?;
+    Ok(format!("0x{}", private_to_stark_key(&private_key_int)?.to_str_radix(16)))
+}
+
+/// Given private key as hex string, return the public x, y pair as hex.
+pub fn private_key_to_public_key_pair_hex(private_key_hex: &str) -> Result<(String, String), StarkError> {
+    let private_key_int = hex_to_biguint(private_key_hex)?;
+    let (x, y) = private_key_to_ec_point_on_stark_curve(&private_key_int)?;
+    Ok((format!("0x{}", x.to_str_radix(16)), format!("0x{}", y.to_str_radix(16))))
+}
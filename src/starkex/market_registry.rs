@@ -0,0 +1,205 @@
+use super::helpers::{collateral_asset_id, hex_to_biguint};
+use crate::constants::SYNTHETIC_ASSETS;
+use crate::error::StarkError;
+use crate::modules::public::Public;
+use crate::types::{ConfigResponse, MarketsResponse};
+use crate::Result;
+use num_bigint::BigUint;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+// Registries of from_constants by network id, built once per process.
+static CONSTANTS: Lazy<Mutex<HashMap<usize, Arc<MarketRegistry>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// What order signing needs to know about a market.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketInfo {
+    pub market: String,
+    pub base_asset: String,
+    pub synthetic_asset_id: String,
+    pub asset_resolution: String,
+    #[serde(default)]
+    pub step_size: Option<String>,
+    #[serde(default)]
+    pub tick_size: Option<String>,
+}
+
+/// The markets and collateral asset of one network, as cached on disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketSnapshot {
+    pub network_id: usize,
+    pub collateral_asset_id: String,
+    pub markets: HashMap<String, MarketInfo>,
+}
+
+impl MarketSnapshot {
+    pub fn from_responses(network_id: usize, markets: &MarketsResponse, config: &ConfigResponse) -> MarketSnapshot {
+        MarketSnapshot {
+            network_id,
+            collateral_asset_id: config.collateral_asset_id.clone(),
+            markets: markets
+                .markets
+                .values()
+                .map(|data| {
+                    let info = MarketInfo {
+                        market: data.market.clone(),
+                        base_asset: data.base_asset.clone(),
+                        synthetic_asset_id: data.synthetic_asset_id.clone(),
+                        asset_resolution: data.asset_resolution.clone(),
+                        step_size: Some(data.step_size.clone()),
+                        tick_size: Some(data.tick_size.clone()),
+                    };
+                    (data.market.clone(), info)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Asset ids and resolutions used to sign orders, transfers and withdrawals.
+///
+/// `from_constants` mirrors the markets known to dydx_constants.py; `fetch` and
+/// `load_or_fetch` take them from `Public::get_markets` and `Public::get_config`, so
+/// markets listed after this crate was released can be traded. A registry with a
+/// cache path writes every refresh back to that file. Every snapshot records the
+/// network it was taken from, and a cache of another network is fetched again.
+#[derive(Debug)]
+pub struct MarketRegistry {
+    snapshot: RwLock<MarketSnapshot>,
+    cache_path: Option<PathBuf>,
+}
+
+impl MarketRegistry {
+    pub fn new(snapshot: MarketSnapshot) -> MarketRegistry {
+        MarketRegistry {
+            snapshot: RwLock::new(snapshot),
+            cache_path: None,
+        }
+    }
+
+    /// Registry of the markets hard-coded in constants::SYNTHETIC_ASSETS.
+    pub fn from_constants(network_id: usize) -> std::result::Result<MarketRegistry, StarkError> {
+        let collateral_asset_id = format!("{:#x}", collateral_asset_id(network_id)?);
+        let markets = SYNTHETIC_ASSETS
+            .iter()
+            .map(|asset| {
+                let info = MarketInfo {
+                    market: asset.market.to_string(),
+                    base_asset: asset.asset.to_string(),
+                    synthetic_asset_id: asset.asset_id.to_string(),
+                    asset_resolution: asset.resolution.to_string(),
+                    step_size: None,
+                    tick_size: None,
+                };
+                (asset.market.to_string(), info)
+            })
+            .collect();
+        Ok(MarketRegistry::new(MarketSnapshot {
+            network_id,
+            collateral_asset_id,
+            markets,
+        }))
+    }
+
+    /// from_constants, built once per network and shared.
+    pub(crate) fn constants(network_id: usize) -> std::result::Result<Arc<MarketRegistry>, StarkError> {
+        let mut registries = CONSTANTS.lock().unwrap();
+        if let Some(registry) = registries.get(&network_id) {
+            return Ok(registry.clone());
+        }
+        let registry = Arc::new(MarketRegistry::from_constants(network_id)?);
+        registries.insert(network_id, registry.clone());
+        Ok(registry)
+    }
+
+    /// Fetch the registry of network_id from public, which must be an API of that network.
+    pub async fn fetch(public: &Public<'_>, network_id: usize) -> Result<MarketRegistry> {
+        Ok(MarketRegistry::new(MarketRegistry::fetch_snapshot(public, network_id).await?))
+    }
+
+    /// Load the registry cached at cache_path, or fetch it and write the cache if the
+    /// file is missing, unreadable or of another network.
+    pub async fn load_or_fetch(
+        public: &Public<'_>,
+        network_id: usize,
+        cache_path: impl Into<PathBuf>,
+    ) -> Result<MarketRegistry> {
+        let cache_path = cache_path.into();
+        let registry = match MarketRegistry::load(&cache_path) {
+            Ok(registry) if registry.network_id() == network_id => registry,
+            _ => {
+                let registry = MarketRegistry::fetch(public, network_id).await?;
+                registry.save(&cache_path)?;
+                registry
+            }
+        };
+        Ok(registry.with_cache_path(cache_path))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<MarketRegistry> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(MarketRegistry::new(serde_json::from_str(&json)?))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.snapshot())?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Write the registry to path on every refresh.
+    pub fn with_cache_path(mut self, path: impl Into<PathBuf>) -> MarketRegistry {
+        self.cache_path = Some(path.into());
+        self
+    }
+
+    /// Re-fetch markets and config of the same network, replacing the registry contents
+    /// (and the cache file, if any). On error the registry is left unchanged.
+    pub async fn refresh(&self, public: &Public<'_>) -> Result<()> {
+        let snapshot = MarketRegistry::fetch_snapshot(public, self.network_id()).await?;
+        *self.snapshot.write().unwrap() = snapshot;
+        if let Some(path) = &self.cache_path {
+            self.save(path)?;
+        }
+        Ok(())
+    }
+
+    pub fn network_id(&self) -> usize {
+        self.snapshot.read().unwrap().network_id
+    }
+
+    pub fn snapshot(&self) -> MarketSnapshot {
+        self.snapshot.read().unwrap().clone()
+    }
+
+    pub fn market(&self, market: &str) -> std::result::Result<MarketInfo, StarkError> {
+        self.snapshot
+            .read()
+            .unwrap()
+            .markets
+            .get(market)
+            .cloned()
+            .ok_or_else(|| StarkError::UnknownMarket(market.to_string()))
+    }
+
+    pub fn markets(&self) -> Vec<String> {
+        let mut markets: Vec<String> = self.snapshot.read().unwrap().markets.keys().cloned().collect();
+        markets.sort();
+        markets
+    }
+
+    pub fn collateral_asset_id(&self) -> std::result::Result<BigUint, StarkError> {
+        hex_to_biguint(&self.snapshot.read().unwrap().collateral_asset_id)
+    }
+
+    async fn fetch_snapshot(public: &Public<'_>, network_id: usize) -> Result<MarketSnapshot> {
+        let markets = public.get_markets(None).await?;
+        let config = public.get_config().await?;
+        Ok(MarketSnapshot::from_responses(network_id, &markets, &config))
+    }
+}
@@ -0,0 +1,209 @@
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+pub type EcPoint = (BigUint, BigUint);
+
+/// Finds a nonnegative integer 0 <= x < p such that (m * x) % p == n
+pub fn div_mod(n: &BigUint, m: &BigUint, p: &BigUint) -> BigUint {
+    let inverse = (m % p).modinv(p).expect("div_mod: m is not invertible mod p");
+    (n % p) * inverse % p
+}
+
+fn sub_mod(a: &BigUint, b: &BigUint, p: &BigUint) -> BigUint {
+    if a >= b {
+        (a - b) % p
+    } else {
+        (p - (b - a) % p) % p
+    }
+}
+
+/// Returns True if n is a quadratic residue mod p (p an odd prime).
+pub fn is_quad_residue(n: &BigUint, p: &BigUint) -> bool {
+    let n = n % p;
+    n.is_zero() || n.modpow(&((p - 1u8) >> 1u8), p).is_one()
+}
+
+/// Finds x such that x^2 = n mod p (p an odd prime), using Tonelli-Shanks.
+/// Returns None if n is not a quadratic residue mod p.
+pub fn sqrt_mod(n: &BigUint, p: &BigUint) -> Option<BigUint> {
+    let n = n % p;
+    if n.is_zero() {
+        return Some(BigUint::zero());
+    }
+    if !is_quad_residue(&n, p) {
+        return None;
+    }
+
+    // p - 1 = q * 2^s with q odd.
+    let p_minus_one = p - 1u8;
+    let s = p_minus_one.trailing_zeros().unwrap_or(0);
+    let q = &p_minus_one >> s;
+
+    let mut z = BigUint::from(2u8);
+    while is_quad_residue(&z, p) {
+        z += 1u8;
+    }
+
+    let mut m = s;
+    let mut c = z.modpow(&q, p);
+    let mut t = n.modpow(&q, p);
+    let mut r = n.modpow(&((&q + 1u8) >> 1u8), p);
+    while !t.is_one() {
+        // Find the least i with t^(2^i) = 1.
+        let mut i = 0;
+        let mut t_pow = t.clone();
+        while !t_pow.is_one() {
+            t_pow = &t_pow * &t_pow % p;
+            i += 1;
+        }
+        let b = c.modpow(&(BigUint::one() << (m - i - 1)), p);
+        m = i;
+        c = &b * &b % p;
+        t = t * &c % p;
+        r = r * b % p;
+    }
+    Some(r)
+}
+
+/// Gets two points on an elliptic curve mod p and returns their sum.
+/// Assumes the points are given in affine form (x, y) and have different x coordinates.
+pub fn ec_add(point1: &EcPoint, point2: &EcPoint, p: &BigUint) -> EcPoint {
+    assert!(point1.0 != point2.0, "ec_add: points share an x coordinate");
+    let m = div_mod(
+        &sub_mod(&point1.1, &point2.1, p),
+        &sub_mod(&point1.0, &point2.0, p),
+        p,
+    );
+    let x = sub_mod(&sub_mod(&(&m * &m), &point1.0, p), &point2.0, p);
+    let y = sub_mod(&(&m * sub_mod(&point1.0, &x, p)), &point1.1, p);
+    (x, y)
+}
+
+/// Given a point (x,y) return (x, -y)
+pub fn ec_neg(point: &EcPoint, p: &BigUint) -> EcPoint {
+    (point.0.clone(), sub_mod(&BigUint::zero(), &point.1, p))
+}
+
+/// Doubles a point on an elliptic curve with the equation y^2 = x^3 + alpha*x + beta mod p.
+/// Assumes the point is given in affine form (x, y) and has y != 0.
+pub fn ec_double(point: &EcPoint, alpha: &BigUint, p: &BigUint) -> EcPoint {
+    assert!(!(&point.1 % p).is_zero(), "ec_double: y coordinate is zero");
+    let m = div_mod(
+        &(BigUint::from(3u8) * &point.0 * &point.0 + alpha),
+        &(BigUint::from(2u8) * &point.1),
+        p,
+    );
+    let x = sub_mod(&(&m * &m), &(BigUint::from(2u8) * &point.0), p);
+    let y = sub_mod(&(&m * sub_mod(&point.0, &x, p)), &point.1, p);
+    (x, y)
+}
+
+/// Multiplies by m a point on the elliptic curve with equation y^2 = x^3 + alpha*x + beta mod p.
+/// Assumes the point is given in affine form (x, y) and that 0 < m < order(point).
+pub fn ec_mult(m: &BigUint, point: &EcPoint, alpha: &BigUint, p: &BigUint) -> EcPoint {
+    assert!(!m.is_zero(), "ec_mult: m must be positive");
+    let mut acc = JacobianPoint::infinity();
+    for i in (0..m.bits()).rev() {
+        acc = acc.double(alpha, p);
+        if m.bit(i) {
+            acc = acc.add_affine(point, alpha, p);
+        }
+    }
+    acc.to_affine(p)
+}
+
+/// A point in Jacobian coordinates (X, Y, Z) representing the affine point (X/Z^2, Y/Z^3).
+/// Used internally so that long chains of additions need a single modular inversion.
+#[derive(Debug, Clone)]
+pub(crate) struct JacobianPoint {
+    x: BigUint,
+    y: BigUint,
+    z: BigUint,
+}
+
+impl JacobianPoint {
+    pub(crate) fn infinity() -> JacobianPoint {
+        JacobianPoint {
+            x: BigUint::one(),
+            y: BigUint::one(),
+            z: BigUint::zero(),
+        }
+    }
+
+    pub(crate) fn from_affine(point: &EcPoint) -> JacobianPoint {
+        JacobianPoint {
+            x: point.0.clone(),
+            y: point.1.clone(),
+            z: BigUint::one(),
+        }
+    }
+
+    pub(crate) fn is_infinity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    pub(crate) fn to_affine(&self, p: &BigUint) -> EcPoint {
+        assert!(!self.is_infinity(), "point at infinity has no affine form");
+        let z_inv = self.z.modinv(p).expect("z is not invertible mod p");
+        let z_inv_2 = &z_inv * &z_inv % p;
+        let z_inv_3 = &z_inv_2 * &z_inv % p;
+        (&self.x * z_inv_2 % p, &self.y * z_inv_3 % p)
+    }
+
+    pub(crate) fn double(&self, alpha: &BigUint, p: &BigUint) -> JacobianPoint {
+        if self.is_infinity() || self.y.is_zero() {
+            return JacobianPoint::infinity();
+        }
+        let xx = &self.x * &self.x % p;
+        let yy = &self.y * &self.y % p;
+        let yyyy = &yy * &yy % p;
+        let zz = &self.z * &self.z % p;
+        let s = BigUint::from(4u8) * &self.x * &yy % p;
+        let m = (BigUint::from(3u8) * xx + alpha * (&zz * &zz % p)) % p;
+        let x3 = sub_mod(&(&m * &m % p), &(BigUint::from(2u8) * &s), p);
+        let y3 = sub_mod(
+            &(&m * sub_mod(&s, &x3, p) % p),
+            &(BigUint::from(8u8) * yyyy),
+            p,
+        );
+        let z3 = BigUint::from(2u8) * &self.y * &self.z % p;
+        JacobianPoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    pub(crate) fn add_affine(&self, point: &EcPoint, alpha: &BigUint, p: &BigUint) -> JacobianPoint {
+        if self.is_infinity() {
+            return JacobianPoint::from_affine(point);
+        }
+        let z1z1 = &self.z * &self.z % p;
+        let u2 = &point.0 * &z1z1 % p;
+        let s2 = &point.1 * &self.z % p * &z1z1 % p;
+        let h = sub_mod(&u2, &self.x, p);
+        let r = sub_mod(&s2, &self.y, p);
+        if h.is_zero() {
+            return if r.is_zero() {
+                self.double(alpha, p)
+            } else {
+                JacobianPoint::infinity()
+            };
+        }
+        let hh = &h * &h % p;
+        let hhh = &h * &hh % p;
+        let v = &self.x * &hh % p;
+        let x3 = sub_mod(
+            &sub_mod(&(&r * &r % p), &hhh, p),
+            &(BigUint::from(2u8) * &v),
+            p,
+        );
+        let y3 = sub_mod(&(&r * sub_mod(&v, &x3, p) % p), &(&self.y * &hhh % p), p);
+        let z3 = &self.z * &h % p;
+        JacobianPoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+}
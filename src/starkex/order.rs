@@ -0,0 +1,175 @@
+use super::constants::*;
+use super::helpers::*;
+use super::market_registry::MarketRegistry;
+use super::quantums::OrderQuantums;
+use super::signable::Signable;
+use super::signature::pedersen_hash;
+use crate::error::StarkError;
+use crate::types::OrderSide;
+use num_bigint::BigUint;
+use std::fmt;
+
+/// An order as hashed for the STARK signature, with amounts in quantums.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarkwareOrder {
+    pub asset_id_synthetic: BigUint,
+    pub asset_id_collateral: BigUint,
+    pub asset_id_fee: BigUint,
+    pub quantums_amount_synthetic: u64,
+    pub quantums_amount_collateral: u64,
+    pub quantums_amount_fee: u64,
+    pub is_buying_synthetic: bool,
+    pub position_id: u64,
+    pub nonce: u64,
+    pub expiration_epoch_hours: i64,
+}
+
+impl StarkwareOrder {
+    /// Calculate the hash of the order, the message signed with the STARK key.
+    pub fn hash(&self) -> Result<BigUint, StarkError> {
+        let (asset_id_sell, asset_id_buy, quantums_amount_sell, quantums_amount_buy) =
+            if self.is_buying_synthetic {
+                (
+                    &self.asset_id_collateral,
+                    &self.asset_id_synthetic,
+                    self.quantums_amount_collateral,
+                    self.quantums_amount_synthetic,
+                )
+            } else {
+                (
+                    &self.asset_id_synthetic,
+                    &self.asset_id_collateral,
+                    self.quantums_amount_synthetic,
+                    self.quantums_amount_collateral,
+                )
+            };
+
+        let mut part_1 = BigUint::from(quantums_amount_sell);
+        part_1 <<= ORDER_FIELD_BIT_LENGTHS.quantums_amount;
+        part_1 += quantums_amount_buy;
+        part_1 <<= ORDER_FIELD_BIT_LENGTHS.quantums_amount;
+        part_1 += self.quantums_amount_fee;
+        part_1 <<= ORDER_FIELD_BIT_LENGTHS.nonce;
+        part_1 += self.nonce;
+
+        let mut part_2 = BigUint::from(ORDER_PREFIX);
+        for _ in 0..3 {
+            part_2 <<= ORDER_FIELD_BIT_LENGTHS.position_id;
+            part_2 += self.position_id;
+        }
+        part_2 <<= ORDER_FIELD_BIT_LENGTHS.expiration_epoch_hours;
+        part_2 += packed_epoch_hours(
+            self.expiration_epoch_hours,
+            ORDER_FIELD_BIT_LENGTHS.expiration_epoch_hours,
+        )?;
+        part_2 <<= ORDER_PADDING_BITS;
+
+        let assets_hash = pedersen_hash(&[
+            &pedersen_hash(&[asset_id_sell, asset_id_buy])?,
+            &self.asset_id_fee,
+        ])?;
+        pedersen_hash(&[&pedersen_hash(&[&assets_hash, &part_1])?, &part_2])
+    }
+}
+
+impl fmt::Display for StarkwareOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "StarkwareOrder")?;
+        writeln!(f, "  side: {}", if self.is_buying_synthetic { "BUY" } else { "SELL" })?;
+        writeln!(f, "  asset_id_synthetic: {:#x}", self.asset_id_synthetic)?;
+        writeln!(f, "  asset_id_collateral: {:#x}", self.asset_id_collateral)?;
+        writeln!(f, "  asset_id_fee: {:#x}", self.asset_id_fee)?;
+        writeln!(f, "  quantums_amount_synthetic: {}", self.quantums_amount_synthetic)?;
+        writeln!(f, "  quantums_amount_collateral: {}", self.quantums_amount_collateral)?;
+        writeln!(f, "  quantums_amount_fee: {}", self.quantums_amount_fee)?;
+        writeln!(f, "  position_id: {}", self.position_id)?;
+        writeln!(f, "  nonce: {}", self.nonce)?;
+        write!(f, "  expiration_epoch_hours: {}", display_epoch_hours(self.expiration_epoch_hours))
+    }
+}
+
+/// Wrapper object to convert an order from human units, and hash and sign it.
+#[derive(Debug, Clone)]
+pub struct SignableOrder {
+    message: StarkwareOrder,
+}
+
+impl SignableOrder {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        network_id: usize,
+        market: &str,
+        side: &str,
+        position_id: &str,
+        human_size: &str,
+        human_price: &str,
+        limit_fee: &str,
+        client_id: &str,
+        expiration_epoch_seconds: i64,
+    ) -> Result<SignableOrder, StarkError> {
+        SignableOrder::with_registry(
+            &*MarketRegistry::constants(network_id)?,
+            market,
+            side,
+            position_id,
+            human_size,
+            human_price,
+            limit_fee,
+            client_id,
+            expiration_epoch_seconds,
+        )
+    }
+
+    /// Like new, with market and collateral asset ids taken from registry.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_registry(
+        registry: &MarketRegistry,
+        market: &str,
+        side: &str,
+        position_id: &str,
+        human_size: &str,
+        human_price: &str,
+        limit_fee: &str,
+        client_id: &str,
+        expiration_epoch_seconds: i64,
+    ) -> Result<SignableOrder, StarkError> {
+        let market_info = registry.market(market)?;
+        let synthetic_asset_id = hex_to_biguint(&market_info.synthetic_asset_id)?;
+        let collateral_asset_id = registry.collateral_asset_id()?;
+        let is_buying_synthetic = side == OrderSide::BUY;
+        let quantums = OrderQuantums::new(&market_info, is_buying_synthetic, human_size, human_price, limit_fee)?;
+
+        // Orders may have a short time-to-live on the orderbook, but we need
+        // to ensure their signatures are valid by the time they reach the
+        // blockchain. Therefore, we enforce that the signed expiration includes
+        // a buffer relative to the expiration timestamp sent to the dYdX API.
+        let expiration_epoch_hours = expiration_epoch_hours(expiration_epoch_seconds)
+            + ORDER_SIGNATURE_EXPIRATION_BUFFER_HOURS;
+
+        Ok(SignableOrder {
+            message: StarkwareOrder {
+                asset_id_synthetic: synthetic_asset_id,
+                asset_id_collateral: collateral_asset_id.clone(),
+                asset_id_fee: collateral_asset_id,
+                quantums_amount_synthetic: quantums.synthetic,
+                quantums_amount_collateral: quantums.collateral,
+                quantums_amount_fee: quantums.fee,
+                is_buying_synthetic,
+                position_id: parse_position_id(position_id)?,
+                nonce: nonce_from_client_id(client_id),
+                expiration_epoch_hours,
+            },
+        })
+    }
+
+    /// The quantized order that is hashed and signed.
+    pub fn message(&self) -> &StarkwareOrder {
+        &self.message
+    }
+}
+
+impl Signable for SignableOrder {
+    fn calculate_hash(&self) -> Result<BigUint, StarkError> {
+        self.message.hash()
+    }
+}
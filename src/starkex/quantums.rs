@@ -0,0 +1,164 @@
+//! Conversion of human amounts to quantums, the integer amounts of Starkware messages,
+//! with the rounding of starkex/helpers.py.
+//!
+//! Every error names the offending field, so an order can be checked before signing,
+//! e.g. "size 0.0013 is not a multiple of step 0.001".
+
+use super::helpers::round_to_context;
+use super::market_registry::MarketInfo;
+use crate::constants::COLLATERAL_ASSET_RESOLUTION;
+use crate::error::QuantumsError;
+use bigdecimal::{BigDecimal, One, RoundingMode, Zero};
+use num_bigint::{BigInt, BigUint};
+use num_traits::ToPrimitive;
+use std::str::FromStr;
+
+/// What to do with an amount that is not a whole number of quantums.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Fail with NotMultipleOfQuantum.
+    Exact,
+    Up,
+    Down,
+}
+
+impl Rounding {
+    fn mode(self) -> Option<RoundingMode> {
+        match self {
+            Rounding::Exact => None,
+            Rounding::Up => Some(RoundingMode::Up),
+            Rounding::Down => Some(RoundingMode::Down),
+        }
+    }
+}
+
+/// The quantized amounts of an order, computed as in SignableOrder of starkex/order.py.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderQuantums {
+    pub synthetic: u64,
+    pub collateral: u64,
+    pub fee: u64,
+}
+
+impl OrderQuantums {
+    pub fn new(
+        market: &MarketInfo,
+        is_buying_synthetic: bool,
+        human_size: &str,
+        human_price: &str,
+        limit_fee: &str,
+    ) -> Result<OrderQuantums, QuantumsError> {
+        let size = parse_amount("size", human_size)?;
+        let price = parse_amount("price", human_price)?;
+        let synthetic = to_quantums("size", &size, &market.asset_resolution, Rounding::Exact)?;
+
+        // Note: The product is computed with full precision and only then rounded
+        // to the decimal context, matching the Python implementation.
+        let rounding = if is_buying_synthetic { Rounding::Up } else { Rounding::Down };
+        let cost = round_to_context(&(&size * &price), rounding.mode()).unwrap();
+        let collateral = to_quantums("cost", &cost, COLLATERAL_ASSET_RESOLUTION, rounding)?;
+
+        // The limitFee is a fraction, e.g. 0.01 is a 1 % fee.
+        // It is always paid in the collateral asset.
+        // Constrain the limit fee to six decimals of precision.
+        // The final fee amount must be rounded up.
+        let limit_fee = parse_amount("limit_fee", limit_fee)?.with_scale_round(6, RoundingMode::Down);
+        let fee = round_to_context(
+            &(limit_fee * BigDecimal::from(BigInt::from(collateral.clone()))),
+            Some(RoundingMode::Up),
+        )
+        .unwrap()
+        .with_scale_round(0, RoundingMode::Up);
+
+        Ok(OrderQuantums {
+            synthetic: to_u64("quantums_amount_synthetic", &synthetic)?,
+            collateral: to_u64("quantums_amount_collateral", &collateral)?,
+            fee: to_u64("quantums_amount_fee", &to_biguint("quantums_amount_fee", &fee)?)?,
+        })
+    }
+}
+
+/// Check that size and price are multiples of the step and tick size of market, where
+/// known (markets of MarketRegistry::from_constants have neither).
+pub fn check_order(market: &MarketInfo, human_size: &str, human_price: &str) -> Result<(), QuantumsError> {
+    if let Some(step_size) = &market.step_size {
+        check_step("size", &parse_amount("size", human_size)?, step_size)?;
+    }
+    if let Some(tick_size) = &market.tick_size {
+        check_step("price", &parse_amount("price", human_price)?, tick_size)?;
+    }
+    Ok(())
+}
+
+/// Fails with NotMultipleOfStep unless human_amount is a whole number of steps.
+pub fn check_step(field: &'static str, human_amount: &BigDecimal, step: &str) -> Result<(), QuantumsError> {
+    let step_amount = parse_amount("step", step)?;
+    if step_amount <= BigDecimal::zero() {
+        return Err(QuantumsError::OutOfRange {
+            field: "step",
+            value: step.to_string(),
+        });
+    }
+    if (human_amount % &step_amount).is_zero() {
+        Ok(())
+    } else {
+        Err(QuantumsError::NotMultipleOfStep {
+            field,
+            value: human_amount.to_string(),
+            step: step.to_string(),
+        })
+    }
+}
+
+pub fn parse_amount(field: &'static str, value: &str) -> Result<BigDecimal, QuantumsError> {
+    BigDecimal::from_str(value.trim()).map_err(|_| QuantumsError::InvalidNumber {
+        field,
+        value: value.to_string(),
+    })
+}
+
+/// Convert human_amount to quantums of an asset with the given resolution (quantums per
+/// unit, e.g. "1e6" for USDC).
+pub fn to_quantums(
+    field: &'static str,
+    human_amount: &BigDecimal,
+    resolution: &str,
+    rounding: Rounding,
+) -> Result<BigUint, QuantumsError> {
+    let resolution = parse_amount("resolution", resolution)?;
+    if resolution <= BigDecimal::zero() {
+        return Err(QuantumsError::OutOfRange {
+            field: "resolution",
+            value: resolution.to_string(),
+        });
+    }
+    let inexact = || QuantumsError::NotMultipleOfQuantum {
+        field,
+        value: human_amount.to_string(),
+        quantum_size: (BigDecimal::one() / &resolution).normalized().to_plain_string(),
+    };
+    let mode = rounding.mode();
+    let amount = round_to_context(human_amount, mode).ok_or_else(inexact)?;
+    let product = round_to_context(&(amount * &resolution), mode).ok_or_else(inexact)?;
+    let quantums = match mode {
+        Some(mode) => product.with_scale_round(0, mode),
+        None if product.is_integer() => product,
+        None => return Err(inexact()),
+    };
+    to_biguint(field, &quantums)
+}
+
+fn to_biguint(field: &'static str, value: &BigDecimal) -> Result<BigUint, QuantumsError> {
+    let (digits, _) = value.with_scale(0).into_bigint_and_exponent();
+    digits.to_biguint().ok_or(QuantumsError::OutOfRange {
+        field,
+        value: value.to_string(),
+    })
+}
+
+fn to_u64(field: &'static str, value: &BigUint) -> Result<u64, QuantumsError> {
+    value.to_u64().ok_or(QuantumsError::OutOfRange {
+        field,
+        value: value.to_string(),
+    })
+}
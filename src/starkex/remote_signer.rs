@@ -0,0 +1,230 @@
+use super::helpers::{hex_to_biguint, parse_field_element};
+use super::signature::{verify, EcSignature};
+use super::signers::{SignatureFuture, StarkSigner};
+use super::signing_pool::SigningPool;
+use crate::error::StarkError;
+use crate::Result;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(unix)]
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+
+/// How long a signing daemon may take to answer, unless set with with_timeout.
+pub const DEFAULT_REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest line read from a Unix socket by either side; longer lines fail.
+pub const MAX_REMOTE_LINE_LEN: u64 = 4096;
+
+/// Request sent to a signing daemon: one JSON object per line over a Unix
+/// socket, or the body of a POST request over HTTP.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteSignRequest {
+    pub message_hash: String,
+}
+
+/// Response from a signing daemon. Either r and s, or error, is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemoteSignResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub s: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl RemoteSignResponse {
+    fn into_signature(self) -> Result<EcSignature> {
+        match (self.r, self.s, self.error) {
+            (_, _, Some(error)) => Err(Box::new(StarkError::SignerRejected(error))),
+            (Some(r), Some(s), None) => Ok((hex_to_biguint(&r)?, hex_to_biguint(&s)?)),
+            _ => Err(Box::new(StarkError::SignerRejected(
+                "response has no signature".to_string(),
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum RemoteEndpoint {
+    #[cfg(unix)]
+    UnixSocket(PathBuf),
+    Http(String),
+}
+
+/// A signer delegating to a signing daemon, so that the STARK private key
+/// never enters this process. Signatures are verified against the public key of
+/// the daemon before they are returned, on a SigningPool (the shared one unless
+/// given with with_pool).
+#[derive(Debug, Clone)]
+pub struct RemoteStarkSigner {
+    endpoint: RemoteEndpoint,
+    public_key: BigUint,
+    timeout: Duration,
+    client: reqwest::Client,
+    pool: Arc<SigningPool>,
+}
+
+impl RemoteStarkSigner {
+    #[cfg(unix)]
+    pub fn unix_socket(path: impl Into<PathBuf>, public_key_hex: &str) -> std::result::Result<RemoteStarkSigner, StarkError> {
+        RemoteStarkSigner::with_endpoint(RemoteEndpoint::UnixSocket(path.into()), public_key_hex)
+    }
+
+    pub fn http(url: &str, public_key_hex: &str) -> std::result::Result<RemoteStarkSigner, StarkError> {
+        RemoteStarkSigner::with_endpoint(RemoteEndpoint::Http(url.to_string()), public_key_hex)
+    }
+
+    fn with_endpoint(endpoint: RemoteEndpoint, public_key_hex: &str) -> std::result::Result<RemoteStarkSigner, StarkError> {
+        Ok(RemoteStarkSigner {
+            endpoint,
+            public_key: parse_field_element(public_key_hex, "public_key")?,
+            timeout: DEFAULT_REMOTE_SIGNER_TIMEOUT,
+            client: reqwest::Client::new(),
+            pool: SigningPool::shared(),
+        })
+    }
+
+    /// Verify signatures on pool.
+    pub fn with_pool(mut self, pool: Arc<SigningPool>) -> RemoteStarkSigner {
+        self.pool = pool;
+        self
+    }
+
+    /// Fail a signature when the daemon has not answered within timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> RemoteStarkSigner {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn public_key(&self) -> &BigUint {
+        &self.public_key
+    }
+
+    async fn request(&self, message_hash: &BigUint) -> Result<RemoteSignResponse> {
+        let request = RemoteSignRequest {
+            message_hash: format!("0x{}", message_hash.to_str_radix(16)),
+        };
+        match &self.endpoint {
+            #[cfg(unix)]
+            RemoteEndpoint::UnixSocket(path) => {
+                let exchange = async {
+                    let mut stream = BufReader::new(UnixStream::connect(path).await?);
+                    let mut line = serde_json::to_string(&request)?;
+                    line.push('\n');
+                    stream.get_mut().write_all(line.as_bytes()).await?;
+                    let response = read_line(&mut stream).await?.ok_or_else(|| {
+                        std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "signing daemon sent no response")
+                    })?;
+                    Ok(serde_json::from_str(&response)?)
+                };
+                tokio::time::timeout(self.timeout, exchange).await?
+            }
+            RemoteEndpoint::Http(url) => {
+                let response = self.client.post(url).json(&request).timeout(self.timeout).send().await?;
+                if !response.status().is_success() {
+                    return Err(Box::new(StarkError::SignerRejected(format!("HTTP status {}", response.status()))));
+                }
+                Ok(response.json::<RemoteSignResponse>().await?)
+            }
+        }
+    }
+}
+
+impl StarkSigner for RemoteStarkSigner {
+    fn sign<'a>(&'a self, message_hash: &'a BigUint) -> SignatureFuture<'a> {
+        Box::pin(async move {
+            let (r, s) = self.request(message_hash).await?.into_signature()?;
+            let (hash, public_key) = (message_hash.clone(), self.public_key.clone());
+            let (valid, r, s) = self
+                .pool
+                .run(move || (verify(&hash, &r, &s, &public_key), r, s))
+                .await?;
+            if !valid {
+                let signature = format!("r=0x{} s=0x{}", r.to_str_radix(16), s.to_str_radix(16));
+                return Err(StarkError::InvalidSignature(signature).into());
+            }
+            Ok((r, s))
+        })
+    }
+}
+
+/// Serve signing requests from RemoteStarkSigner clients on a Unix socket,
+/// using the given signer. Runs until accepting a connection fails.
+#[cfg(unix)]
+pub async fn serve_unix_socket(listener: UnixListener, signer: Arc<dyn StarkSigner>) -> std::io::Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let signer = signer.clone();
+        tokio::spawn(async move {
+            let mut stream = BufReader::new(stream);
+            loop {
+                let (response, last) = match read_line(&mut stream).await {
+                    Ok(Some(line)) => (handle_request(signer.as_ref(), &line).await, false),
+                    Ok(None) => break,
+                    Err(error) => {
+                        let response = RemoteSignResponse {
+                            error: Some(error.to_string()),
+                            ..Default::default()
+                        };
+                        (response, true)
+                    }
+                };
+                let mut output = match serde_json::to_string(&response) {
+                    Ok(output) => output,
+                    Err(_) => break,
+                };
+                output.push('\n');
+                if stream.get_mut().write_all(output.as_bytes()).await.is_err() || last {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+// A line of at most MAX_REMOTE_LINE_LEN bytes, or None at the end of the stream.
+#[cfg(unix)]
+async fn read_line<R: AsyncBufRead + Unpin>(stream: &mut R) -> std::io::Result<Option<String>> {
+    let mut line = String::new();
+    let n = stream.take(MAX_REMOTE_LINE_LEN).read_line(&mut line).await?;
+    if n == 0 {
+        return Ok(None);
+    }
+    if n as u64 == MAX_REMOTE_LINE_LEN && !line.ends_with('\n') {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("line longer than {} bytes", MAX_REMOTE_LINE_LEN),
+        ));
+    }
+    Ok(Some(line))
+}
+
+#[cfg(unix)]
+async fn handle_request(signer: &dyn StarkSigner, line: &str) -> RemoteSignResponse {
+    let message_hash = match serde_json::from_str::<RemoteSignRequest>(line) {
+        Ok(request) => hex_to_biguint(&request.message_hash).map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let signature = match message_hash {
+        Ok(message_hash) => signer.sign(&message_hash).await.map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
+    match signature {
+        Ok((r, s)) => RemoteSignResponse {
+            r: Some(format!("0x{}", r.to_str_radix(16))),
+            s: Some(format!("0x{}", s.to_str_radix(16))),
+            error: None,
+        },
+        Err(error) => RemoteSignResponse {
+            error: Some(error),
+            ..Default::default()
+        },
+    }
+}
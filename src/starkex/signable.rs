@@ -0,0 +1,29 @@
+use super::helpers::{deserialize_signature, hex_to_biguint, serialize_signature};
+use super::signature::{sign, verify};
+use crate::error::StarkError;
+use num_bigint::BigUint;
+
+/// Base trait for an object signable with a STARK key.
+pub trait Signable {
+    /// Calculate the hash of the Starkware object.
+    fn calculate_hash(&self) -> Result<BigUint, StarkError>;
+
+    /// Sign the hash of the object using the given private key. k is derived from the key
+    /// and the hash as in RFC 6979, so signing the same object always gives the same signature.
+    fn sign(&self, private_key_hex: &str) -> Result<String, StarkError> {
+        self.sign_with_seed(private_key_hex, None)
+    }
+
+    /// Like sign, with seed as extra entropy for k, like the seed of StarkWare's sign. The
+    /// signature is still deterministic for a given seed.
+    fn sign_with_seed(&self, private_key_hex: &str, seed: Option<&BigUint>) -> Result<String, StarkError> {
+        let (r, s) = sign(&self.calculate_hash()?, &hex_to_biguint(private_key_hex)?, seed)?;
+        serialize_signature(&r, &s)
+    }
+
+    /// Verify a serialized signature of the object against the given STARK public key.
+    fn verify(&self, signature: &str, public_key_hex: &str) -> Result<bool, StarkError> {
+        let (r, s) = deserialize_signature(signature)?;
+        Ok(verify(&self.calculate_hash()?, &r, &s, &hex_to_biguint(public_key_hex)?))
+    }
+}
@@ -0,0 +1,355 @@
+// Starkware crypto functions, ported from starkex_resources/python_signature.py
+// (itself copied from starkware-libs/starkex-resources crypto/starkware/crypto/signature/signature.py).
+
+use super::math_utils::{div_mod, ec_double, ec_mult, sqrt_mod, EcPoint, JacobianPoint};
+use crate::error::StarkError;
+use hmac::{Hmac, Mac};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use once_cell::sync::Lazy;
+use sha2::Sha256;
+
+const PEDERSEN_PARAMS_JSON: &str =
+    include_str!("../stark/starkex/starkex_resources/pedersen_params.json");
+
+pub const N_ELEMENT_BITS_ECDSA: u64 = 251;
+pub const N_ELEMENT_BITS_HASH: usize = 252;
+
+pub struct PedersenParams {
+    pub field_prime: BigUint,
+    pub field_gen: BigUint,
+    pub alpha: BigUint,
+    pub beta: BigUint,
+    pub ec_order: BigUint,
+    pub constant_points: Vec<EcPoint>,
+}
+
+impl PedersenParams {
+    pub fn shift_point(&self) -> &EcPoint {
+        &self.constant_points[0]
+    }
+
+    pub fn ec_gen(&self) -> &EcPoint {
+        &self.constant_points[1]
+    }
+}
+
+pub static PEDERSEN_PARAMS: Lazy<PedersenParams> = Lazy::new(|| {
+    let constant_points_at = PEDERSEN_PARAMS_JSON
+        .find("\"CONSTANT_POINTS\"")
+        .expect("pedersen_params.json: missing CONSTANT_POINTS");
+    let scalar = |key: &str| -> BigUint {
+        let header = PEDERSEN_PARAMS_JSON[..constant_points_at]
+            .find(&format!("\"{}\":", key))
+            .unwrap_or_else(|| panic!("pedersen_params.json: missing {}", key));
+        parse_integers(&PEDERSEN_PARAMS_JSON[header + key.len() + 3..])
+            .next()
+            .unwrap()
+    };
+    let coordinates: Vec<BigUint> =
+        parse_integers(&PEDERSEN_PARAMS_JSON[constant_points_at..]).collect();
+    let constant_points = coordinates
+        .chunks(2)
+        .map(|pair| (pair[0].clone(), pair[1].clone()))
+        .collect();
+
+    PedersenParams {
+        field_prime: scalar("FIELD_PRIME"),
+        field_gen: scalar("FIELD_GEN"),
+        alpha: scalar("ALPHA"),
+        beta: scalar("BETA"),
+        ec_order: scalar("EC_ORDER"),
+        constant_points,
+    }
+});
+
+/// EC_GEN, 2*EC_GEN, 4*EC_GEN, ..., so that multiples of EC_GEN take additions only.
+static EC_GEN_POWERS: Lazy<Vec<EcPoint>> = Lazy::new(|| {
+    let params = &*PEDERSEN_PARAMS;
+    let mut powers = vec![params.ec_gen().clone()];
+    for _ in 1..N_ELEMENT_BITS_HASH {
+        let last = powers.last().unwrap();
+        powers.push(ec_double(last, &params.alpha, &params.field_prime));
+    }
+    powers
+});
+
+/// m * EC_GEN, for 0 < m < 2**N_ELEMENT_BITS_HASH.
+fn ec_gen_mult(m: &BigUint) -> EcPoint {
+    assert!(!m.is_zero(), "ec_gen_mult: m must be positive");
+    let params = &*PEDERSEN_PARAMS;
+    let mut acc = JacobianPoint::infinity();
+    for (i, power) in EC_GEN_POWERS.iter().enumerate() {
+        if m.bit(i as u64) {
+            acc = acc.add_affine(power, &params.alpha, &params.field_prime);
+        }
+    }
+    acc.to_affine(&params.field_prime)
+}
+
+// The params file holds integers wider than any JSON number type, so the decimal
+// digit runs are parsed directly.
+fn parse_integers(input: &str) -> impl Iterator<Item = BigUint> + '_ {
+    input
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|digits| !digits.is_empty())
+        .map(|digits| digits.parse::<BigUint>().unwrap())
+}
+
+pub type EcSignature = (BigUint, BigUint);
+
+pub fn private_key_to_ec_point_on_stark_curve(priv_key: &BigUint) -> Result<EcPoint, StarkError> {
+    let params = &*PEDERSEN_PARAMS;
+    if priv_key.is_zero() || priv_key >= &params.ec_order {
+        return Err(StarkError::InvalidPrivateKey);
+    }
+    Ok(ec_gen_mult(priv_key))
+}
+
+pub fn private_to_stark_key(priv_key: &BigUint) -> Result<BigUint, StarkError> {
+    Ok(private_key_to_ec_point_on_stark_curve(priv_key)?.0)
+}
+
+pub fn inv_mod_curve_size(x: &BigUint) -> BigUint {
+    div_mod(&BigUint::one(), x, &PEDERSEN_PARAMS.ec_order)
+}
+
+fn int_to_bytes(x: &BigUint) -> Vec<u8> {
+    if x.is_zero() {
+        Vec::new()
+    } else {
+        x.to_bytes_be()
+    }
+}
+
+fn bits2int(data: &[u8], qlen: u64) -> BigUint {
+    let x = BigUint::from_bytes_be(data);
+    let l = data.len() as u64 * 8;
+    if l > qlen {
+        x >> (l - qlen)
+    } else {
+        x
+    }
+}
+
+fn int_to_fixed_bytes(x: &BigUint, len: usize) -> Vec<u8> {
+    let bytes = x.to_bytes_be();
+    let mut padded = vec![0u8; len.saturating_sub(bytes.len())];
+    padded.extend_from_slice(&bytes);
+    padded.truncate(len);
+    padded
+}
+
+fn hmac_sha256(key: &[u8], parts: &[&[u8]]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().to_vec()
+}
+
+// RFC 6979 nonce generation, matching ecdsa.rfc6979.generate_k as called by python_signature.py.
+fn generate_k(order: &BigUint, secexp: &BigUint, data: &[u8], extra_entropy: &[u8]) -> BigUint {
+    let qlen = order.bits();
+    let rolen = qlen.div_ceil(8) as usize;
+    let orderlen = order.to_str_radix(16).len().div_ceil(2);
+
+    let z1 = bits2int(data, qlen);
+    let z2 = if &z1 >= order { &z1 - order } else { z1 };
+    let secexp_bytes = int_to_fixed_bytes(secexp, orderlen);
+    let data_bytes = int_to_fixed_bytes(&z2, orderlen);
+
+    let mut v = vec![0x01u8; 32];
+    let mut k = vec![0x00u8; 32];
+    k = hmac_sha256(&k, &[&v, &[0x00], &secexp_bytes, &data_bytes, extra_entropy]);
+    v = hmac_sha256(&k, &[&v]);
+    k = hmac_sha256(&k, &[&v, &[0x01], &secexp_bytes, &data_bytes, extra_entropy]);
+    v = hmac_sha256(&k, &[&v]);
+
+    loop {
+        let mut t = Vec::new();
+        while t.len() < rolen {
+            v = hmac_sha256(&k, &[&v]);
+            t.extend_from_slice(&v);
+        }
+        let secret = bits2int(&t, qlen);
+        if !secret.is_zero() && &secret < order {
+            return secret;
+        }
+        k = hmac_sha256(&k, &[&v, &[0x00]]);
+        v = hmac_sha256(&k, &[&v]);
+    }
+}
+
+pub fn generate_k_rfc6979(msg_hash: &BigUint, priv_key: &BigUint, seed: Option<&BigUint>) -> BigUint {
+    // Pad the message hash, for consistency with the elliptic.js library.
+    let bit_length = msg_hash.bits();
+    let msg_hash = if (1..=4).contains(&(bit_length % 8)) && bit_length >= 248 {
+        // Only if we are one-nibble short:
+        msg_hash * 16u8
+    } else {
+        msg_hash.clone()
+    };
+
+    let extra_entropy = match seed {
+        Some(seed) => int_to_bytes(seed),
+        None => Vec::new(),
+    };
+
+    generate_k(
+        &PEDERSEN_PARAMS.ec_order,
+        priv_key,
+        &int_to_bytes(&msg_hash),
+        &extra_entropy,
+    )
+}
+
+/// Sign msg_hash, like py_sign. k is generated as in RFC 6979 from the key and the hash,
+/// with seed, if any, as extra entropy; a k that cannot be used is replaced by the k of the
+/// next seed (1 when there is none). The signature is a function of the inputs.
+pub fn sign(
+    msg_hash: &BigUint,
+    priv_key: &BigUint,
+    seed: Option<&BigUint>,
+) -> Result<EcSignature, StarkError> {
+    let params = &*PEDERSEN_PARAMS;
+    // Note: msg_hash must be smaller than 2**N_ELEMENT_BITS_ECDSA.
+    // Message whose hash is >= 2**N_ELEMENT_BITS_ECDSA cannot be signed.
+    // This happens with a very small probability.
+    if msg_hash.bits() > N_ELEMENT_BITS_ECDSA {
+        return Err(StarkError::MessageNotSignable);
+    }
+    if priv_key.is_zero() || priv_key >= &params.ec_order {
+        return Err(StarkError::InvalidPrivateKey);
+    }
+
+    // Choose a valid k. In our version of ECDSA not every k value is valid,
+    // and there is a negligible probability a drawn k cannot be used for signing.
+    // This is why we have this loop.
+    let mut seed = seed.cloned();
+    loop {
+        let k = generate_k_rfc6979(msg_hash, priv_key, seed.as_ref());
+        // Update seed for next iteration in case the value of k is bad.
+        seed = Some(match seed {
+            Some(seed) => seed + 1u8,
+            None => BigUint::one(),
+        });
+
+        // Cannot fail because 0 < k < EC_ORDER and EC_ORDER is prime.
+        let x = ec_gen_mult(&k).0;
+
+        // DIFF: in classic ECDSA, we take int(x) % n.
+        let r = x;
+        if r.is_zero() || r.bits() > N_ELEMENT_BITS_ECDSA {
+            // Bad value. This fails with negligible probability.
+            continue;
+        }
+
+        let msg_plus_r_priv = (msg_hash + &r * priv_key) % &params.ec_order;
+        if msg_plus_r_priv.is_zero() {
+            // Bad value. This fails with negligible probability.
+            continue;
+        }
+
+        let w = div_mod(&k, &msg_plus_r_priv, &params.ec_order);
+        if w.is_zero() || w.bits() > N_ELEMENT_BITS_ECDSA {
+            // Bad value. This fails with negligible probability.
+            continue;
+        }
+
+        let s = inv_mod_curve_size(&w);
+        return Ok((r, s));
+    }
+}
+
+/// Given the x coordinate of a stark_key, returns a possible y coordinate such that together the
+/// point (x,y) is on the curve. Note that the real y coordinate is either y or -y.
+pub fn get_y_coordinate(stark_key_x_coordinate: &BigUint) -> Option<BigUint> {
+    let params = &*PEDERSEN_PARAMS;
+    let x = stark_key_x_coordinate;
+    let y_squared = (x * x * x + &params.alpha * x + &params.beta) % &params.field_prime;
+    sqrt_mod(&y_squared, &params.field_prime)
+}
+
+/// Verifies (r, s) against msg_hash and the x coordinate of a stark public key.
+pub fn verify(msg_hash: &BigUint, r: &BigUint, s: &BigUint, public_key: &BigUint) -> bool {
+    let params = &*PEDERSEN_PARAMS;
+    // Only the x coordinate of the point is given, check the two possibilities for the y
+    // coordinate.
+    match get_y_coordinate(public_key) {
+        Some(y) => {
+            let minus_y = (&params.field_prime - &y) % &params.field_prime;
+            verify_with_point(msg_hash, r, s, &(public_key.clone(), y))
+                || verify_with_point(msg_hash, r, s, &(public_key.clone(), minus_y))
+        }
+        None => false,
+    }
+}
+
+/// Verifies (r, s) against msg_hash and a stark public key given as a curve point.
+pub fn verify_with_point(msg_hash: &BigUint, r: &BigUint, s: &BigUint, public_key: &EcPoint) -> bool {
+    let params = &*PEDERSEN_PARAMS;
+    let in_range = |x: &BigUint| !x.is_zero() && x.bits() <= N_ELEMENT_BITS_ECDSA;
+    if s.is_zero() || s >= &params.ec_order {
+        return false;
+    }
+    // DIFF: in classic ECDSA, we check 1 <= r, w <= EC_ORDER-1.
+    // Since r, w < 2**N_ELEMENT_BITS_ECDSA < EC_ORDER, we only need to verify r, w != 0.
+    let w = inv_mod_curve_size(s);
+    if !in_range(r) || !in_range(&w) || !in_range(msg_hash) {
+        return false;
+    }
+    let (x, y) = public_key;
+    let on_curve = (y * y) % &params.field_prime
+        == (x * x * x + &params.alpha * x + &params.beta) % &params.field_prime;
+    if !on_curve {
+        return false;
+    }
+
+    // x = w*(msg_hash*EC_GEN + r*public_key), as in python_signature.py.
+    let z_g = ec_gen_mult(msg_hash);
+    let r_q = ec_mult(r, public_key, &params.alpha, &params.field_prime);
+    let sum = JacobianPoint::from_affine(&z_g).add_affine(&r_q, &params.alpha, &params.field_prime);
+    if sum.is_infinity() {
+        return false;
+    }
+    let sum = sum.to_affine(&params.field_prime);
+    let x = ec_mult(&w, &sum, &params.alpha, &params.field_prime).0;
+
+    // DIFF: Here we drop the mod n from classic ECDSA.
+    r == &x
+}
+
+/// The Pedersen hash of at most two elements, each of which must be below FIELD_PRIME.
+pub fn pedersen_hash(elements: &[&BigUint]) -> Result<BigUint, StarkError> {
+    Ok(pedersen_hash_as_point(elements)?.0)
+}
+
+/// Similar to pedersen_hash but also returns the y coordinate of the resulting EC point.
+pub fn pedersen_hash_as_point(elements: &[&BigUint]) -> Result<EcPoint, StarkError> {
+    let params = &*PEDERSEN_PARAMS;
+    // The constant points cover two elements, as in the reference implementation.
+    if elements.len() > (params.constant_points.len() - 2) / N_ELEMENT_BITS_HASH {
+        return Err(StarkError::OutOfRange {
+            field: "pedersen_hash element count",
+            value: elements.len().to_string(),
+        });
+    }
+    let mut point = JacobianPoint::from_affine(params.shift_point());
+    for (i, x) in elements.iter().enumerate() {
+        if *x >= &params.field_prime {
+            return Err(StarkError::OutOfRange {
+                field: "pedersen_hash",
+                value: format!("{:#x}", x),
+            });
+        }
+        let point_list = &params.constant_points
+            [2 + i * N_ELEMENT_BITS_HASH..2 + (i + 1) * N_ELEMENT_BITS_HASH];
+        for (bit, pt) in point_list.iter().enumerate() {
+            if x.bit(bit as u64) {
+                point = point.add_affine(pt, &params.alpha, &params.field_prime);
+            }
+        }
+    }
+    Ok(point.to_affine(&params.field_prime))
+}
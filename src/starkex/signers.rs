@@ -0,0 +1,93 @@
+use super::helpers::hex_to_biguint;
+use super::signature::{private_to_stark_key, sign, EcSignature};
+use super::signing_pool::SigningPool;
+use crate::error::StarkError;
+use crate::Result;
+use num_bigint::BigUint;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub type SignatureFuture<'a> = Pin<Box<dyn Future<Output = Result<EcSignature>> + Send + 'a>>;
+
+/// Signs the hash of a Starkware object, returning the (r, s) signature.
+///
+/// Implement this to keep the STARK private key outside the trading process.
+pub trait StarkSigner: Send + Sync + fmt::Debug {
+    fn sign<'a>(&'a self, message_hash: &'a BigUint) -> SignatureFuture<'a>;
+}
+
+/// A signer holding the STARK private key in memory. Signatures are computed on a
+/// SigningPool, the shared one unless given with with_pool, and are deterministic
+/// (RFC 6979, see signature::sign).
+pub struct StarkKeySigner {
+    private_key: BigUint,
+    public_key: BigUint,
+    pool: Arc<SigningPool>,
+    seed: Option<BigUint>,
+}
+
+impl StarkKeySigner {
+    pub fn new(private_key_hex: &str) -> std::result::Result<StarkKeySigner, StarkError> {
+        StarkKeySigner::with_pool(private_key_hex, SigningPool::shared())
+    }
+
+    pub fn with_pool(private_key_hex: &str, pool: Arc<SigningPool>) -> std::result::Result<StarkKeySigner, StarkError> {
+        let private_key = hex_to_biguint(private_key_hex)?;
+        let public_key = private_to_stark_key(&private_key)?;
+        Ok(StarkKeySigner {
+            private_key,
+            public_key,
+            pool,
+            seed: None,
+        })
+    }
+
+    /// Sign with seed as extra entropy for k.
+    pub fn with_seed(mut self, seed: BigUint) -> StarkKeySigner {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn public_key(&self) -> &BigUint {
+        &self.public_key
+    }
+}
+
+/// A StarkKeySigner from private_key_hex or, if the key is invalid, a signer failing
+/// every signature with the error, so that constructors that cannot fail report it on
+/// the first signature instead of panicking.
+pub(crate) fn key_signer_or_failing(private_key_hex: &str, pool: Arc<SigningPool>) -> Arc<dyn StarkSigner> {
+    match StarkKeySigner::with_pool(private_key_hex, pool) {
+        Ok(signer) => Arc::new(signer),
+        Err(error) => Arc::new(FailingSigner(error)),
+    }
+}
+
+#[derive(Debug)]
+struct FailingSigner(StarkError);
+
+impl StarkSigner for FailingSigner {
+    fn sign<'a>(&'a self, _message_hash: &'a BigUint) -> SignatureFuture<'a> {
+        Box::pin(async move { Err(self.0.clone().into()) })
+    }
+}
+
+// Never print the private key.
+impl fmt::Debug for StarkKeySigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StarkKeySigner")
+            .field("public_key", &format!("0x{}", self.public_key.to_str_radix(16)))
+            .finish()
+    }
+}
+
+impl StarkSigner for StarkKeySigner {
+    fn sign<'a>(&'a self, message_hash: &'a BigUint) -> SignatureFuture<'a> {
+        let message_hash = message_hash.clone();
+        let private_key = self.private_key.clone();
+        let seed = self.seed.clone();
+        Box::pin(async move { Ok(self.pool.run(move || sign(&message_hash, &private_key, seed.as_ref())).await??) })
+    }
+}
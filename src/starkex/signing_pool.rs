@@ -0,0 +1,53 @@
+use crate::Result;
+use once_cell::sync::Lazy;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+static SHARED_POOL: Lazy<Arc<SigningPool>> = Lazy::new(|| {
+    let max_parallelism = std::thread::available_parallelism().map_or(1, |n| n.get());
+    Arc::new(SigningPool::new(max_parallelism))
+});
+
+/// Runs CPU-bound signing work (Pedersen hashing, ECDSA, Python calls) on Tokio's
+/// blocking thread pool, so it never stalls the async workers, with at most
+/// max_parallelism jobs running at a time. Further jobs wait for a free slot.
+#[derive(Debug)]
+pub struct SigningPool {
+    permits: Arc<Semaphore>,
+    max_parallelism: usize,
+}
+
+impl SigningPool {
+    pub fn new(max_parallelism: usize) -> SigningPool {
+        let max_parallelism = max_parallelism.max(1);
+        SigningPool {
+            permits: Arc::new(Semaphore::new(max_parallelism)),
+            max_parallelism,
+        }
+    }
+
+    /// The process-wide pool used unless another one is configured, sized to the
+    /// number of available CPUs.
+    pub fn shared() -> Arc<SigningPool> {
+        SHARED_POOL.clone()
+    }
+
+    pub fn max_parallelism(&self) -> usize {
+        self.max_parallelism
+    }
+
+    /// Run job on the blocking pool once a slot is free. A panic in job is returned as an error.
+    /// The job holds its slot until it finishes, even if the returned future is dropped first.
+    pub async fn run<T, F>(&self, job: F) -> Result<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let permit = self.permits.clone().acquire_owned().await?;
+        Ok(tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            job()
+        })
+        .await?)
+    }
+}
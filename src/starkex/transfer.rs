@@ -0,0 +1,135 @@
+use super::constants::*;
+use super::helpers::*;
+use super::market_registry::MarketRegistry;
+use super::signable::Signable;
+use super::signature::pedersen_hash;
+use crate::constants::COLLATERAL_ASSET;
+use crate::error::StarkError;
+use num_bigint::BigUint;
+use std::fmt;
+
+/// A transfer as hashed for the STARK signature, with the amount in quantums.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarkwareTransfer {
+    pub asset_id_collateral: BigUint,
+    pub sender_position_id: u64,
+    pub receiver_position_id: u64,
+    pub receiver_public_key: BigUint,
+    pub quantums_amount: u64,
+    pub nonce: u64,
+    pub expiration_epoch_hours: i64,
+}
+
+impl StarkwareTransfer {
+    /// Calculate the hash of the transfer, the message signed with the STARK key.
+    pub fn hash(&self) -> Result<BigUint, StarkError> {
+        let asset_ids = pedersen_hash(&[
+            &self.asset_id_collateral,
+            &BigUint::from(TRANSFER_FEE_ASSET_ID),
+        ])?;
+
+        let part1 = pedersen_hash(&[&asset_ids, &self.receiver_public_key])?;
+
+        let mut part2 = BigUint::from(self.sender_position_id);
+        part2 <<= TRANSFER_FIELD_BIT_LENGTHS.position_id;
+        part2 += self.receiver_position_id;
+        part2 <<= TRANSFER_FIELD_BIT_LENGTHS.position_id;
+        part2 += self.sender_position_id;
+        part2 <<= TRANSFER_FIELD_BIT_LENGTHS.nonce;
+        part2 += self.nonce;
+
+        let mut part3 = BigUint::from(TRANSFER_PREFIX);
+        part3 <<= TRANSFER_FIELD_BIT_LENGTHS.quantums_amount;
+        part3 += self.quantums_amount;
+        part3 <<= TRANSFER_FIELD_BIT_LENGTHS.quantums_amount;
+        part3 += TRANSFER_MAX_AMOUNT_FEE;
+        part3 <<= TRANSFER_FIELD_BIT_LENGTHS.expiration_epoch_hours;
+        part3 += packed_epoch_hours(
+            self.expiration_epoch_hours,
+            TRANSFER_FIELD_BIT_LENGTHS.expiration_epoch_hours,
+        )?;
+        part3 <<= TRANSFER_PADDING_BITS;
+
+        pedersen_hash(&[&pedersen_hash(&[&part1, &part2])?, &part3])
+    }
+}
+
+impl fmt::Display for StarkwareTransfer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "StarkwareTransfer")?;
+        writeln!(f, "  asset_id_collateral: {:#x}", self.asset_id_collateral)?;
+        writeln!(f, "  sender_position_id: {}", self.sender_position_id)?;
+        writeln!(f, "  receiver_position_id: {}", self.receiver_position_id)?;
+        writeln!(f, "  receiver_public_key: {:#x}", self.receiver_public_key)?;
+        writeln!(f, "  quantums_amount: {}", self.quantums_amount)?;
+        writeln!(f, "  nonce: {}", self.nonce)?;
+        write!(f, "  expiration_epoch_hours: {}", display_epoch_hours(self.expiration_epoch_hours))
+    }
+}
+
+/// Wrapper object to convert a transfer, and hash and sign it.
+#[derive(Debug, Clone)]
+pub struct SignableTransfer {
+    message: StarkwareTransfer,
+}
+
+impl SignableTransfer {
+    pub fn new(
+        network_id: usize,
+        sender_position_id: &str,
+        receiver_position_id: &str,
+        receiver_public_key: &str,
+        human_amount: &str,
+        client_id: &str,
+        expiration_epoch_seconds: i64,
+    ) -> Result<SignableTransfer, StarkError> {
+        SignableTransfer::with_registry(
+            &*MarketRegistry::constants(network_id)?,
+            sender_position_id,
+            receiver_position_id,
+            receiver_public_key,
+            human_amount,
+            client_id,
+            expiration_epoch_seconds,
+        )
+    }
+
+    /// Like new, with the collateral asset id taken from registry.
+    pub fn with_registry(
+        registry: &MarketRegistry,
+        sender_position_id: &str,
+        receiver_position_id: &str,
+        receiver_public_key: &str,
+        human_amount: &str,
+        client_id: &str,
+        expiration_epoch_seconds: i64,
+    ) -> Result<SignableTransfer, StarkError> {
+        let nonce = nonce_from_client_id(client_id);
+
+        // The transfer asset is always the collateral asset.
+        let quantums_amount = to_quantums_exact(&parse_decimal(human_amount)?, COLLATERAL_ASSET)?;
+
+        Ok(SignableTransfer {
+            message: StarkwareTransfer {
+                asset_id_collateral: registry.collateral_asset_id()?,
+                sender_position_id: parse_position_id(sender_position_id)?,
+                receiver_position_id: parse_position_id(receiver_position_id)?,
+                receiver_public_key: parse_field_element(receiver_public_key, "receiver_public_key")?,
+                quantums_amount: to_u64(&quantums_amount, "quantums_amount")?,
+                nonce,
+                expiration_epoch_hours: expiration_epoch_hours(expiration_epoch_seconds),
+            },
+        })
+    }
+
+    /// The quantized transfer that is hashed and signed.
+    pub fn message(&self) -> &StarkwareTransfer {
+        &self.message
+    }
+}
+
+impl Signable for SignableTransfer {
+    fn calculate_hash(&self) -> Result<BigUint, StarkError> {
+        self.message.hash()
+    }
+}
@@ -0,0 +1,107 @@
+use super::constants::*;
+use super::helpers::*;
+use super::market_registry::MarketRegistry;
+use super::signable::Signable;
+use super::signature::pedersen_hash;
+use crate::constants::COLLATERAL_ASSET;
+use crate::error::StarkError;
+use num_bigint::BigUint;
+use std::fmt;
+
+/// A withdrawal as hashed for the STARK signature, with the amount in quantums.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarkwareWithdrawal {
+    pub asset_id_collateral: BigUint,
+    pub quantums_amount: u64,
+    pub position_id: u64,
+    pub nonce: u64,
+    pub expiration_epoch_hours: i64,
+}
+
+impl StarkwareWithdrawal {
+    /// Calculate the hash of the withdrawal, the message signed with the STARK key.
+    pub fn hash(&self) -> Result<BigUint, StarkError> {
+        let mut packed = BigUint::from(WITHDRAWAL_PREFIX);
+        packed <<= WITHDRAWAL_FIELD_BIT_LENGTHS.position_id;
+        packed += self.position_id;
+        packed <<= WITHDRAWAL_FIELD_BIT_LENGTHS.nonce;
+        packed += self.nonce;
+        packed <<= WITHDRAWAL_FIELD_BIT_LENGTHS.quantums_amount;
+        packed += self.quantums_amount;
+        packed <<= WITHDRAWAL_FIELD_BIT_LENGTHS.expiration_epoch_hours;
+        packed += packed_epoch_hours(
+            self.expiration_epoch_hours,
+            WITHDRAWAL_FIELD_BIT_LENGTHS.expiration_epoch_hours,
+        )?;
+        packed <<= WITHDRAWAL_PADDING_BITS;
+
+        pedersen_hash(&[&self.asset_id_collateral, &packed])
+    }
+}
+
+impl fmt::Display for StarkwareWithdrawal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "StarkwareWithdrawal")?;
+        writeln!(f, "  asset_id_collateral: {:#x}", self.asset_id_collateral)?;
+        writeln!(f, "  quantums_amount: {}", self.quantums_amount)?;
+        writeln!(f, "  position_id: {}", self.position_id)?;
+        writeln!(f, "  nonce: {}", self.nonce)?;
+        write!(f, "  expiration_epoch_hours: {}", display_epoch_hours(self.expiration_epoch_hours))
+    }
+}
+
+/// Wrapper object to convert a withdrawal from human units, and hash and sign it.
+#[derive(Debug, Clone)]
+pub struct SignableWithdrawal {
+    message: StarkwareWithdrawal,
+}
+
+impl SignableWithdrawal {
+    pub fn new(
+        network_id: usize,
+        position_id: &str,
+        human_amount: &str,
+        client_id: &str,
+        expiration_epoch_seconds: i64,
+    ) -> Result<SignableWithdrawal, StarkError> {
+        SignableWithdrawal::with_registry(
+            &*MarketRegistry::constants(network_id)?,
+            position_id,
+            human_amount,
+            client_id,
+            expiration_epoch_seconds,
+        )
+    }
+
+    /// Like new, with the collateral asset id taken from registry.
+    pub fn with_registry(
+        registry: &MarketRegistry,
+        position_id: &str,
+        human_amount: &str,
+        client_id: &str,
+        expiration_epoch_seconds: i64,
+    ) -> Result<SignableWithdrawal, StarkError> {
+        let quantums_amount = to_quantums_exact(&parse_decimal(human_amount)?, COLLATERAL_ASSET)?;
+
+        Ok(SignableWithdrawal {
+            message: StarkwareWithdrawal {
+                asset_id_collateral: registry.collateral_asset_id()?,
+                quantums_amount: to_u64(&quantums_amount, "quantums_amount")?,
+                position_id: parse_position_id(position_id)?,
+                nonce: nonce_from_client_id(client_id),
+                expiration_epoch_hours: expiration_epoch_hours(expiration_epoch_seconds),
+            },
+        })
+    }
+
+    /// The quantized withdrawal that is hashed and signed.
+    pub fn message(&self) -> &StarkwareWithdrawal {
+        &self.message
+    }
+}
+
+impl Signable for SignableWithdrawal {
+    fn calculate_hash(&self) -> Result<BigUint, StarkError> {
+        self.message.hash()
+    }
+}
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ApiKeyCredentialsResponseObject {
     pub key: String,
     pub secret: String,
@@ -35,10 +35,32 @@ pub struct ApiKeyCredentials<'a> {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct KeyPairWithYCoordinate<'a> {
-    pub public_key: &'a str,
-    pub public_key_y_coordinate: &'a str,
-    pub private_key: &'a str,
+pub struct KeyPairWithYCoordinate {
+    pub public_key: String,
+    pub public_key_y_coordinate: String,
+    pub private_key: String,
+}
+
+impl KeyPairWithYCoordinate {
+    pub fn create_user_params(&self) -> CreateUserParams<'_> {
+        CreateUserParams {
+            stark_key: &self.public_key,
+            stark_key_y_coordinate: &self.public_key_y_coordinate,
+            referred_by_affiliate_link: None,
+            country: None,
+        }
+    }
+}
+
+/// An EthPrivate request to be signed, with the EIP-712 typed data for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthPrivateSigningRequest {
+    pub method: String,
+    pub request_path: String,
+    pub body: String,
+    pub timestamp: String,
+    pub typed_data: Value,
 }
 
 #[non_exhaustive]
@@ -431,7 +453,7 @@ pub struct MarketData {
     pub synthetic_asset_id: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OrderbookResponse {
     pub asks: Vec<OrderbookResponseOrder>,
@@ -445,7 +467,7 @@ pub struct OrderbookResponseOrder {
     pub price: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Trade {
     pub side: String,
@@ -535,6 +557,8 @@ pub struct PositionResponseObject {
     pub max_size: String,
     pub entry_price: String,
     pub exit_price: Option<String>,
+    // Absent from the position updates of the v3_accounts channel.
+    #[serde(default)]
     pub unrealized_pnl: String,
     pub realized_pnl: Option<String>,
     pub created_at: String,
@@ -648,7 +672,7 @@ pub struct HistoricalPnlResponseObject {
     pub account_id: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FundingResponseObject {
     pub market: String,
@@ -766,7 +790,7 @@ pub struct PublicRetroactiveMiningRewardsResponse {
     pub target_volume: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FillResponseObject {
     pub id: String,
@@ -814,7 +838,7 @@ pub struct HistoricalLeaderboardPnlsResponse {
 #[serde(rename_all = "camelCase")]
 pub struct HistoricalLeaderboardPnlsResponseObject {}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OrderResponseObject {
     pub id: String,
@@ -907,7 +931,7 @@ pub struct CreateUserParams<'a> {
     pub country: Option<&'a str>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TransferResponseObject {
     pub id: String,
@@ -956,6 +980,8 @@ pub struct TransferParams<'a> {
     pub receiver_account_id: &'a str,
     pub receiver_public_key: &'a str,
     pub receiver_position_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<&'a str>,
     pub expiration: i64,
 }
 
@@ -975,6 +1001,8 @@ pub struct ApiWithdrawParams<'a> {
     pub position_id: &'a str,
     pub amount: &'a str,
     pub asset: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<&'a str>,
     pub expiration: i64,
 }
 
@@ -998,6 +1026,8 @@ pub struct ApiFastWithdrawalParams<'a> {
     pub to_address: &'a str,
     pub lp_position_id: &'a str,
     pub lp_stark_key: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<&'a str>,
     pub expiration: i64,
 }
 
@@ -0,0 +1,6 @@
+pub mod account_state;
+pub mod client;
+pub mod connection;
+pub mod messages;
+pub mod orderbook;
+pub mod supervisor;
@@ -0,0 +1,457 @@
+//! A replica of an account: its balances, open orders and open positions.
+//!
+//! AccountState is seeded from REST and kept current by the updates of the v3_accounts
+//! channel, or by calling refresh periodically when the WebSocket is not used. Every
+//! change is published as a consistent AccountSnapshot to watchers and as AccountDiffs
+//! to diff receivers.
+//!
+//! The v3_accounts channel updates quoteBalance but not equity or free collateral, which
+//! depend on oracle prices. handle therefore re-reads the account from REST after fills
+//! and position changes; with apply they are as of the last refresh.
+
+use super::messages::{AccountsUpdate, Channel, WsEvent};
+use super::supervisor::SupervisorEvent;
+use crate::modules::private::Private;
+use crate::types::*;
+use crate::Result;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+
+/// Statuses of orders that can still be filled.
+pub const OPEN_ORDER_STATUSES: [&str; 3] = ["PENDING", "OPEN", "UNTRIGGERED"];
+/// Diffs a receiver can fall behind by before it misses some.
+pub const DIFF_CHANNEL_CAPACITY: usize = 1024;
+/// Orders and fills read per REST request.
+pub const PAGE_SIZE: usize = 100;
+/// Ids of reported fills kept to recognize repeats; older ones are forgotten.
+pub const MAX_SEEN_FILLS: usize = 10_000;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountSnapshot {
+    pub account_id: String,
+    pub account_number: String,
+    pub equity: String,
+    pub free_collateral: String,
+    pub quote_balance: String,
+    /// Open orders by id.
+    pub orders: HashMap<String, OrderResponseObject>,
+    /// Open positions by market.
+    pub positions: HashMap<String, PositionResponseObject>,
+    /// Whether the state has been seeded.
+    pub seeded: bool,
+    /// Counts the changes of the state.
+    pub version: u64,
+    order_ids_by_client_id: HashMap<String, String>,
+}
+
+impl AccountSnapshot {
+    pub fn order_by_client_id(&self, client_id: &str) -> Option<&OrderResponseObject> {
+        self.orders.get(self.order_ids_by_client_id.get(client_id)?)
+    }
+
+    fn insert_order(&mut self, order: OrderResponseObject) {
+        self.order_ids_by_client_id
+            .insert(order.client_id.clone(), order.id.clone());
+        self.orders.insert(order.id.clone(), order);
+    }
+
+    fn remove_order(&mut self, id: &str) -> Option<OrderResponseObject> {
+        let order = self.orders.remove(id)?;
+        self.order_ids_by_client_id.remove(&order.client_id);
+        Some(order)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountDiff {
+    /// An order was placed or changed, e.g. partially filled.
+    OrderUpdated(OrderResponseObject),
+    /// An order was filled or canceled. From REST, the order as it was last seen.
+    OrderClosed(OrderResponseObject),
+    PositionUpdated(PositionResponseObject),
+    /// A position was closed. From REST, the position as it was last seen.
+    PositionClosed(PositionResponseObject),
+    BalanceUpdated {
+        equity: String,
+        free_collateral: String,
+        quote_balance: String,
+    },
+    Fill(FillResponseObject),
+    Transfer(TransferResponseObject),
+    FundingPayment(FundingResponseObject),
+}
+
+#[derive(Debug)]
+pub struct AccountState {
+    snapshot: AccountSnapshot,
+    // Ids of fills already reported.
+    seen_fills: SeenFills,
+    // Whether fills have been read from REST.
+    fills_read: bool,
+    snapshots: watch::Sender<AccountSnapshot>,
+    diffs: broadcast::Sender<AccountDiff>,
+}
+
+impl Default for AccountState {
+    fn default() -> AccountState {
+        AccountState::new()
+    }
+}
+
+impl AccountState {
+    /// An empty state, waiting to be seeded.
+    pub fn new() -> AccountState {
+        AccountState {
+            snapshot: AccountSnapshot::default(),
+            seen_fills: SeenFills::default(),
+            fills_read: false,
+            snapshots: watch::channel(AccountSnapshot::default()).0,
+            diffs: broadcast::channel(DIFF_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    pub fn snapshot(&self) -> &AccountSnapshot {
+        &self.snapshot
+    }
+
+    /// Receives the snapshot after every change.
+    pub fn watch(&self) -> watch::Receiver<AccountSnapshot> {
+        self.snapshots.subscribe()
+    }
+
+    /// Receives every diff applied from now on.
+    pub fn diffs(&self) -> broadcast::Receiver<AccountDiff> {
+        self.diffs.subscribe()
+    }
+
+    /// Replace the state with account and its open orders, returning what changed.
+    pub fn seed(&mut self, account: &AccountObject, orders: &[OrderResponseObject]) -> Vec<AccountDiff> {
+        let mut next = AccountSnapshot {
+            account_id: account.id.clone(),
+            account_number: account.account_number.clone(),
+            equity: account.equity.clone(),
+            free_collateral: account.free_collateral.clone(),
+            quote_balance: account.quote_balance.clone(),
+            positions: account.open_positions.clone(),
+            seeded: true,
+            ..AccountSnapshot::default()
+        };
+        for order in orders.iter().filter(|order| is_open(order)) {
+            next.insert_order(order.clone());
+        }
+
+        let mut diffs = Vec::new();
+        for (id, order) in &self.snapshot.orders {
+            if !next.orders.contains_key(id) {
+                diffs.push(AccountDiff::OrderClosed(order.clone()));
+            }
+        }
+        for (id, order) in &next.orders {
+            if self.snapshot.orders.get(id) != Some(order) {
+                diffs.push(AccountDiff::OrderUpdated(order.clone()));
+            }
+        }
+        diffs.extend(account_diffs(&self.snapshot, &next));
+        let first_seed = !self.snapshot.seeded;
+        next.version = self.snapshot.version;
+        self.snapshot = next;
+        self.publish(diffs, first_seed)
+    }
+
+    /// Seed the state again from REST: the account of ethereum_address, all of its open
+    /// orders and the fills since the last refresh (the latest page on the first one).
+    /// Fills not seen before are reported, except on the first seed.
+    pub async fn refresh(&mut self, private: &Private<'_>, ethereum_address: &str) -> Result<Vec<AccountDiff>> {
+        let account = private.get_account(ethereum_address).await?.account;
+        let limit = PAGE_SIZE.to_string();
+        let mut orders = Vec::new();
+        let mut order_ids = HashSet::new();
+        for status in OPEN_ORDER_STATUSES {
+            let mut created_before_or_at: Option<String> = None;
+            loop {
+                let page = private
+                    .get_orders(None, Some(status), None, None, Some(&limit), created_before_or_at.as_deref(), None)
+                    .await?
+                    .orders;
+                let full = page.len() >= PAGE_SIZE;
+                created_before_or_at = page.last().map(|order| order.created_at.clone());
+                let mut new = false;
+                for order in page {
+                    // Pages overlap at their createdAt bounds.
+                    if order_ids.insert(order.id.clone()) {
+                        orders.push(order);
+                        new = true;
+                    }
+                }
+                if !full || !new {
+                    break;
+                }
+            }
+        }
+        let fills = self.read_new_fills(private, &limit).await?;
+
+        let report_fills = self.snapshot.seeded;
+        let mut diffs = self.seed(&account, &orders);
+        let mut new_fills = Vec::new();
+        // Oldest first, as they happened.
+        for fill in fills.into_iter().rev() {
+            if self.seen_fills.insert(&fill.id) && report_fills {
+                new_fills.push(AccountDiff::Fill(fill));
+            }
+        }
+        self.fills_read = true;
+        diffs.extend(self.publish(new_fills, false));
+        Ok(diffs)
+    }
+
+    /// Re-read the balances and open positions of the account from REST, leaving orders
+    /// and fills as they are.
+    pub async fn refresh_account(&mut self, private: &Private<'_>, ethereum_address: &str) -> Result<Vec<AccountDiff>> {
+        let account = private.get_account(ethereum_address).await?.account;
+        let next = AccountSnapshot {
+            equity: account.equity,
+            free_collateral: account.free_collateral,
+            quote_balance: account.quote_balance,
+            positions: account.open_positions,
+            ..self.snapshot.clone()
+        };
+        let diffs = account_diffs(&self.snapshot, &next);
+        self.snapshot = next;
+        Ok(self.publish(diffs, false))
+    }
+
+    // Fills newest first, paged back to the first one already seen. Before the first
+    // read only the latest page is read, since none of it is reported.
+    async fn read_new_fills(&self, private: &Private<'_>, limit: &str) -> Result<Vec<FillResponseObject>> {
+        let mut fills = Vec::new();
+        let mut fill_ids = HashSet::new();
+        let mut created_before_or_at: Option<String> = None;
+        loop {
+            let page = private
+                .get_fills(None, None, Some(limit), created_before_or_at.as_deref())
+                .await?
+                .fills;
+            let full = page.len() >= PAGE_SIZE;
+            created_before_or_at = page.last().map(|fill| fill.created_at.clone());
+            let mut new = false;
+            let mut caught_up = false;
+            for fill in page {
+                if self.seen_fills.contains(&fill.id) {
+                    caught_up = true;
+                } else if fill_ids.insert(fill.id.clone()) {
+                    fills.push(fill);
+                    new = true;
+                }
+            }
+            if !full || !new || caught_up || !self.fills_read {
+                return Ok(fills);
+            }
+        }
+    }
+
+    /// Refresh every interval, for when the v3_accounts channel is not used. Returns only
+    /// on a REST error.
+    pub async fn poll(&mut self, private: &Private<'_>, ethereum_address: &str, interval: Duration) -> Result<()> {
+        let mut ticks = tokio::time::interval(interval);
+        loop {
+            ticks.tick().await;
+            self.refresh(private, ethereum_address).await?;
+        }
+    }
+
+    /// Apply a message of the v3_accounts channel of account_id.
+    pub fn apply(&mut self, account_id: &str, snapshot: bool, update: &AccountsUpdate) -> Vec<AccountDiff> {
+        if snapshot {
+            let mut diffs = match &update.account {
+                Some(account) => self.seed(account, &update.orders),
+                None => Vec::new(),
+            };
+            diffs.extend(self.apply_delta(&AccountsUpdate {
+                account: None,
+                orders: Vec::new(),
+                ..update.clone()
+            }));
+            return diffs;
+        }
+        if self.snapshot.seeded && self.snapshot.account_id != account_id {
+            return Vec::new();
+        }
+        self.apply_delta(update)
+    }
+
+    /// Apply the v3_accounts events of this account, ignoring other events.
+    pub fn apply_event(&mut self, event: &WsEvent) -> Vec<AccountDiff> {
+        match event {
+            WsEvent::Accounts {
+                account_id,
+                snapshot,
+                update,
+            } => self.apply(account_id, *snapshot, update),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Apply an event of a WsSupervisor: v3_accounts updates are applied, and the state
+    /// is refreshed from REST when the v3_accounts channel of its account number (or of
+    /// any account, before the first seed) may have missed updates. Fills and position
+    /// updates also re-read the balances with refresh_account.
+    pub async fn handle(
+        &mut self,
+        event: &SupervisorEvent,
+        private: &Private<'_>,
+        ethereum_address: &str,
+    ) -> Result<Vec<AccountDiff>> {
+        match event {
+            SupervisorEvent::Event(event) => {
+                let mut diffs = self.apply_event(event);
+                let moves_balances = diffs.iter().any(|diff| {
+                    matches!(
+                        diff,
+                        AccountDiff::Fill(_) | AccountDiff::PositionUpdated(_) | AccountDiff::PositionClosed(_)
+                    )
+                });
+                if moves_balances && !matches!(event, WsEvent::Accounts { snapshot: true, .. }) {
+                    diffs.extend(self.refresh_account(private, ethereum_address).await?);
+                }
+                Ok(diffs)
+            }
+            SupervisorEvent::GapDetected {
+                channel: Channel::Accounts(account_number),
+                ..
+            } if self.tracks(account_number) => {
+                self.refresh(private, ethereum_address).await
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    // Whether the v3_accounts channel of account_number feeds this state: the channel of
+    // its account, or any before the first seed.
+    pub(crate) fn tracks(&self, account_number: &str) -> bool {
+        !self.snapshot.seeded || account_number == self.snapshot.account_number
+    }
+
+    fn apply_delta(&mut self, update: &AccountsUpdate) -> Vec<AccountDiff> {
+        let mut diffs = Vec::new();
+        for order in &update.orders {
+            if is_open(order) {
+                self.snapshot.insert_order(order.clone());
+                diffs.push(AccountDiff::OrderUpdated(order.clone()));
+            } else {
+                self.snapshot.remove_order(&order.id);
+                diffs.push(AccountDiff::OrderClosed(order.clone()));
+            }
+        }
+        for position in &update.positions {
+            if position.status == "OPEN" {
+                self.snapshot
+                    .positions
+                    .insert(position.market.clone(), position.clone());
+                diffs.push(AccountDiff::PositionUpdated(position.clone()));
+            } else {
+                self.snapshot.positions.remove(&position.market);
+                diffs.push(AccountDiff::PositionClosed(position.clone()));
+            }
+        }
+        for fields in &update.accounts {
+            let mut changed = false;
+            for (field, value) in [
+                ("equity", &mut self.snapshot.equity),
+                ("freeCollateral", &mut self.snapshot.free_collateral),
+                ("quoteBalance", &mut self.snapshot.quote_balance),
+            ] {
+                if let Some(Value::String(new)) = fields.get(field) {
+                    changed |= new != value;
+                    *value = new.clone();
+                }
+            }
+            if changed {
+                diffs.push(balance_diff(&self.snapshot));
+            }
+        }
+        for fill in &update.fills {
+            if self.seen_fills.insert(&fill.id) {
+                diffs.push(AccountDiff::Fill(fill.clone()));
+            }
+        }
+        diffs.extend(update.transfers.iter().cloned().map(AccountDiff::Transfer));
+        diffs.extend(update.funding_payments.iter().cloned().map(AccountDiff::FundingPayment));
+        self.publish(diffs, false)
+    }
+
+    // Publishes the snapshot if anything changed, or if force is set.
+    fn publish(&mut self, diffs: Vec<AccountDiff>, force: bool) -> Vec<AccountDiff> {
+        if diffs.is_empty() && !force {
+            return diffs;
+        }
+        self.snapshot.version += 1;
+        self.snapshots.send_replace(self.snapshot.clone());
+        for diff in &diffs {
+            // Sending fails only when nobody receives the diffs.
+            let _ = self.diffs.send(diff.clone());
+        }
+        diffs
+    }
+}
+
+fn is_open(order: &OrderResponseObject) -> bool {
+    OPEN_ORDER_STATUSES.contains(&order.status.as_str())
+}
+
+// Diffs of the positions and balances of next from those of previous.
+fn account_diffs(previous: &AccountSnapshot, next: &AccountSnapshot) -> Vec<AccountDiff> {
+    let mut diffs = Vec::new();
+    for (market, position) in &previous.positions {
+        if !next.positions.contains_key(market) {
+            diffs.push(AccountDiff::PositionClosed(position.clone()));
+        }
+    }
+    for (market, position) in &next.positions {
+        if previous.positions.get(market) != Some(position) {
+            diffs.push(AccountDiff::PositionUpdated(position.clone()));
+        }
+    }
+    if (&next.equity, &next.free_collateral, &next.quote_balance)
+        != (&previous.equity, &previous.free_collateral, &previous.quote_balance)
+    {
+        diffs.push(balance_diff(next));
+    }
+    diffs
+}
+
+fn balance_diff(snapshot: &AccountSnapshot) -> AccountDiff {
+    AccountDiff::BalanceUpdated {
+        equity: snapshot.equity.clone(),
+        free_collateral: snapshot.free_collateral.clone(),
+        quote_balance: snapshot.quote_balance.clone(),
+    }
+}
+
+// The ids of the latest MAX_SEEN_FILLS fills, oldest first.
+#[derive(Debug, Default)]
+struct SeenFills {
+    ids: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl SeenFills {
+    fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    // Returns whether id is new.
+    fn insert(&mut self, id: &str) -> bool {
+        if !self.ids.insert(id.to_string()) {
+            return false;
+        }
+        self.order.push_back(id.to_string());
+        if self.order.len() > MAX_SEEN_FILLS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,96 @@
+//! Client of the v3 WebSocket API.
+
+use super::connection::WsConnection;
+use super::messages::{Channel, Subscription, WsEvent, WsMessage};
+use crate::error::WsError;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+#[derive(Debug)]
+pub struct WsClient {
+    connection: WsConnection,
+    pending: VecDeque<WsEvent>,
+    last_message_id: Option<u64>,
+    // Account numbers by account id, from the snapshots of v3_accounts.
+    account_numbers: HashMap<String, String>,
+}
+
+impl WsClient {
+    /// Connect to url, e.g. MAINNET_WS_URL or TESTNET_WS_URL.
+    pub async fn connect(url: &str) -> Result<WsClient, WsError> {
+        Ok(WsClient {
+            connection: WsConnection::connect(url).await?,
+            pending: VecDeque::new(),
+            last_message_id: None,
+            account_numbers: HashMap::new(),
+        })
+    }
+
+    /// See WsConnection::set_heartbeat_timeout.
+    pub fn set_heartbeat_timeout(&mut self, timeout: Option<Duration>) {
+        self.connection.set_heartbeat_timeout(timeout);
+    }
+
+    /// Subscribe to a channel. Its snapshot and updates arrive through next_event.
+    pub async fn subscribe(&mut self, subscription: &Subscription) -> Result<(), WsError> {
+        self.connection.send_text(&subscription.subscribe_message()).await
+    }
+
+    pub async fn unsubscribe(&mut self, subscription: &Subscription) -> Result<(), WsError> {
+        self.connection.send_text(&subscription.unsubscribe_message()).await
+    }
+
+    /// See WsConnection::set_max_message_len.
+    pub fn set_max_message_len(&mut self, max_message_len: usize) {
+        self.connection.set_max_message_len(max_message_len);
+    }
+
+    /// The next event, or None once the server has closed the connection. Cancel-safe,
+    /// like WsConnection::receive.
+    pub async fn next_event(&mut self) -> Result<Option<WsEvent>, WsError> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+            let text = match self.connection.receive().await? {
+                Some(text) => text,
+                None => return Ok(None),
+            };
+            let message = WsMessage::parse(&text)?;
+            if message.message_id.is_some() {
+                self.last_message_id = message.message_id;
+            }
+            for event in message.events {
+                let event = self.normalize(event);
+                self.pending.push_back(event);
+            }
+        }
+    }
+
+    // Channels of the server name accounts by id; subscriptions name them by number.
+    fn normalize(&mut self, event: WsEvent) -> WsEvent {
+        match event {
+            WsEvent::Accounts { ref update, .. } => {
+                if let Some(account) = &update.account {
+                    self.account_numbers
+                        .insert(account.id.clone(), account.account_number.clone());
+                }
+                event
+            }
+            WsEvent::Unsubscribed(Channel::Accounts(account_id)) => {
+                let account_number = self.account_numbers.remove(&account_id).unwrap_or(account_id);
+                WsEvent::Unsubscribed(Channel::Accounts(account_number))
+            }
+            event => event,
+        }
+    }
+
+    /// The message_id of the last message received on this connection.
+    pub fn last_message_id(&self) -> Option<u64> {
+        self.last_message_id
+    }
+
+    pub async fn close(&mut self) -> Result<(), WsError> {
+        self.connection.close().await
+    }
+}
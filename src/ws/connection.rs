@@ -0,0 +1,163 @@
+//! A WebSocket connection over TCP or TLS, on tokio-tungstenite.
+
+use crate::error::WsError;
+use futures_util::{SinkExt, StreamExt};
+use std::fmt;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// Frames may not be larger than this.
+pub const MAX_FRAME_LEN: usize = 16 << 20;
+/// Messages joined from fragments may not be larger than this.
+pub const MAX_MESSAGE_LEN: usize = 64 << 20;
+
+pub struct WsConnection {
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    closed: bool,
+    heartbeat_timeout: Option<Duration>,
+    max_message_len: usize,
+}
+
+impl fmt::Debug for WsConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WsConnection")
+            .field("closed", &self.closed)
+            .finish()
+    }
+}
+
+impl WsConnection {
+    /// Open a connection to a ws:// or wss:// URL, e.g. MAINNET_WS_URL.
+    pub async fn connect(url: &str) -> Result<WsConnection, WsError> {
+        let (stream, _) = tokio_tungstenite::connect_async_with_config(url, Some(config()), true)
+            .await
+            .map_err(|error| match error {
+                tungstenite::Error::Url(_) => WsError::InvalidUrl(url.to_string()),
+                error => ws_error(error),
+            })?;
+        Ok(WsConnection::new(stream))
+    }
+
+    /// Accept a client connected to stream, as the server. For local stand-ins of the
+    /// dYdX server, e.g. in tests.
+    pub async fn accept(stream: TcpStream) -> Result<WsConnection, WsError> {
+        let stream = tokio_tungstenite::accept_async_with_config(MaybeTlsStream::Plain(stream), Some(config()))
+            .await
+            .map_err(ws_error)?;
+        Ok(WsConnection::new(stream))
+    }
+
+    fn new(stream: WebSocketStream<MaybeTlsStream<TcpStream>>) -> WsConnection {
+        WsConnection {
+            stream,
+            closed: false,
+            heartbeat_timeout: None,
+            max_message_len: MAX_MESSAGE_LEN,
+        }
+    }
+
+    /// Fail receive with HeartbeatTimeout, and close the connection, when no frame arrives
+    /// within timeout. The server pings every few seconds, so silence means it is gone.
+    pub fn set_heartbeat_timeout(&mut self, timeout: Option<Duration>) {
+        self.heartbeat_timeout = timeout;
+    }
+
+    /// Fail receive with a Protocol error, and close the connection, when a message is
+    /// larger than max_message_len bytes. Messages are never buffered beyond
+    /// MAX_MESSAGE_LEN, the default.
+    pub fn set_max_message_len(&mut self, max_message_len: usize) {
+        self.max_message_len = max_message_len;
+    }
+
+    pub async fn send_text(&mut self, text: &str) -> Result<(), WsError> {
+        if self.closed {
+            return Err(WsError::Closed);
+        }
+        self.stream
+            .send(Message::Text(text.to_string()))
+            .await
+            .map_err(ws_error)
+    }
+
+    /// The next text message. Pings are answered as they arrive, and None is returned once
+    /// the peer has closed the connection. Cancel-safe: a message is never lost by
+    /// dropping the future, e.g. in tokio::select!.
+    pub async fn receive(&mut self) -> Result<Option<String>, WsError> {
+        loop {
+            if self.closed {
+                return Ok(None);
+            }
+            let next = match self.heartbeat_timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, self.stream.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        self.closed = true;
+                        return Err(WsError::HeartbeatTimeout(timeout));
+                    }
+                },
+                None => self.stream.next().await,
+            };
+            let text = match next {
+                Some(Ok(Message::Text(text))) => text,
+                Some(Ok(Message::Binary(bytes))) => match String::from_utf8(bytes) {
+                    Ok(text) => text,
+                    Err(_) => return Err(self.fail(WsError::Protocol("message is not UTF-8".to_string()))),
+                },
+                Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_))) => continue,
+                Some(Ok(Message::Close(_))) | Some(Err(tungstenite::Error::ConnectionClosed)) | None => {
+                    self.closed = true;
+                    return Ok(None);
+                }
+                Some(Err(error)) => return Err(self.fail(ws_error(error))),
+            };
+            if text.len() > self.max_message_len {
+                return Err(self.fail(WsError::Protocol(format!(
+                    "message of more than {} bytes is too large",
+                    self.max_message_len
+                ))));
+            }
+            return Ok(Some(text));
+        }
+    }
+
+    /// Start the closing handshake. Later receive calls return None.
+    pub async fn close(&mut self) -> Result<(), WsError> {
+        if self.closed {
+            return Ok(());
+        }
+        self.closed = true;
+        match self.stream.close(None).await {
+            Ok(()) | Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => Ok(()),
+            Err(error) => Err(ws_error(error)),
+        }
+    }
+
+    // After an error the connection is unusable.
+    fn fail(&mut self, error: WsError) -> WsError {
+        self.closed = true;
+        error
+    }
+}
+
+fn config() -> WebSocketConfig {
+    WebSocketConfig {
+        max_frame_size: Some(MAX_FRAME_LEN),
+        max_message_size: Some(MAX_MESSAGE_LEN),
+        ..WebSocketConfig::default()
+    }
+}
+
+fn ws_error(error: tungstenite::Error) -> WsError {
+    match error {
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => WsError::Closed,
+        tungstenite::Error::Io(error) => WsError::Io(error.to_string()),
+        tungstenite::Error::Tls(error) => WsError::Tls(error.to_string()),
+        tungstenite::Error::Http(response) => WsError::Handshake(format!("HTTP {}", response.status())),
+        tungstenite::Error::HttpFormat(error) => WsError::Handshake(error.to_string()),
+        tungstenite::Error::Utf8 => WsError::Protocol("message is not UTF-8".to_string()),
+        error => WsError::Protocol(error.to_string()),
+    }
+}
@@ -0,0 +1,459 @@
+//! Subscriptions to the v3 WebSocket channels and the typed events of their messages.
+//!
+//! Batched subscriptions deliver channel_batch_data messages; their contents are split
+//! into one event per update, so batched and unbatched subscriptions read the same.
+
+use crate::error::WsError;
+use crate::modules::api_key_signer::ApiKeySigner;
+use crate::types::*;
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// v3_orderbook of a market, e.g. "BTC-USD".
+    Orderbook(String),
+    /// v3_trades of a market.
+    Trades(String),
+    /// v3_markets, all markets.
+    Markets,
+    /// v3_accounts of an account number, e.g. "0". Messages from the server name the
+    /// account by its id instead; WsClient maps the ids of its accounts subscriptions back
+    /// to their numbers, so only channels of a bare WsMessage::parse hold an account id.
+    Accounts(String),
+}
+
+impl Channel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Channel::Orderbook(_) => "v3_orderbook",
+            Channel::Trades(_) => "v3_trades",
+            Channel::Markets => "v3_markets",
+            Channel::Accounts(_) => "v3_accounts",
+        }
+    }
+
+    /// The id of the channel in subscribe messages, if it has one.
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Channel::Orderbook(market) | Channel::Trades(market) => Some(market),
+            Channel::Markets | Channel::Accounts(_) => None,
+        }
+    }
+
+    fn from_message(name: &str, id: Option<&str>) -> Option<Channel> {
+        match (name, id) {
+            ("v3_orderbook", Some(market)) => Some(Channel::Orderbook(market.to_string())),
+            ("v3_trades", Some(market)) => Some(Channel::Trades(market.to_string())),
+            ("v3_markets", _) => Some(Channel::Markets),
+            ("v3_accounts", Some(account_id)) => Some(Channel::Accounts(account_id.to_string())),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Subscription {
+    pub channel: Channel,
+    /// Receive updates in batches, at most a few times per second.
+    pub batched: bool,
+    /// API key of the private v3_accounts channel.
+    pub credentials: Option<Credentials>,
+}
+
+/// The API key credentials of a v3_accounts subscription. Debug output shows only the key.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Credentials(ApiKeyCredentialsResponseObject);
+
+impl Credentials {
+    pub fn new(credentials: ApiKeyCredentials<'_>) -> Credentials {
+        Credentials(ApiKeyCredentialsResponseObject {
+            key: credentials.key.to_string(),
+            secret: credentials.secret.to_string(),
+            passphrase: credentials.passphrase.to_string(),
+        })
+    }
+
+    pub fn key(&self) -> &str {
+        &self.0.key
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("key", &self.0.key)
+            .field("secret", &"<redacted>")
+            .field("passphrase", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Subscription {
+    /// Order book of market, with the offset of every level.
+    pub fn orderbook(market: &str) -> Subscription {
+        Subscription {
+            channel: Channel::Orderbook(market.to_string()),
+            batched: false,
+            credentials: None,
+        }
+    }
+
+    pub fn trades(market: &str) -> Subscription {
+        Subscription {
+            channel: Channel::Trades(market.to_string()),
+            batched: false,
+            credentials: None,
+        }
+    }
+
+    pub fn markets() -> Subscription {
+        Subscription {
+            channel: Channel::Markets,
+            batched: false,
+            credentials: None,
+        }
+    }
+
+    /// Orders, fills, positions, transfers and funding payments of an account, e.g. "0".
+    /// The subscription is signed with credentials each time it is sent.
+    pub fn accounts(account_number: &str, credentials: ApiKeyCredentials<'_>) -> Subscription {
+        Subscription {
+            channel: Channel::Accounts(account_number.to_string()),
+            batched: false,
+            credentials: Some(Credentials::new(credentials)),
+        }
+    }
+
+    pub fn batched(mut self) -> Subscription {
+        self.batched = true;
+        self
+    }
+
+    pub fn subscribe_message(&self) -> String {
+        let mut message = json!({ "type": "subscribe", "channel": self.channel.name() });
+        if let Some(id) = self.channel.id() {
+            message["id"] = json!(id);
+        }
+        if let Channel::Orderbook(_) = self.channel {
+            message["includeOffsets"] = json!(true);
+        }
+        if self.batched {
+            message["batched"] = json!(true);
+        }
+        if let Channel::Accounts(account_number) = &self.channel {
+            message["accountNumber"] = json!(account_number);
+            if let Some(Credentials(credentials)) = &self.credentials {
+                let iso_timestamp = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+                let signer = ApiKeySigner::new(ApiKeyCredentials {
+                    key: &credentials.key,
+                    secret: &credentials.secret,
+                    passphrase: &credentials.passphrase,
+                });
+                message["apiKey"] = json!(credentials.key);
+                message["passphrase"] = json!(credentials.passphrase);
+                message["timestamp"] = json!(iso_timestamp);
+                message["signature"] = json!(signer.sign("/ws/accounts", "GET", &iso_timestamp, None));
+            }
+        }
+        message.to_string()
+    }
+
+    pub fn unsubscribe_message(&self) -> String {
+        let mut message = json!({ "type": "unsubscribe", "channel": self.channel.name() });
+        if let Some(id) = self.channel.id() {
+            message["id"] = json!(id);
+        }
+        if let Channel::Accounts(account_number) = &self.channel {
+            message["accountNumber"] = json!(account_number);
+        }
+        message.to_string()
+    }
+}
+
+/// A price level. offset orders the updates of a level: an update with a lower offset
+/// than the level already has is stale.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderbookLevel {
+    pub order: OrderbookResponseOrder,
+    pub offset: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderbookUpdate {
+    /// The whole book, sent on subscription.
+    Snapshot { asks: Vec<OrderbookLevel>, bids: Vec<OrderbookLevel> },
+    /// Changed levels; a size of zero removes the level.
+    Delta {
+        offset: Option<u64>,
+        asks: Vec<OrderbookLevel>,
+        bids: Vec<OrderbookLevel>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarketsUpdate {
+    /// Every market, sent on subscription.
+    Snapshot(HashMap<String, MarketData>),
+    /// The changed fields of each changed market, e.g. {"BTC-USD": {"indexPrice": "..."}}.
+    Delta(HashMap<String, Map<String, Value>>),
+}
+
+impl MarketsUpdate {
+    /// Apply the update to markets, as kept from a snapshot. Returns the markets of a delta
+    /// that could not be applied, being missing from markets or sent with fields of the
+    /// wrong type: resubscribe to get a snapshot of them.
+    pub fn apply(&self, markets: &mut HashMap<String, MarketData>) -> Vec<String> {
+        let mut skipped = Vec::new();
+        match self {
+            MarketsUpdate::Snapshot(snapshot) => *markets = snapshot.clone(),
+            MarketsUpdate::Delta(delta) => {
+                for (market, fields) in delta {
+                    let data = match markets.get_mut(market) {
+                        Some(data) => data,
+                        None => {
+                            skipped.push(market.clone());
+                            continue;
+                        }
+                    };
+                    let mut merged = match serde_json::to_value(&*data) {
+                        Ok(merged) => merged,
+                        Err(_) => {
+                            skipped.push(market.clone());
+                            continue;
+                        }
+                    };
+                    for (field, value) in fields {
+                        merged[field] = value.clone();
+                    }
+                    match serde_json::from_value(merged) {
+                        Ok(updated) => *data = updated,
+                        Err(_) => skipped.push(market.clone()),
+                    }
+                }
+            }
+        }
+        skipped.sort();
+        skipped
+    }
+}
+
+/// The contents of a v3_accounts message. Updates carry only what changed.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountsUpdate {
+    /// The account with its open positions, sent on subscription.
+    #[serde(default)]
+    pub account: Option<Box<AccountObject>>,
+    #[serde(default)]
+    pub orders: Vec<OrderResponseObject>,
+    #[serde(default)]
+    pub fills: Vec<FillResponseObject>,
+    #[serde(default)]
+    pub positions: Vec<PositionResponseObject>,
+    #[serde(default)]
+    pub transfers: Vec<TransferResponseObject>,
+    #[serde(default)]
+    pub funding_payments: Vec<FundingResponseObject>,
+    /// The changed fields of the account, e.g. {"quoteBalance": "..."}.
+    #[serde(default)]
+    pub accounts: Vec<Map<String, Value>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WsEvent {
+    Connected { connection_id: String },
+    Orderbook { market: String, update: OrderbookUpdate },
+    /// Recent trades on subscription (snapshot), then new trades.
+    Trades { market: String, snapshot: bool, trades: Vec<Trade> },
+    Markets(MarketsUpdate),
+    /// The account and its active orders on subscription (snapshot), then updates.
+    Accounts { account_id: String, snapshot: bool, update: AccountsUpdate },
+    Unsubscribed(Channel),
+    /// An error reported by the server, e.g. for an invalid subscription.
+    Error { message: String },
+}
+
+/// The events of one message from the server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WsMessage {
+    /// Numbers the messages of a connection, from 0.
+    pub message_id: Option<u64>,
+    pub events: Vec<WsEvent>,
+}
+
+#[derive(Deserialize)]
+struct Envelope {
+    #[serde(rename = "type")]
+    type_field: String,
+    connection_id: Option<String>,
+    message_id: Option<u64>,
+    channel: Option<String>,
+    id: Option<String>,
+    #[serde(default)]
+    contents: Value,
+    message: Option<String>,
+}
+
+impl WsMessage {
+    /// Parse a message. Messages of unknown types or channels have no events.
+    pub fn parse(text: &str) -> Result<WsMessage, WsError> {
+        let invalid = |reason: String| WsError::InvalidMessage {
+            reason,
+            message: text.to_string(),
+        };
+        let envelope: Envelope = serde_json::from_str(text).map_err(|e| invalid(e.to_string()))?;
+        let channel = envelope
+            .channel
+            .as_deref()
+            .and_then(|name| Channel::from_message(name, envelope.id.as_deref()));
+
+        let events = match (envelope.type_field.as_str(), channel) {
+            ("connected", _) => vec![WsEvent::Connected {
+                connection_id: envelope.connection_id.unwrap_or_default(),
+            }],
+            ("error", _) => vec![WsEvent::Error {
+                message: envelope.message.unwrap_or_default(),
+            }],
+            ("unsubscribed", Some(channel)) => vec![WsEvent::Unsubscribed(channel)],
+            ("subscribed", Some(channel)) => vec![channel_event(channel, envelope.contents, true).map_err(invalid)?],
+            ("channel_data", Some(channel)) => vec![channel_event(channel, envelope.contents, false).map_err(invalid)?],
+            ("channel_batch_data", Some(channel)) => match envelope.contents {
+                Value::Array(contents) => contents
+                    .into_iter()
+                    .map(|contents| channel_event(channel.clone(), contents, false))
+                    .collect::<Result<_, _>>()
+                    .map_err(invalid)?,
+                _ => return Err(invalid("batch contents are not an array".to_string())),
+            },
+            _ => Vec::new(),
+        };
+        Ok(WsMessage {
+            message_id: envelope.message_id,
+            events,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct SnapshotLevel {
+    size: String,
+    price: String,
+    offset: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OrderbookSnapshot {
+    #[serde(default)]
+    asks: Vec<SnapshotLevel>,
+    #[serde(default)]
+    bids: Vec<SnapshotLevel>,
+}
+
+#[derive(Deserialize)]
+struct OrderbookDelta {
+    offset: Option<String>,
+    // [price, size] or, with includeOffsets, [price, size, offset].
+    #[serde(default)]
+    asks: Vec<Vec<String>>,
+    #[serde(default)]
+    bids: Vec<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct TradesContents {
+    trades: Vec<Trade>,
+}
+
+#[derive(Deserialize)]
+struct MarketsSnapshot {
+    markets: HashMap<String, MarketData>,
+}
+
+fn channel_event(channel: Channel, contents: Value, snapshot: bool) -> Result<WsEvent, String> {
+    match channel {
+        Channel::Orderbook(market) => {
+            let update = if snapshot {
+                let book: OrderbookSnapshot = serde_json::from_value(contents).map_err(|e| e.to_string())?;
+                let levels = |levels: Vec<SnapshotLevel>| -> Result<Vec<OrderbookLevel>, String> {
+                    levels
+                        .into_iter()
+                        .map(|level| {
+                            Ok(OrderbookLevel {
+                                order: OrderbookResponseOrder {
+                                    size: level.size,
+                                    price: level.price,
+                                },
+                                offset: level.offset.as_deref().map(parse_offset).transpose()?,
+                            })
+                        })
+                        .collect()
+                };
+                OrderbookUpdate::Snapshot {
+                    asks: levels(book.asks)?,
+                    bids: levels(book.bids)?,
+                }
+            } else {
+                let delta: OrderbookDelta = serde_json::from_value(contents).map_err(|e| e.to_string())?;
+                let offset = delta.offset.as_deref().map(parse_offset).transpose()?;
+                let levels = |levels: Vec<Vec<String>>| -> Result<Vec<OrderbookLevel>, String> {
+                    levels
+                        .into_iter()
+                        .map(|level| match level.as_slice() {
+                            [price, size] | [price, size, _] => Ok(OrderbookLevel {
+                                order: OrderbookResponseOrder {
+                                    size: size.clone(),
+                                    price: price.clone(),
+                                },
+                                offset: match level.get(2) {
+                                    Some(level_offset) => Some(parse_offset(level_offset)?),
+                                    None => offset,
+                                },
+                            }),
+                            _ => Err(format!("invalid order book level {:?}", level)),
+                        })
+                        .collect()
+                };
+                OrderbookUpdate::Delta {
+                    offset,
+                    asks: levels(delta.asks)?,
+                    bids: levels(delta.bids)?,
+                }
+            };
+            Ok(WsEvent::Orderbook { market, update })
+        }
+        Channel::Trades(market) => {
+            let contents: TradesContents = serde_json::from_value(contents).map_err(|e| e.to_string())?;
+            Ok(WsEvent::Trades {
+                market,
+                snapshot,
+                trades: contents.trades,
+            })
+        }
+        Channel::Accounts(account_id) => Ok(WsEvent::Accounts {
+            account_id,
+            snapshot,
+            update: serde_json::from_value(contents).map_err(|e| e.to_string())?,
+        }),
+        Channel::Markets => {
+            if snapshot {
+                let contents: MarketsSnapshot = serde_json::from_value(contents).map_err(|e| e.to_string())?;
+                Ok(WsEvent::Markets(MarketsUpdate::Snapshot(contents.markets)))
+            } else {
+                let contents = match contents {
+                    Value::Object(mut contents) if contents.contains_key("markets") => contents.remove("markets").unwrap(),
+                    contents => contents,
+                };
+                let delta = serde_json::from_value(contents).map_err(|e| e.to_string())?;
+                Ok(WsEvent::Markets(MarketsUpdate::Delta(delta)))
+            }
+        }
+    }
+}
+
+fn parse_offset(offset: &str) -> Result<u64, String> {
+    offset.parse().map_err(|_| format!("invalid offset {}", offset))
+}
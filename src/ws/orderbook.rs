@@ -0,0 +1,285 @@
+//! An order book kept locally from a snapshot and the deltas of the v3_orderbook channel.
+//!
+//! Each level keeps the offset of its last update, and updates with a lower offset are
+//! stale. Removed levels remember their offset too, so a stale update cannot bring them
+//! back; beyond MAX_REMOVED_LEVELS per side the oldest are forgotten, and updates older
+//! than the forgotten offsets are stale at every price. A crossed book means updates were
+//! missed: deltas are ignored until the book is seeded again, e.g. by resync.
+
+use super::messages::{OrderbookLevel, OrderbookUpdate, WsEvent};
+use crate::modules::public::Public;
+use crate::types::{OrderbookResponse, OrderbookResponseOrder};
+use crate::Result;
+use bigdecimal::{BigDecimal, Zero};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use tokio::sync::watch;
+
+/// Removed levels remembered per side of a book.
+pub const MAX_REMOVED_LEVELS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceLevel {
+    pub price: BigDecimal,
+    pub size: BigDecimal,
+    /// Offset of the last update of the level; None if it was seeded from REST.
+    pub offset: Option<u64>,
+}
+
+/// The top of the book, as published to watchers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderbookTop {
+    pub best_bid: Option<PriceLevel>,
+    pub best_ask: Option<PriceLevel>,
+    /// Whether the book is seeded and not crossed.
+    pub synced: bool,
+    /// Counts the changes of the book.
+    pub version: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookStatus {
+    /// The book changed.
+    Updated,
+    /// Nothing changed: every level was stale, or the book is waiting for a snapshot.
+    Unchanged,
+    /// The book is crossed or an update could not be read. Deltas are ignored until the
+    /// next snapshot.
+    NeedsResync,
+}
+
+#[derive(Debug, Default)]
+struct BookSide {
+    levels: BTreeMap<BigDecimal, PriceLevel>,
+    // Offsets of removed levels, at most MAX_REMOVED_LEVELS.
+    removed: BTreeMap<BigDecimal, u64>,
+    // The highest offset of the removed levels that were forgotten.
+    forgotten_offset: Option<u64>,
+}
+
+impl BookSide {
+    fn clear(&mut self) {
+        self.levels.clear();
+        self.removed.clear();
+        self.forgotten_offset = None;
+    }
+
+    fn last_offset(&self, price: &BigDecimal) -> Option<u64> {
+        match self.levels.get(price) {
+            Some(level) => level.offset,
+            None => self.removed.get(price).copied().or(self.forgotten_offset),
+        }
+    }
+
+    // Forgets the older half of the removed levels.
+    fn prune_removed(&mut self) {
+        let mut offsets: Vec<u64> = self.removed.values().copied().collect();
+        offsets.sort_unstable();
+        let cutoff = offsets[offsets.len() - MAX_REMOVED_LEVELS / 2 - 1];
+        self.removed.retain(|_, offset| *offset > cutoff);
+        self.forgotten_offset = self.forgotten_offset.max(Some(cutoff));
+    }
+
+    // Applies a level, returning whether it changed the book.
+    fn apply(&mut self, level: PriceLevel) -> bool {
+        if let (Some(last), Some(offset)) = (self.last_offset(&level.price), level.offset) {
+            if offset < last {
+                return false;
+            }
+        }
+        if level.size.is_zero() {
+            if let Some(offset) = level.offset {
+                self.removed.insert(level.price.clone(), offset);
+                if self.removed.len() > MAX_REMOVED_LEVELS {
+                    self.prune_removed();
+                }
+            }
+            self.levels.remove(&level.price).is_some()
+        } else {
+            self.removed.remove(&level.price);
+            self.levels.insert(level.price.clone(), level.clone()) != Some(level)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LocalOrderBook {
+    market: String,
+    asks: BookSide,
+    bids: BookSide,
+    synced: bool,
+    version: u64,
+    top: watch::Sender<OrderbookTop>,
+}
+
+impl LocalOrderBook {
+    /// An empty book of market, e.g. "BTC-USD", waiting for a snapshot.
+    pub fn new(market: &str) -> LocalOrderBook {
+        LocalOrderBook {
+            market: market.to_string(),
+            asks: BookSide::default(),
+            bids: BookSide::default(),
+            synced: false,
+            version: 0,
+            top: watch::channel(OrderbookTop::default()).0,
+        }
+    }
+
+    pub fn market(&self) -> &str {
+        &self.market
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.synced
+    }
+
+    /// Seed the book from the snapshot of Public::get_orderbook. Its levels have no
+    /// offset, so any later delta replaces them.
+    pub fn seed(&mut self, snapshot: &OrderbookResponse) -> BookStatus {
+        let level = |order: &OrderbookResponseOrder| OrderbookLevel {
+            order: order.clone(),
+            offset: None,
+        };
+        self.apply(&OrderbookUpdate::Snapshot {
+            asks: snapshot.asks.iter().map(level).collect(),
+            bids: snapshot.bids.iter().map(level).collect(),
+        })
+    }
+
+    /// Apply a snapshot or delta of the v3_orderbook channel.
+    pub fn apply(&mut self, update: &OrderbookUpdate) -> BookStatus {
+        let (asks, bids) = match update {
+            OrderbookUpdate::Snapshot { asks, bids } => {
+                self.asks.clear();
+                self.bids.clear();
+                self.synced = true;
+                (asks, bids)
+            }
+            OrderbookUpdate::Delta { asks, bids, .. } => {
+                if !self.synced {
+                    return BookStatus::Unchanged;
+                }
+                (asks, bids)
+            }
+        };
+        let parsed = asks.iter().map(parse_level).collect::<Option<Vec<_>>>().zip(
+            bids.iter().map(parse_level).collect::<Option<Vec<_>>>(),
+        );
+        let (asks, bids) = match parsed {
+            Some(levels) => levels,
+            None => return self.desync(),
+        };
+        let mut changed = matches!(update, OrderbookUpdate::Snapshot { .. });
+        for level in asks {
+            changed |= self.asks.apply(level);
+        }
+        for level in bids {
+            changed |= self.bids.apply(level);
+        }
+        if self.is_crossed() {
+            return self.desync();
+        }
+        if !changed {
+            return BookStatus::Unchanged;
+        }
+        self.publish();
+        BookStatus::Updated
+    }
+
+    /// Apply the order book events of this market, ignoring other events.
+    pub fn apply_event(&mut self, event: &WsEvent) -> Option<BookStatus> {
+        match event {
+            WsEvent::Orderbook { market, update } if *market == self.market => Some(self.apply(update)),
+            _ => None,
+        }
+    }
+
+    /// Seed the book again from Public::get_orderbook.
+    pub async fn resync(&mut self, public: &Public<'_>) -> Result<BookStatus> {
+        let snapshot = public.get_orderbook(&self.market).await?;
+        Ok(self.seed(&snapshot))
+    }
+
+    /// Apply update, and resync from public if the book needs it.
+    pub async fn apply_or_resync(&mut self, update: &OrderbookUpdate, public: &Public<'_>) -> Result<BookStatus> {
+        match self.apply(update) {
+            BookStatus::NeedsResync => self.resync(public).await,
+            status => Ok(status),
+        }
+    }
+
+    /// Mark the book out of sync, e.g. when its updates stop arriving. Deltas are ignored
+    /// until the next snapshot.
+    pub fn invalidate(&mut self) {
+        if self.synced {
+            self.desync();
+        }
+    }
+
+    /// Whether the best bid is at or above the best ask.
+    pub fn is_crossed(&self) -> bool {
+        match (self.bids.levels.keys().next_back(), self.asks.levels.keys().next()) {
+            (Some(bid), Some(ask)) => bid >= ask,
+            _ => false,
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<&PriceLevel> {
+        self.bids.levels.values().next_back()
+    }
+
+    pub fn best_ask(&self) -> Option<&PriceLevel> {
+        self.asks.levels.values().next()
+    }
+
+    /// The n best bids, best first.
+    pub fn top_bids(&self, n: usize) -> Vec<&PriceLevel> {
+        self.bids.levels.values().rev().take(n).collect()
+    }
+
+    /// The n best asks, best first.
+    pub fn top_asks(&self, n: usize) -> Vec<&PriceLevel> {
+        self.asks.levels.values().take(n).collect()
+    }
+
+    /// The book in the shape of Public::get_orderbook, best levels first.
+    pub fn to_response(&self) -> OrderbookResponse {
+        let order = |level: &PriceLevel| OrderbookResponseOrder {
+            size: level.size.to_string(),
+            price: level.price.to_string(),
+        };
+        OrderbookResponse {
+            asks: self.asks.levels.values().map(order).collect(),
+            bids: self.bids.levels.values().rev().map(order).collect(),
+        }
+    }
+
+    /// Receives the top of the book each time the book changes or loses sync.
+    pub fn watch(&self) -> watch::Receiver<OrderbookTop> {
+        self.top.subscribe()
+    }
+
+    fn desync(&mut self) -> BookStatus {
+        self.synced = false;
+        self.publish();
+        BookStatus::NeedsResync
+    }
+
+    fn publish(&mut self) {
+        self.version += 1;
+        self.top.send_replace(OrderbookTop {
+            best_bid: self.best_bid().cloned(),
+            best_ask: self.best_ask().cloned(),
+            synced: self.synced,
+            version: self.version,
+        });
+    }
+}
+
+fn parse_level(level: &OrderbookLevel) -> Option<PriceLevel> {
+    Some(PriceLevel {
+        price: BigDecimal::from_str(&level.order.price).ok()?,
+        size: BigDecimal::from_str(&level.order.size).ok()?,
+        offset: level.offset,
+    })
+}
@@ -0,0 +1,332 @@
+//! A WebSocket client that survives disconnects.
+//!
+//! WsSupervisor keeps the subscriptions it was given and an order book per v3_orderbook
+//! subscription. When the connection fails, the server closes it or its heartbeat stops,
+//! it reconnects with the "ws_reconnect" backoff of its ExponentialBuilderHelperGet,
+//! subscribes again and re-seeds the order books from REST. Every channel that may have
+//! missed updates is reported with a GapDetected event.
+//!
+//! With with_account_state it also keeps an AccountState current from the v3_accounts
+//! channel, and re-seeds it from REST after gaps of that channel.
+
+use super::account_state::AccountState;
+use super::client::WsClient;
+use super::messages::{Channel, Subscription, WsEvent};
+use super::orderbook::{BookStatus, LocalOrderBook};
+use crate::error::WsError;
+use crate::modules::private::Private;
+use crate::modules::public::Public;
+use crate::retry::ExponentialBuilderHelperGet;
+use backon::BackoffBuilder;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Key of the reconnect backoff in the ExponentialBuilderHelperGet.
+pub const RECONNECT_BACKOFF_KEY: &str = "ws_reconnect";
+/// The server pings every 30 seconds.
+pub const DEFAULT_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GapReason {
+    /// The connection was lost; updates sent meanwhile are missing.
+    Reconnected,
+    /// The message_id of the connection skipped from expected to received.
+    MissedMessages { expected: u64, received: u64 },
+    /// The order book of the channel became crossed.
+    CrossedBook,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SupervisorEvent {
+    /// An event of the current connection. Order book events are already applied to the
+    /// book of their market.
+    Event(WsEvent),
+    /// A new connection replaced one lost because of cause, after attempts connection
+    /// attempts, and every subscription was sent again.
+    Reconnected { attempts: usize, cause: String },
+    /// Updates of channel may have been missed. For order book channels, and v3_accounts
+    /// with with_account_state, resynced tells whether the state was re-seeded from REST;
+    /// otherwise it is out of sync until the snapshot of the new subscription arrives.
+    /// Other channels are never resynced.
+    GapDetected { channel: Channel, reason: GapReason, resynced: bool },
+}
+
+#[derive(Debug)]
+struct SupervisedAccount<'a> {
+    state: AccountState,
+    private: Arc<Private<'a>>,
+    ethereum_address: String,
+}
+
+#[derive(Debug)]
+pub struct WsSupervisor<'a> {
+    url: String,
+    backoff_getter: Arc<dyn ExponentialBuilderHelperGet>,
+    public: Option<Public<'a>>,
+    account: Option<SupervisedAccount<'a>>,
+    heartbeat_timeout: Option<Duration>,
+    subscriptions: Vec<Subscription>,
+    books: HashMap<String, LocalOrderBook>,
+    client: Option<WsClient>,
+    // Why the last connection was lost; None before the first connection.
+    lost_because: Option<String>,
+    closed: bool,
+    pending: VecDeque<SupervisorEvent>,
+}
+
+impl<'a> WsSupervisor<'a> {
+    /// A supervisor of connections to url. It connects on the first call of next_event.
+    pub fn new(url: &str, backoff_getter: Arc<dyn ExponentialBuilderHelperGet>) -> WsSupervisor<'a> {
+        WsSupervisor {
+            url: url.to_string(),
+            backoff_getter,
+            public: None,
+            account: None,
+            heartbeat_timeout: Some(DEFAULT_HEARTBEAT_TIMEOUT),
+            subscriptions: Vec::new(),
+            books: HashMap::new(),
+            client: None,
+            lost_because: None,
+            closed: false,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Re-seed order books from the REST API of public after a gap.
+    pub fn with_public(mut self, public: Public<'a>) -> WsSupervisor<'a> {
+        self.public = Some(public);
+        self
+    }
+
+    /// Apply the v3_accounts events of ethereum_address to state, and re-seed it through
+    /// private after gaps of its channel. Its events should not also be passed to
+    /// AccountState::handle.
+    pub fn with_account_state(
+        mut self,
+        state: AccountState,
+        private: Arc<Private<'a>>,
+        ethereum_address: &str,
+    ) -> WsSupervisor<'a> {
+        self.account = Some(SupervisedAccount {
+            state,
+            private,
+            ethereum_address: ethereum_address.to_string(),
+        });
+        self
+    }
+
+    /// Reconnect when nothing is received for timeout; None disables the check.
+    pub fn with_heartbeat_timeout(mut self, timeout: Option<Duration>) -> WsSupervisor<'a> {
+        self.heartbeat_timeout = timeout;
+        self
+    }
+
+    pub fn subscriptions(&self) -> &[Subscription] {
+        &self.subscriptions
+    }
+
+    /// The order book of an orderbook subscription.
+    pub fn book(&self, market: &str) -> Option<&LocalOrderBook> {
+        self.books.get(market)
+    }
+
+    /// The account state given to with_account_state.
+    pub fn account_state(&self) -> Option<&AccountState> {
+        self.account.as_ref().map(|account| &account.state)
+    }
+
+    /// Add a subscription, kept across reconnects. Subscribing twice to a channel
+    /// replaces the first subscription.
+    pub async fn subscribe(&mut self, subscription: Subscription) -> Result<(), WsError> {
+        self.subscriptions.retain(|s| s.channel != subscription.channel);
+        if let Channel::Orderbook(market) = &subscription.channel {
+            self.books
+                .entry(market.clone())
+                .or_insert_with(|| LocalOrderBook::new(market));
+        }
+        self.subscriptions.push(subscription.clone());
+        if let Some(client) = &mut self.client {
+            if let Err(error) = client.subscribe(&subscription).await {
+                // next_event reconnects and sends every subscription.
+                self.lose_connection(error.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn unsubscribe(&mut self, channel: &Channel) -> Result<(), WsError> {
+        let subscription = match self.subscriptions.iter().position(|s| s.channel == *channel) {
+            Some(index) => self.subscriptions.remove(index),
+            None => return Ok(()),
+        };
+        if let Channel::Orderbook(market) = channel {
+            self.books.remove(market);
+        }
+        if let Some(client) = &mut self.client {
+            if let Err(error) = client.unsubscribe(&subscription).await {
+                self.lose_connection(error.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// The next event. Connection failures are handled here and reported as Reconnected
+    /// and GapDetected events; an error is returned only when the backoff gives up, or
+    /// for a message that cannot be parsed. None once close was called. Not cancel-safe:
+    /// a future dropped while reconnecting or re-seeding loses those steps. The heartbeat
+    /// timeout already bounds every wait.
+    pub async fn next_event(&mut self) -> Result<Option<SupervisorEvent>, WsError> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+            if self.closed {
+                return Ok(None);
+            }
+            let client = match &mut self.client {
+                Some(client) => client,
+                None => {
+                    self.connect().await?;
+                    continue;
+                }
+            };
+            let last_message_id = client.last_message_id();
+            let result = client.next_event().await;
+            let message_id = client.last_message_id();
+            match result {
+                Ok(Some(event)) => {
+                    if let (Some(last), Some(received)) = (last_message_id, message_id) {
+                        if received > last + 1 {
+                            let reason = GapReason::MissedMessages {
+                                expected: last + 1,
+                                received,
+                            };
+                            self.report_gaps(reason).await;
+                        }
+                    }
+                    self.handle(event).await;
+                }
+                Ok(None) => self.lose_connection("closed by the server".to_string()),
+                Err(error @ WsError::InvalidMessage { .. }) => return Err(error),
+                Err(error) => self.lose_connection(error.to_string()),
+            }
+        }
+    }
+
+    /// Close the connection. Subscriptions are kept, but next_event returns None.
+    pub async fn close(&mut self) -> Result<(), WsError> {
+        self.closed = true;
+        match self.client.take() {
+            Some(mut client) => client.close().await,
+            None => Ok(()),
+        }
+    }
+
+    async fn handle(&mut self, event: WsEvent) {
+        let mut crossed = None;
+        if let WsEvent::Orderbook { market, update } = &event {
+            if let Some(book) = self.books.get_mut(market) {
+                if book.apply(update) == BookStatus::NeedsResync {
+                    crossed = Some(SupervisorEvent::GapDetected {
+                        channel: Channel::Orderbook(market.clone()),
+                        reason: GapReason::CrossedBook,
+                        resynced: resync(book, self.public.as_ref()).await,
+                    });
+                }
+            }
+        }
+        let event = SupervisorEvent::Event(event);
+        if let Some(account) = &mut self.account {
+            // A failed re-read of the balances leaves them as of the last one; the
+            // events themselves are applied regardless.
+            let _ = account
+                .state
+                .handle(&event, &account.private, &account.ethereum_address)
+                .await;
+        }
+        self.pending.push_back(event);
+        self.pending.extend(crossed);
+    }
+
+    fn lose_connection(&mut self, cause: String) {
+        self.client = None;
+        self.lost_because = Some(cause);
+        for book in self.books.values_mut() {
+            book.invalidate();
+        }
+    }
+
+    async fn connect(&mut self) -> Result<(), WsError> {
+        let mut backoff = self.backoff_getter.get(RECONNECT_BACKOFF_KEY).build();
+        let mut attempts = 0;
+        let client = loop {
+            attempts += 1;
+            let connected = match WsClient::connect(&self.url).await {
+                Ok(mut client) => {
+                    client.set_heartbeat_timeout(self.heartbeat_timeout);
+                    subscribe_all(&mut client, &self.subscriptions).await.map(|_| client)
+                }
+                Err(error) => Err(error),
+            };
+            match connected {
+                Ok(client) => break client,
+                Err(error) => match backoff.next() {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(error),
+                },
+            }
+        };
+        self.client = Some(client);
+        if let Some(cause) = self.lost_because.take() {
+            self.pending.push_back(SupervisorEvent::Reconnected { attempts, cause });
+            self.report_gaps(GapReason::Reconnected).await;
+        }
+        Ok(())
+    }
+
+    // A GapDetected event for every subscription, re-seeding the order books and the
+    // account state.
+    async fn report_gaps(&mut self, reason: GapReason) {
+        for subscription in &self.subscriptions {
+            let resynced = match &subscription.channel {
+                Channel::Orderbook(market) => match self.books.get_mut(market) {
+                    Some(book) => resync(book, self.public.as_ref()).await,
+                    None => false,
+                },
+                Channel::Accounts(account_number) => match &mut self.account {
+                    Some(account) if account.state.tracks(account_number) => account
+                        .state
+                        .refresh(&account.private, &account.ethereum_address)
+                        .await
+                        .is_ok(),
+                    _ => false,
+                },
+                _ => false,
+            };
+            self.pending.push_back(SupervisorEvent::GapDetected {
+                channel: subscription.channel.clone(),
+                reason: reason.clone(),
+                resynced,
+            });
+        }
+    }
+}
+
+async fn subscribe_all(client: &mut WsClient, subscriptions: &[Subscription]) -> Result<(), WsError> {
+    for subscription in subscriptions {
+        client.subscribe(subscription).await?;
+    }
+    Ok(())
+}
+
+// Re-seeds book from REST, returning whether it is in sync again.
+async fn resync(book: &mut LocalOrderBook, public: Option<&Public<'_>>) -> bool {
+    match public {
+        Some(public) => matches!(book.resync(public).await, Ok(BookStatus::Updated | BookStatus::Unchanged)),
+        None => {
+            book.invalidate();
+            false
+        }
+    }
+}
@@ -0,0 +1,301 @@
+macro_rules! b {
+        ($e:expr) => {
+                tokio_test::block_on($e)
+        };
+}
+
+mod common;
+
+use dydx_v3_rust::helper::get_account_id;
+use dydx_v3_rust::ws::account_state::*;
+use dydx_v3_rust::ws::messages::{AccountsUpdate, Channel, WsEvent};
+use dydx_v3_rust::ws::supervisor::{GapReason, SupervisorEvent};
+use dydx_v3_rust::DydxClient;
+use serde_json::{json, Value};
+use speculate::speculate;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+const ADDRESS: &str = "0x0000000000000000000000000000000000000001";
+
+fn order(id: &str, client_id: &str, status: &str) -> Value {
+        json!({"id":id,"clientId":client_id,"accountId":"a1","market":"BTC-USD","side":"BUY","price":"100","triggerPrice":null,"trailingPercent":null,"size":"1","remainingSize":"1","type":"LIMIT","createdAt":"2023-01-01T00:00:00.000Z","unfillableAt":null,"expiresAt":"2023-02-01T00:00:00.000Z","status":status,"timeInForce":"GTT","postOnly":false,"cancelReason":null})
+}
+
+fn position(market: &str, status: &str, size: &str) -> Value {
+        json!({"market":market,"status":status,"side":"LONG","size":size,"maxSize":size,"entryPrice":"100","exitPrice":null,"unrealizedPnl":"0","realizedPnl":"0","createdAt":"2023-01-01T00:00:00.000Z","closedAt":null,"sumOpen":size,"sumClose":"0","netFunding":"0"})
+}
+
+fn fill(id: &str) -> Value {
+        json!({"id":id,"side":"BUY","liquidity":"TAKER","type":"LIMIT","market":"BTC-USD","orderId":"o1","price":"100","size":"1","fee":"0.1","createdAt":"2023-01-01T00:00:01.000Z"})
+}
+
+fn account(quote_balance: &str, positions: Value) -> Value {
+        json!({"starkKey":"0x1","positionId":"1","equity":"1000","freeCollateral":"900","pendingDeposits":"0","pendingWithdrawals":"0","openPositions":positions,"accountNumber":"0","id":"a1","quoteBalance":quote_balance})
+}
+
+fn created_at(mut value: Value, second: usize) -> Value {
+        value["createdAt"] = json!(format!("2023-01-01T{:02}:{:02}:{:02}.000Z", second / 3600, second / 60 % 60, second % 60));
+        value
+}
+
+fn update(contents: Value) -> AccountsUpdate {
+        serde_json::from_value(contents).unwrap()
+}
+
+// The items of a listing that match the status, createdBeforeOrAt and limit of query,
+// newest first.
+fn page(items: &Value, query: &str) -> Value {
+        let query: HashMap<&str, String> = query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(key, value)| (key, value.replace("%3A", ":")))
+                .collect();
+        let mut items: Vec<Value> = items
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|item| query.get("status").is_none_or(|status| item["status"] == status.as_str()))
+                .filter(|item| query.get("createdBeforeOrAt").is_none_or(|at| item["createdAt"].as_str().unwrap() <= at.as_str()))
+                .cloned()
+                .collect();
+        items.sort_by(|a, b| b["createdAt"].as_str().cmp(&a["createdAt"].as_str()));
+        items.truncate(query.get("limit").map_or(100, |limit| limit.parse().unwrap()));
+        json!(items)
+}
+
+// Serves the account, open orders and fills in state, one request per connection.
+async fn mock_api(state: Arc<Mutex<Value>>) -> String {
+        common::serve_api(move |request| {
+                let state = state.lock().unwrap().clone();
+                let head = request.head;
+                let body = if head.starts_with(&format!("GET /v3/accounts/{} ", get_account_id(ADDRESS))) {
+                        json!({"account": state["account"]})
+                } else if let Some(query) = head.strip_prefix("GET /v3/orders?") {
+                        json!({"orders": page(&state["orders"], query.split(' ').next().unwrap())})
+                } else if let Some(query) = head.strip_prefix("GET /v3/fills?") {
+                        json!({"fills": page(&state["fills"], query.split(' ').next().unwrap())})
+                } else {
+                        panic!("unexpected request {}", head)
+                };
+                async move { body.to_string() }
+        })
+        .await
+}
+
+#[cfg(test)]
+speculate! {
+        describe "accountStateTest" {
+                it "appliesAccountsChannelUpdates" {
+                        let mut state = AccountState::new();
+                        let mut diffs = state.diffs();
+                        let mut snapshots = state.watch();
+
+                        let seeded = state.apply("a1", true, &update(json!({
+                                "account": account("1000", json!({"BTC-USD": position("BTC-USD", "OPEN", "1")})),
+                                "orders": [order("o1", "c1", "OPEN"), order("o2", "c2", "UNTRIGGERED")],
+                        })));
+                        assert_eq!(seeded.len(), 4);
+                        let snapshot = snapshots.borrow_and_update().clone();
+                        assert!(snapshot.seeded);
+                        assert_eq!(snapshot.free_collateral, "900");
+                        assert_eq!(snapshot.positions["BTC-USD"].size, "1");
+                        assert_eq!(snapshot.order_by_client_id("c2").unwrap().id, "o2");
+
+                        let applied = state.apply("a1", false, &update(json!({
+                                "orders": [order("o1", "c1", "FILLED")],
+                                "fills": [fill("f1")],
+                                "positions": [position("BTC-USD", "CLOSED", "0"), position("ETH-USD", "OPEN", "2")],
+                                "accounts": [{"id": "a1", "quoteBalance": "899.9"}],
+                        })));
+                        assert!(matches!(&applied[0], AccountDiff::OrderClosed(order) if order.id == "o1"));
+                        assert!(matches!(&applied[1], AccountDiff::PositionClosed(position) if position.market == "BTC-USD"));
+                        assert!(matches!(&applied[2], AccountDiff::PositionUpdated(position) if position.market == "ETH-USD"));
+                        assert_eq!(applied[3], AccountDiff::BalanceUpdated {
+                                equity: "1000".into(),
+                                free_collateral: "900".into(),
+                                quote_balance: "899.9".into(),
+                        });
+                        assert!(matches!(&applied[4], AccountDiff::Fill(fill) if fill.id == "f1"));
+                        assert_eq!(applied.len(), 5);
+
+                        let snapshot = state.snapshot();
+                        assert!(!snapshot.orders.contains_key("o1"));
+                        assert!(snapshot.order_by_client_id("c1").is_none());
+                        assert_eq!(snapshot.positions.keys().collect::<Vec<_>>(), vec!["ETH-USD"]);
+                        assert_eq!(snapshot.quote_balance, "899.9");
+                        assert_eq!(snapshots.borrow_and_update().version, snapshot.version);
+
+                        // Repeated fills and other accounts change nothing.
+                        assert!(state.apply("a1", false, &update(json!({"fills": [fill("f1")]}))).is_empty());
+                        assert!(state.apply_event(&WsEvent::Accounts {
+                                account_id: "a2".into(),
+                                snapshot: false,
+                                update: update(json!({"orders": [order("o3", "c3", "OPEN")]})),
+                        }).is_empty());
+                        assert!(!snapshots.has_changed().unwrap());
+
+                        let mut received = Vec::new();
+                        while let Ok(diff) = diffs.try_recv() {
+                                received.push(diff);
+                        }
+                        assert_eq!(received, [seeded, applied].concat());
+                }
+
+                it "refreshesFromRest" {
+                        b!(async {
+                                let api = Arc::new(Mutex::new(json!({
+                                        "account": account("1000", json!({"BTC-USD": position("BTC-USD", "OPEN", "1")})),
+                                        "orders": [order("o1", "c1", "OPEN")],
+                                        "fills": [fill("f1")],
+                                })));
+                                let host = mock_api(api.clone()).await;
+                                let client = DydxClient::new(&host, common::options());
+                                let private = client.private.unwrap();
+                                let mut state = AccountState::new();
+
+                                let seeded = state.refresh(&private, ADDRESS).await.unwrap();
+                                assert!(seeded.iter().all(|diff| !matches!(diff, AccountDiff::Fill(_))));
+                                assert_eq!(state.snapshot().order_by_client_id("c1").unwrap().id, "o1");
+                                assert_eq!(state.snapshot().account_id, "a1");
+
+                                *api.lock().unwrap() = json!({
+                                        "account": account("950", json!({"BTC-USD": position("BTC-USD", "OPEN", "2")})),
+                                        "orders": [],
+                                        "fills": [fill("f2"), fill("f1")],
+                                });
+                                let diffs = state.refresh(&private, ADDRESS).await.unwrap();
+                                assert_eq!(diffs.len(), 4);
+                                assert!(matches!(&diffs[0], AccountDiff::OrderClosed(order) if order.status == "OPEN"));
+                                assert!(matches!(&diffs[1], AccountDiff::PositionUpdated(position) if position.size == "2"));
+                                assert!(matches!(&diffs[2], AccountDiff::BalanceUpdated { quote_balance, .. } if quote_balance == "950"));
+                                assert!(matches!(&diffs[3], AccountDiff::Fill(fill) if fill.id == "f2"));
+                                assert!(state.snapshot().orders.is_empty());
+
+                                assert!(state.refresh(&private, ADDRESS).await.unwrap().is_empty());
+                        });
+                }
+
+                it "pagesOrdersAndFills" {
+                        b!(async {
+                                let orders: Vec<Value> = (0..250).map(|i| created_at(order(&format!("o{}", i), &format!("c{}", i), "OPEN"), i)).collect();
+                                let fills: Vec<Value> = (0..150).map(|i| created_at(fill(&format!("f{}", i)), i)).collect();
+                                let api = Arc::new(Mutex::new(json!({
+                                        "account": account("1000", json!({})),
+                                        "orders": orders,
+                                        "fills": fills,
+                                })));
+                                let host = mock_api(api.clone()).await;
+                                let client = DydxClient::new(&host, common::options());
+                                let private = client.private.unwrap();
+                                let mut state = AccountState::new();
+
+                                state.refresh(&private, ADDRESS).await.unwrap();
+                                assert_eq!(state.snapshot().orders.len(), 250);
+
+                                // 220 new fills span three pages; the oldest 50 were read before.
+                                let fills: Vec<Value> = (0..370).map(|i| created_at(fill(&format!("f{}", i)), i)).collect();
+                                api.lock().unwrap()["fills"] = json!(fills);
+                                let diffs = state.refresh(&private, ADDRESS).await.unwrap();
+                                let reported: Vec<String> = diffs
+                                        .iter()
+                                        .filter_map(|diff| match diff {
+                                                AccountDiff::Fill(fill) => Some(fill.id.clone()),
+                                                _ => None,
+                                        })
+                                        .collect();
+                                assert_eq!(reported, (150..370).map(|i| format!("f{}", i)).collect::<Vec<_>>());
+                        });
+                }
+
+                it "forgetsOldFills" {
+                        let mut state = AccountState::new();
+                        let fills: Vec<Value> = (0..=MAX_SEEN_FILLS).map(|i| fill(&format!("f{}", i))).collect();
+                        assert_eq!(state.apply("a1", false, &update(json!({"fills": fills}))).len(), MAX_SEEN_FILLS + 1);
+
+                        let latest = format!("f{}", MAX_SEEN_FILLS);
+                        assert!(state.apply("a1", false, &update(json!({"fills": [fill(&latest)]}))).is_empty());
+                        assert_eq!(state.apply("a1", false, &update(json!({"fills": [fill("f0")]}))).len(), 1);
+                }
+
+                it "refreshesBalancesAfterFills" {
+                        b!(async {
+                                let api = Arc::new(Mutex::new(json!({
+                                        "account": account("1000", json!({})),
+                                        "orders": [],
+                                        "fills": [],
+                                })));
+                                let host = mock_api(api.clone()).await;
+                                let client = DydxClient::new(&host, common::options());
+                                let private = client.private.unwrap();
+                                let mut state = AccountState::new();
+                                state.refresh(&private, ADDRESS).await.unwrap();
+
+                                let mut account = account("900", json!({"BTC-USD": position("BTC-USD", "OPEN", "1")}));
+                                account["freeCollateral"] = json!("800");
+                                api.lock().unwrap()["account"] = account;
+                                let event = SupervisorEvent::Event(WsEvent::Accounts {
+                                        account_id: "a1".into(),
+                                        snapshot: false,
+                                        update: update(json!({"fills": [fill("f1")]})),
+                                });
+                                let diffs = state.handle(&event, &private, ADDRESS).await.unwrap();
+                                assert!(matches!(&diffs[0], AccountDiff::Fill(fill) if fill.id == "f1"));
+                                assert!(matches!(&diffs[1], AccountDiff::PositionUpdated(position) if position.market == "BTC-USD"));
+                                assert!(matches!(&diffs[2], AccountDiff::BalanceUpdated { free_collateral, .. } if free_collateral == "800"));
+                                assert_eq!(state.snapshot().quote_balance, "900");
+                        });
+                }
+
+                it "refreshesOnSupervisorGaps" {
+                        b!(async {
+                                let api = Arc::new(Mutex::new(json!({
+                                        "account": account("1000", json!({})),
+                                        "orders": [order("o1", "c1", "OPEN")],
+                                        "fills": [],
+                                })));
+                                let host = mock_api(api.clone()).await;
+                                let client = DydxClient::new(&host, common::options());
+                                let private = client.private.unwrap();
+                                let mut state = AccountState::new();
+
+                                let trades_gap = SupervisorEvent::GapDetected {
+                                        channel: Channel::Trades("BTC-USD".into()),
+                                        reason: GapReason::Reconnected,
+                                        resynced: false,
+                                };
+                                assert!(state.handle(&trades_gap, &private, ADDRESS).await.unwrap().is_empty());
+                                assert!(!state.snapshot().seeded);
+
+                                let accounts_gap = SupervisorEvent::GapDetected {
+                                        channel: Channel::Accounts("0".into()),
+                                        reason: GapReason::Reconnected,
+                                        resynced: false,
+                                };
+                                state.handle(&accounts_gap, &private, ADDRESS).await.unwrap();
+                                assert!(state.snapshot().seeded);
+                                assert!(state.snapshot().orders.contains_key("o1"));
+
+                                // Gaps of another account number do not refresh this account.
+                                api.lock().unwrap()["orders"] = json!([]);
+                                let other_gap = SupervisorEvent::GapDetected {
+                                        channel: Channel::Accounts("1".into()),
+                                        reason: GapReason::Reconnected,
+                                        resynced: false,
+                                };
+                                assert!(state.handle(&other_gap, &private, ADDRESS).await.unwrap().is_empty());
+                                assert!(state.snapshot().orders.contains_key("o1"));
+                                api.lock().unwrap()["orders"] = json!([order("o1", "c1", "OPEN")]);
+
+                                let event = SupervisorEvent::Event(WsEvent::Accounts {
+                                        account_id: "a1".into(),
+                                        snapshot: false,
+                                        update: update(json!({"orders": [order("o1", "c1", "CANCELED")]})),
+                                });
+                                let diffs = state.handle(&event, &private, ADDRESS).await.unwrap();
+                                assert!(matches!(&diffs[0], AccountDiff::OrderClosed(order) if order.status == "CANCELED"));
+                                assert!(state.snapshot().orders.is_empty());
+                        });
+                }
+        }
+}
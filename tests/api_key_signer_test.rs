@@ -0,0 +1,62 @@
+macro_rules! b {
+        ($e:expr) => {
+                tokio_test::block_on($e)
+        };
+}
+
+mod common;
+
+use common::credentials;
+use dydx_v3_rust::modules::api_key_signer::ApiKeySigner;
+use dydx_v3_rust::DydxClient;
+use http::Method;
+use serde_json::json;
+use speculate::speculate;
+
+const TIMESTAMP: &str = "2023-01-01T00:00:00.000Z";
+
+#[cfg(test)]
+speculate! {
+        describe "apiKeySignerTest" {
+                it "signsPathWithQuery" {
+                        let signer = ApiKeySigner::new(credentials());
+                        assert_eq!(signer.sign("/v3/orders?market=BTC-USD", "GET", TIMESTAMP, None), "rKsLONo53Tdhni7AbiT22ly6A/qJGDDOBRTIqzDvByg=");
+                        assert_eq!(signer.sign("/v3/orders?market=BTC-USD", "GET", TIMESTAMP, Some("{}")), "rKsLONo53Tdhni7AbiT22ly6A/qJGDDOBRTIqzDvByg=");
+                }
+
+                it "signsBody" {
+                        let signer = ApiKeySigner::new(credentials());
+                        assert_eq!(signer.sign("/v3/orders", "POST", TIMESTAMP, Some(r#"{"market":"BTC-USD"}"#)), "co7ExCSB8J2L53e1nobNw5triFhoYLTrd11DwLhBWc8=");
+                }
+
+                it "buildsHeaders" {
+                        let headers = ApiKeySigner::new(credentials()).headers_at("GET", "/v3/orders?market=BTC-USD", None, TIMESTAMP);
+                        assert_eq!(headers, vec![
+                                ("DYDX-SIGNATURE", "rKsLONo53Tdhni7AbiT22ly6A/qJGDDOBRTIqzDvByg=".to_string()),
+                                ("DYDX-TIMESTAMP", TIMESTAMP.to_string()),
+                                ("DYDX-API-KEY", "key".to_string()),
+                                ("DYDX-PASSPHRASE", "passphrase".to_string()),
+                        ]);
+                }
+
+                it "requestRawAuthenticates" {
+                        b!(async {
+                                let (host, received) = common::serve_once(r#"{"ok":true}"#).await;
+                                let client = DydxClient::new(&host, common::options());
+                                let private = client.private.unwrap();
+                                let response: serde_json::Value = private
+                                        .request_raw(Method::GET, "new-endpoint", vec![("market", "BTC-USD")], json!({}))
+                                        .await
+                                        .unwrap();
+                                assert_eq!(response, json!({"ok": true}));
+
+                                let head = received.await.unwrap().head;
+                                assert!(head.starts_with("GET /v3/new-endpoint?market=BTC-USD HTTP/1.1"));
+                                let timestamp = head.lines().find_map(|line| line.strip_prefix("dydx-timestamp: ")).unwrap();
+                                let signature = private.api_key_signer().sign("/v3/new-endpoint?market=BTC-USD", "GET", timestamp, None);
+                                assert!(head.contains(&format!("dydx-signature: {}\r\n", signature)));
+                                assert!(head.contains("dydx-api-key: key\r\n"));
+                        });
+                }
+        }
+}
@@ -0,0 +1,108 @@
+//! Fixtures shared by the tests: client options and a local stand-in for the REST API.
+#![allow(dead_code)]
+
+use dydx_v3_rust::constants::TESTNET_NETWORK_ID;
+use dydx_v3_rust::types::ApiKeyCredentials;
+use dydx_v3_rust::{ClientOptions, DydxClient};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+pub const API_SECRET: &str = "c2VjcmV0";
+
+pub fn credentials() -> ApiKeyCredentials<'static> {
+        ApiKeyCredentials {
+                key: "key",
+                secret: API_SECRET,
+                passphrase: "passphrase",
+        }
+}
+
+/// Testnet options with API key credentials, failing private requests instead of retrying.
+pub fn options() -> ClientOptions<'static> {
+        ClientOptions {
+                network_id: Some(TESTNET_NETWORK_ID),
+                api_key_credentials: Some(credentials()),
+                private_backoff_getter: Some(DydxClient::get_no_backoff_getter()),
+                ..ClientOptions::default()
+        }
+}
+
+/// A request received by the stand-in API.
+#[derive(Debug)]
+pub struct Request {
+        /// Request line and headers, up to the blank line.
+        pub head: String,
+        pub body: String,
+}
+
+/// Serve the REST API on a local port, answering each request with the JSON body respond
+/// returns for it. Connections are served concurrently. Returns the host for DydxClient.
+pub async fn serve_api<F, R>(respond: F) -> String
+where
+        F: Fn(Request) -> R + Send + Sync + 'static,
+        R: Future<Output = String> + Send + 'static,
+{
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
+        let respond = Arc::new(respond);
+        tokio::spawn(async move {
+                loop {
+                        let (mut stream, _) = listener.accept().await.unwrap();
+                        let respond = respond.clone();
+                        tokio::spawn(async move {
+                                let request = read_request(&mut stream).await;
+                                let body = respond(request).await;
+                                let response = format!(
+                                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\nconnection: close\r\ncontent-length: {}\r\n\r\n{}",
+                                        body.len(),
+                                        body
+                                );
+                                stream.write_all(response.as_bytes()).await.unwrap();
+                        });
+                }
+        });
+        host
+}
+
+/// Serve the REST API, answering with body, and send back the first request received.
+pub async fn serve_once(body: &'static str) -> (String, oneshot::Receiver<Request>) {
+        let (sender, receiver) = oneshot::channel();
+        let sender = Mutex::new(Some(sender));
+        let host = serve_api(move |request| {
+                if let Some(sender) = sender.lock().unwrap().take() {
+                        sender.send(request).unwrap();
+                }
+                async move { body.to_string() }
+        })
+        .await;
+        (host, receiver)
+}
+
+async fn read_request(stream: &mut TcpStream) -> Request {
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        let body_at = loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                if let Some(at) = String::from_utf8_lossy(&request).find("\r\n\r\n") {
+                        break at + 4;
+                }
+        };
+        let head = String::from_utf8_lossy(&request[..body_at]).to_string();
+        let content_length: usize = head
+                .to_lowercase()
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:").map(|length| length.trim().parse().unwrap()))
+                .unwrap_or(0);
+        while request.len() < body_at + content_length {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+        }
+        Request {
+                head,
+                body: String::from_utf8_lossy(&request[body_at..]).to_string(),
+        }
+}
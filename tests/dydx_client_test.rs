@@ -0,0 +1,56 @@
+mod common;
+
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::types::*;
+use dydx_v3_rust::modules::self_test::BACKEND;
+use dydx_v3_rust::{ClientOptions, DydxClient, EthError, SelfTestError, StarkError};
+use speculate::speculate;
+
+fn options<'a>(stark_private_key: Option<&'a str>, eth_private_key: Option<&'a str>) -> ClientOptions<'a> {
+        ClientOptions {
+                stark_private_key,
+                eth_private_key,
+                ..common::options()
+        }
+}
+
+#[cfg(test)]
+speculate! {
+        describe "dydxClientTest" {
+                it "tryNewBuildsClient" {
+                        let client = DydxClient::try_new(TESTNET_API_URL, options(Some(TEST_STARK_PRIVATE_KEY), Some(TEST_PRIVATE_KEY))).unwrap();
+                        assert!(client.private.is_some());
+                        assert!(client.onboarding.is_some());
+                }
+
+                it "tryNewRejectsInvalidStarkKey" {
+                        let error = DydxClient::try_new(TESTNET_API_URL, options(Some("not hex"), None)).unwrap_err();
+                        assert!(error.downcast_ref::<StarkError>().is_some());
+                }
+
+                it "tryNewRejectsInvalidEthKey" {
+                        let error = DydxClient::try_new(TESTNET_API_URL, options(None, Some("0x1234"))).unwrap_err();
+                        assert_eq!(error.downcast_ref::<EthError>(), Some(&EthError::InvalidPrivateKey));
+                }
+
+                it "newDefersInvalidKeysToFirstSignature" {
+                        let client = DydxClient::new(TESTNET_API_URL, options(Some("not hex"), Some("0x1234")));
+                        let params = ApiWithdrawParams { position_id: "228", amount: "10.5", asset: "USDC", client_id: None, expiration: 1700000000 };
+                        let error = tokio_test::block_on(client.private.unwrap().prepare_create_withdraw(params)).unwrap_err();
+                        assert!(error.downcast_ref::<StarkError>().is_some());
+                        let error = tokio_test::block_on(client.onboarding.unwrap().derive_stark_key(TEST_ADDRESS)).unwrap_err();
+                        assert_eq!(error.downcast_ref::<EthError>(), Some(&EthError::InvalidPrivateKey));
+                }
+
+                it "selfTestSignsEveryKind" {
+                        let report = DydxClient::self_test().unwrap();
+                        assert_eq!(report.backend, BACKEND);
+                        assert_eq!(report.checks, vec!["sign_order", "sign_withdraw", "sign_fast_withdraw", "sign_transfer", "sign_private", "sign_onboarding"]);
+                }
+
+                it "selfTestErrorNamesBackendAndCheck" {
+                        let error = SelfTestError::SignFailed { backend: "python", check: "sign_order", reason: "No module named 'web3'".into() };
+                        assert_eq!(error.to_string(), "python signing backend failed sign_order: No module named 'web3'");
+                }
+        }
+}
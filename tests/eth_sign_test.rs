@@ -0,0 +1,78 @@
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::eth::onboarding_action::SignOnboardingAction;
+use dydx_v3_rust::eth::signers::SignWithKey;
+use dydx_v3_rust::eth::util::{fix_raw_signature, to_typed_signature};
+use dydx_v3_rust::modules::eth_sign::*;
+use dydx_v3_rust::modules::onboarding::Onboarding;
+use speculate::speculate;
+use std::sync::Arc;
+
+// Expected values were produced by src/eth_signing/eth_sign.py with the same inputs.
+#[cfg(test)]
+speculate! {
+        describe "ethSignTest" {
+                it "signPrivate" {
+                        let sig = sign_private(TESTNET_NETWORK_ID, TEST_ADDRESS, "POST", "/v3/api-keys", "{}", "2022-05-10T08:11:03.123Z", TEST_PRIVATE_KEY).unwrap();
+                        assert_eq!(sig, "0xba4af9627c5ecea6338bfd6148724076757ca2a1cdbfe5a80d88fa76ee80b63d364e8a761ee365e7e20b8f8d5d7f4af89423c1bf7093b8b472e3063b28cfa26d1b00");
+                }
+
+                it "signPrivateMainnet" {
+                        let sig = sign_private(NETWORK_ID_MAINNET, TEST_ADDRESS, "DELETE", "/v3/api-keys?apiKey=abc", "{}", "2022-05-10T08:11:03.123Z", TEST_PRIVATE_KEY).unwrap();
+                        assert_eq!(sig, "0xee21686b11922016b781760882d21afc58a831f234d3b84204b38d311396edc27a5de63aba530baa9119a66cdd080f84ce37c9bb1d56671aed85a97703026f011b00");
+                }
+
+                it "signOnboarding" {
+                        let sig = sign_onboarding(TESTNET_NETWORK_ID, TEST_ADDRESS, OFF_CHAIN_ONBOARDING_ACTION, TEST_PRIVATE_KEY).unwrap();
+                        assert_eq!(sig, "0xe624761e69e419af19b43dcc24b7b0544e0d1d38e917c5c16e680957385eb8f7399b3751cfba9f5c96d0f6947e5c294050da72ea7a55300ff1e53195d3d0a22f1c00");
+                        let sig = sign_onboarding(NETWORK_ID_MAINNET, TEST_ADDRESS, OFF_CHAIN_ONBOARDING_ACTION, TEST_PRIVATE_KEY).unwrap();
+                        assert_eq!(sig, "0x1472e6a01fdab7dec20a3c2baa100a8de12b4bd48fb0aca834a419563e0754177122be864887751fbbb362afdd07c0e24c0b737e42a72e42cecbfdfa756637091b00");
+                }
+
+                it "signKeyDerivation" {
+                        let sig = sign_onboarding(TESTNET_NETWORK_ID, TEST_ADDRESS, OFF_CHAIN_KEY_DERIVATION_ACTION, TEST_PRIVATE_KEY).unwrap();
+                        assert_eq!(sig, "0xf2ed05ad45c4643024466b9cc0e351fdfed69071a2c2110f4073acb071e2bc7d41443dd8890fd1bff527d127f68caf7e5d74cab28789815bf5a8e6766a3ebb0d1b00");
+                        let sig = sign_onboarding(NETWORK_ID_MAINNET, TEST_ADDRESS, OFF_CHAIN_KEY_DERIVATION_ACTION, TEST_PRIVATE_KEY).unwrap();
+                        assert_eq!(sig, "0x84bcf27040dbbd4eb8a9d0111e467d46720cdf87c64c01b894391db1e10597967b0e0903a55ae28ca21efb3e57ebb2e45798007e128a82e6580fdfe2986224bd1b00");
+                }
+
+                it "verifyOnboarding" {
+                        let signer = SignOnboardingAction::new(Arc::new(SignWithKey::new(TEST_PRIVATE_KEY).unwrap()), TESTNET_NETWORK_ID);
+                        let sig = tokio_test::block_on(signer.sign(TEST_ADDRESS, OFF_CHAIN_ONBOARDING_ACTION)).unwrap();
+                        assert!(signer.verify(&sig, TEST_ADDRESS, OFF_CHAIN_ONBOARDING_ACTION).unwrap());
+                        assert!(!signer.verify(&sig, TEST_ADDRESS, OFF_CHAIN_KEY_DERIVATION_ACTION).unwrap());
+                }
+
+                it "deriveStarkKeyPair" {
+                        let onboarding = Onboarding::new(TESTNET_API_URL, TESTNET_NETWORK_ID, 10, TEST_PRIVATE_KEY);
+                        let key_pair = tokio_test::block_on(onboarding.derive_stark_key_pair(TEST_ADDRESS)).unwrap();
+                        assert_eq!(key_pair.private_key, "0x5809d980df35d4a9531545b206331d8b6344229622dddecb82e4af2825c0932");
+                        assert_eq!(key_pair.public_key, "0x388feaea46b5365db0476ee1890d85debdddb8427a5a0cda7668d305ca426ab");
+                        assert_eq!(key_pair.public_key_y_coordinate, "0x50d4fd84a48a248c98d3195fb683389655d53ff0b79d500dcf485f486eb7387");
+                        let params = key_pair.create_user_params();
+                        assert_eq!(params.stark_key, key_pair.public_key);
+                }
+
+                it "recoverDefaultApiKeyCredentials" {
+                        let onboarding = Onboarding::new(TESTNET_API_URL, TESTNET_NETWORK_ID, 10, TEST_PRIVATE_KEY);
+                        let credentials = tokio_test::block_on(onboarding.recover_default_api_key_credentials(TEST_ADDRESS)).unwrap();
+                        assert_eq!(credentials.key, "fc378d31-9677-b6e7-aa88-db7729b3c9c1");
+                        assert_eq!(credentials.secret, "WnZWw9smV70l7wCLkLQ_97xr60FEiy6aSyLN5iBB");
+                        assert_eq!(credentials.passphrase, "MjKP4Wb13XLOUFZFduGf");
+                }
+
+                it "rejectsNonHexSignatures" {
+                        // 130 bytes, but "é" straddles the boundary of the recovery byte.
+                        let signature = format!("{}é{}", "a".repeat(127), "b");
+                        assert_eq!(signature.len(), 130);
+                        assert!(fix_raw_signature(&signature).is_err());
+                        assert!(to_typed_signature(&signature).is_err());
+                        assert!(fix_raw_signature(&format!("{}zz", "a".repeat(128))).is_err());
+                        assert_eq!(fix_raw_signature(&format!("{}01", "a".repeat(128))).unwrap(), format!("0x{}1c", "a".repeat(128)));
+                }
+
+                it "rejectsWrongSigner" {
+                        let result = sign_onboarding(TESTNET_NETWORK_ID, "0x0000000000000000000000000000000000000001", OFF_CHAIN_ONBOARDING_ACTION, TEST_PRIVATE_KEY);
+                        assert!(result.is_err());
+                }
+        }
+}
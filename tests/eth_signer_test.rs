@@ -0,0 +1,126 @@
+macro_rules! b {
+        ($e:expr) => {
+                tokio_test::block_on($e)
+        };
+}
+
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::eth::onboarding_action::SignOnboardingAction;
+use dydx_v3_rust::eth::signers::*;
+use dydx_v3_rust::modules::onboarding::Onboarding;
+use serde_json::{json, Value};
+use speculate::speculate;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+// Same as sign_onboarding(TESTNET_NETWORK_ID, TEST_ADDRESS, OFF_CHAIN_ONBOARDING_ACTION, TEST_PRIVATE_KEY).
+const ONBOARDING_SIGNATURE: &str = "0xe624761e69e419af19b43dcc24b7b0544e0d1d38e917c5c16e680957385eb8f7399b3751cfba9f5c96d0f6947e5c294050da72ea7a55300ff1e53195d3d0a22f1c00";
+
+// Answers a single JSON-RPC request with the given response, returning the request.
+async fn serve_json_rpc_once(listener: TcpListener, response: Value) -> Value {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        let body = loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(header_end) = text.find("\r\n\r\n") {
+                        let content_length: usize = text[..header_end]
+                                .lines()
+                                .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap()))
+                                .unwrap();
+                        if request.len() >= header_end + 4 + content_length {
+                                break text[header_end + 4..].to_string();
+                        }
+                }
+        };
+        let response = response.to_string();
+        let http_response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                response.len(),
+                response
+        );
+        stream.write_all(http_response.as_bytes()).await.unwrap();
+        serde_json::from_str(&body).unwrap()
+}
+
+// A raw wallet signature, i.e. the typed signature without its type byte.
+fn raw_signature(typed_signature: &str) -> String {
+        typed_signature[..132].to_string()
+}
+
+#[cfg(test)]
+speculate! {
+        describe "ethSignerTest" {
+                it "jsonRpcSigner" {
+                        b!(async {
+                                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                                let url = format!("http://{}", listener.local_addr().unwrap());
+                                let server = tokio::spawn(serve_json_rpc_once(
+                                        listener,
+                                        json!({"jsonrpc": "2.0", "id": 1, "result": raw_signature(ONBOARDING_SIGNATURE)}),
+                                ));
+
+                                let signer = SignOnboardingAction::new(Arc::new(SignWithJsonRpc::new(&url, None)), TESTNET_NETWORK_ID);
+                                let sig = signer.sign(TEST_ADDRESS, OFF_CHAIN_ONBOARDING_ACTION).await.unwrap();
+                                assert_eq!(sig, ONBOARDING_SIGNATURE);
+
+                                let request = server.await.unwrap();
+                                assert_eq!(request["method"], "eth_signTypedData_v4");
+                                assert_eq!(request["params"][0], TEST_ADDRESS);
+                                let typed_data: Value = serde_json::from_str(request["params"][1].as_str().unwrap()).unwrap();
+                                assert_eq!(typed_data["primaryType"], "dYdX");
+                                assert_eq!(typed_data["domain"]["chainId"], TESTNET_NETWORK_ID);
+                                assert_eq!(typed_data["message"], json!({"action": OFF_CHAIN_ONBOARDING_ACTION}));
+                        });
+                }
+
+                it "jsonRpcSignerError" {
+                        b!(async {
+                                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                                let url = format!("http://{}", listener.local_addr().unwrap());
+                                tokio::spawn(serve_json_rpc_once(
+                                        listener,
+                                        json!({"jsonrpc": "2.0", "id": 1, "error": {"code": 4001, "message": "User rejected the request."}}),
+                                ));
+
+                                let signer = SignOnboardingAction::new(Arc::new(SignWithJsonRpc::new(&url, None)), TESTNET_NETWORK_ID);
+                                let error = signer.sign(TEST_ADDRESS, OFF_CHAIN_ONBOARDING_ACTION).await.unwrap_err();
+                                assert!(error.to_string().contains("User rejected"));
+                        });
+                }
+
+                it "callbackSigner" {
+                        let requests = Arc::new(Mutex::new(Vec::new()));
+                        let seen = requests.clone();
+                        let key = Arc::new(SignWithKey::new(TEST_PRIVATE_KEY).unwrap());
+                        let callback: EthSignCallback = Arc::new(move |request: EthSignRequest| {
+                                let key = key.clone();
+                                seen.lock().unwrap().push(request.clone());
+                                Box::pin(async move {
+                                        let typed_signature = key.sign_hash(&request.message_hash, request.signer_address.as_deref())?;
+                                        Ok(raw_signature(&typed_signature))
+                                })
+                        });
+
+                        let onboarding = Onboarding::with_signer(TESTNET_API_URL, NETWORK_ID_MAINNET, 10, Arc::new(SignWithCallback::new(callback)));
+                        let key_pair = b!(onboarding.derive_stark_key_pair(TEST_ADDRESS)).unwrap();
+                        assert_eq!(key_pair.private_key, b!(Onboarding::new(TESTNET_API_URL, NETWORK_ID_MAINNET, 10, TEST_PRIVATE_KEY).derive_stark_key(TEST_ADDRESS)).unwrap());
+
+                        let requests = requests.lock().unwrap();
+                        assert_eq!(requests.len(), 1);
+                        assert_eq!(requests[0].signer_address.as_deref(), Some(TEST_ADDRESS));
+                        assert_eq!(requests[0].eip712_message["message"]["action"], OFF_CHAIN_KEY_DERIVATION_ACTION);
+                        assert_eq!(requests[0].eip712_message["message"]["onlySignOn"], "https://trade.dydx.exchange");
+                }
+
+                it "keySignerDebugHidesPrivateKey" {
+                        let signer = SignWithKey::new(TEST_PRIVATE_KEY).unwrap();
+                        let debug = format!("{:?}", signer);
+                        assert!(!debug.contains(TEST_PRIVATE_KEY));
+                        assert!(debug.contains(TEST_ADDRESS));
+                }
+        }
+}
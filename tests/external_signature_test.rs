@@ -0,0 +1,153 @@
+macro_rules! b {
+        ($e:expr) => {
+                tokio_test::block_on($e)
+        };
+}
+
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::eth::eth_private_action::SignEthPrivateAction;
+use dydx_v3_rust::eth::signers::SignWithKey;
+use dydx_v3_rust::modules::eth_private::EthPrivate;
+use dydx_v3_rust::modules::onboarding::Onboarding;
+use serde_json::json;
+use speculate::speculate;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+// Typed signatures from sign_onboarding(TESTNET_NETWORK_ID, TEST_ADDRESS, <action>, TEST_PRIVATE_KEY).
+const ONBOARDING_SIGNATURE: &str = "0xe624761e69e419af19b43dcc24b7b0544e0d1d38e917c5c16e680957385eb8f7399b3751cfba9f5c96d0f6947e5c294050da72ea7a55300ff1e53195d3d0a22f1c00";
+const STARK_KEY_SIGNATURE: &str = "0xf2ed05ad45c4643024466b9cc0e351fdfed69071a2c2110f4073acb071e2bc7d41443dd8890fd1bff527d127f68caf7e5d74cab28789815bf5a8e6766a3ebb0d1b00";
+
+// A raw wallet signature, i.e. the typed signature without its type byte.
+fn raw_signature(typed_signature: &str) -> String {
+        typed_signature[..132].to_string()
+}
+
+// Answers a single HTTP request with the given JSON body, returning the request head.
+async fn serve_http_once(listener: TcpListener, body: String) -> String {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        while !String::from_utf8_lossy(&request).contains("\r\n\r\n") {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+        }
+        let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&request).to_lowercase()
+}
+
+#[cfg(test)]
+speculate! {
+        describe "externalSignatureTest" {
+                it "onboardingTypedData" {
+                        let onboarding = Onboarding::without_signer(TESTNET_API_URL, TESTNET_NETWORK_ID, 10);
+                        assert_eq!(onboarding.get_onboarding_typed_data(), json!({
+                                "types": {
+                                        "EIP712Domain": [
+                                                {"name": "name", "type": "string"},
+                                                {"name": "version", "type": "string"},
+                                                {"name": "chainId", "type": "uint256"},
+                                        ],
+                                        "dYdX": [
+                                                {"type": "string", "name": "action"},
+                                        ],
+                                },
+                                "domain": {"name": "dYdX", "version": "1.0", "chainId": TESTNET_NETWORK_ID},
+                                "primaryType": "dYdX",
+                                "message": {"action": "dYdX Onboarding"},
+                        }));
+
+                        let mainnet = Onboarding::without_signer(MAINNET_API_URL, NETWORK_ID_MAINNET, 10);
+                        let typed_data = mainnet.get_stark_key_typed_data();
+                        assert_eq!(typed_data["message"], json!({"action": "dYdX STARK Key", "onlySignOn": "https://trade.dydx.exchange"}));
+                        assert_eq!(typed_data["types"]["dYdX"][1]["name"], "onlySignOn");
+                }
+
+                it "recoverCredentialsFromSignature" {
+                        let onboarding = Onboarding::without_signer(TESTNET_API_URL, TESTNET_NETWORK_ID, 10);
+                        let credentials = onboarding
+                                .recover_default_api_key_credentials_from_signature(TEST_ADDRESS, &raw_signature(ONBOARDING_SIGNATURE))
+                                .unwrap();
+                        assert_eq!(credentials.key, "fc378d31-9677-b6e7-aa88-db7729b3c9c1");
+                        assert_eq!(credentials.secret, "WnZWw9smV70l7wCLkLQ_97xr60FEiy6aSyLN5iBB");
+                        assert_eq!(credentials.passphrase, "MjKP4Wb13XLOUFZFduGf");
+                }
+
+                it "deriveStarkKeyFromSignature" {
+                        let onboarding = Onboarding::without_signer(TESTNET_API_URL, TESTNET_NETWORK_ID, 10);
+                        let key_pair = onboarding
+                                .derive_stark_key_pair_from_signature(TEST_ADDRESS, &raw_signature(STARK_KEY_SIGNATURE))
+                                .unwrap();
+                        assert_eq!(key_pair.private_key, "0x5809d980df35d4a9531545b206331d8b6344229622dddecb82e4af2825c0932");
+                        assert_eq!(key_pair.public_key, "0x388feaea46b5365db0476ee1890d85debdddb8427a5a0cda7668d305ca426ab");
+
+                        // The typed signature is accepted as well.
+                        let private_key = onboarding.derive_stark_key_from_signature(TEST_ADDRESS, STARK_KEY_SIGNATURE).unwrap();
+                        assert_eq!(private_key, key_pair.private_key);
+                }
+
+                it "rejectsSignatureOfOtherAction" {
+                        let onboarding = Onboarding::without_signer(TESTNET_API_URL, TESTNET_NETWORK_ID, 10);
+                        assert!(onboarding.derive_stark_key_from_signature(TEST_ADDRESS, ONBOARDING_SIGNATURE).is_err());
+                        assert!(onboarding
+                                .recover_default_api_key_credentials_from_signature("0x0000000000000000000000000000000000000001", ONBOARDING_SIGNATURE)
+                                .is_err());
+                }
+
+                it "signingWithoutSignerFails" {
+                        let onboarding = Onboarding::without_signer(TESTNET_API_URL, TESTNET_NETWORK_ID, 10);
+                        let error = b!(onboarding.derive_stark_key(TEST_ADDRESS)).unwrap_err();
+                        assert_eq!(error.to_string(), "No Ethereum signer configured");
+                }
+
+                it "createApiKeyWithSignature" {
+                        b!(async {
+                                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                                let host = format!("http://{}", listener.local_addr().unwrap());
+                                let server = tokio::spawn(serve_http_once(
+                                        listener,
+                                        r#"{"apiKey":{"key":"k","secret":"s","passphrase":"p"}}"#.to_string(),
+                                ));
+
+                                let eth_private = EthPrivate::without_signer(&host, TESTNET_NETWORK_ID, 10);
+                                let signing_request = eth_private.get_create_api_key_signing_request();
+                                assert_eq!(signing_request.typed_data["message"]["requestPath"], "/v3/api-keys");
+                                assert_eq!(signing_request.typed_data["message"]["method"], "POST");
+                                assert_eq!(signing_request.typed_data["message"]["timestamp"], signing_request.timestamp.as_str());
+
+                                // Stands in for a browser wallet signing signing_request.typed_data.
+                                let message_hash = SignEthPrivateAction::without_signer(TESTNET_NETWORK_ID).get_hash(
+                                        &signing_request.method,
+                                        &signing_request.request_path,
+                                        &signing_request.body,
+                                        &signing_request.timestamp,
+                                );
+                                let signature = SignWithKey::new(TEST_PRIVATE_KEY).unwrap().sign_hash(&message_hash, None).unwrap();
+
+                                let response = eth_private
+                                        .create_api_key_with_signature(TEST_ADDRESS, &signing_request, &raw_signature(&signature))
+                                        .await
+                                        .unwrap();
+                                assert_eq!(response.api_key.key, "k");
+
+                                let request = server.await.unwrap();
+                                assert!(request.starts_with("post /v3/api-keys "));
+                                assert!(request.contains(&format!("dydx-signature: {}", signature)));
+                                assert!(request.contains(&format!("dydx-timestamp: {}", signing_request.timestamp.to_lowercase())));
+                        });
+                }
+
+                it "deleteApiKeyRejectsWrongSigner" {
+                        let eth_private = EthPrivate::without_signer(TESTNET_API_URL, TESTNET_NETWORK_ID, 10);
+                        let signing_request = eth_private.get_delete_api_key_signing_request("abc");
+                        assert_eq!(signing_request.request_path, "/v3/api-keys?apiKey=abc");
+                        let result = b!(eth_private.delete_api_key_with_signature("abc", TEST_ADDRESS, &signing_request, ONBOARDING_SIGNATURE));
+                        assert!(result.is_err());
+                }
+        }
+}
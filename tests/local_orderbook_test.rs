@@ -0,0 +1,157 @@
+macro_rules! b {
+        ($e:expr) => {
+                tokio_test::block_on($e)
+        };
+}
+
+mod common;
+
+use bigdecimal::BigDecimal;
+use dydx_v3_rust::modules::public::Public;
+use dydx_v3_rust::types::{OrderbookResponse, OrderbookResponseOrder};
+use dydx_v3_rust::ws::messages::{OrderbookLevel, OrderbookUpdate, WsEvent};
+use dydx_v3_rust::ws::orderbook::*;
+use dydx_v3_rust::DydxClient;
+use speculate::speculate;
+use std::str::FromStr;
+
+fn level(price: &str, size: &str, offset: u64) -> OrderbookLevel {
+        OrderbookLevel {
+                order: OrderbookResponseOrder { size: size.into(), price: price.into() },
+                offset: Some(offset),
+        }
+}
+
+fn snapshot() -> OrderbookUpdate {
+        OrderbookUpdate::Snapshot {
+                asks: vec![level("101", "1", 10), level("102", "2", 10), level("103", "3", 10)],
+                bids: vec![level("100", "1", 10), level("99", "2", 10), level("98", "3", 10)],
+        }
+}
+
+fn delta(asks: Vec<OrderbookLevel>, bids: Vec<OrderbookLevel>) -> OrderbookUpdate {
+        OrderbookUpdate::Delta { offset: None, asks, bids }
+}
+
+fn decimal(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+}
+
+fn prices(levels: Vec<&PriceLevel>) -> Vec<String> {
+        levels.iter().map(|level| level.price.to_string()).collect()
+}
+
+// Serves GET /v3/orderbook/BTC-USD, one request per connection.
+async fn mock_api() -> String {
+        common::serve_api(|request| {
+                assert!(request.head.starts_with("GET /v3/orderbook/BTC-USD "));
+                async { r#"{"asks":[{"size":"5","price":"105"}],"bids":[{"size":"4","price":"95"}]}"#.to_string() }
+        })
+        .await
+}
+
+#[cfg(test)]
+speculate! {
+        describe "localOrderbookTest" {
+                it "appliesSnapshotAndDeltas" {
+                        let mut book = LocalOrderBook::new("BTC-USD");
+                        assert_eq!(book.apply(&delta(vec![level("101", "1", 1)], vec![])), BookStatus::Unchanged);
+                        assert!(!book.is_synced());
+
+                        assert_eq!(book.apply(&snapshot()), BookStatus::Updated);
+                        assert!(book.is_synced());
+                        assert_eq!(book.best_bid().unwrap().price, decimal("100"));
+                        assert_eq!(book.best_ask().unwrap().price, decimal("101"));
+
+                        assert_eq!(book.apply(&delta(vec![level("101", "0", 11), level("101.5", "4", 11)], vec![level("99", "7", 11)])), BookStatus::Updated);
+                        assert_eq!(prices(book.top_asks(2)), vec!["101.5", "102"]);
+                        assert_eq!(prices(book.top_bids(5)), vec!["100", "99", "98"]);
+                        assert_eq!(book.top_bids(2)[1].size, decimal("7"));
+                        assert_eq!(book.top_bids(2)[1].offset, Some(11));
+                        assert_eq!(book.to_response().asks[0], OrderbookResponseOrder { size: "4".into(), price: "101.5".into() });
+
+                        let other_market = WsEvent::Orderbook { market: "ETH-USD".into(), update: delta(vec![], vec![level("100", "0", 12)]) };
+                        assert_eq!(book.apply_event(&other_market), None);
+                        assert_eq!(book.best_bid().unwrap().price, decimal("100"));
+                }
+
+                it "ignoresStaleUpdates" {
+                        let mut book = LocalOrderBook::new("BTC-USD");
+                        book.apply(&snapshot());
+                        assert_eq!(book.apply(&delta(vec![level("102", "9", 9)], vec![])), BookStatus::Unchanged);
+                        assert_eq!(book.top_asks(2)[1].size, decimal("2"));
+
+                        assert_eq!(book.apply(&delta(vec![level("103", "0", 12)], vec![])), BookStatus::Updated);
+                        assert_eq!(book.apply(&delta(vec![level("103", "3", 11)], vec![])), BookStatus::Unchanged);
+                        assert_eq!(prices(book.top_asks(5)), vec!["101", "102"]);
+                        assert_eq!(book.apply(&delta(vec![level("103", "1", 13)], vec![])), BookStatus::Updated);
+                        assert_eq!(prices(book.top_asks(5)), vec!["101", "102", "103"]);
+                }
+
+                it "forgetsOldRemovedLevelsSafely" {
+                        let mut book = LocalOrderBook::new("BTC-USD");
+                        book.apply(&snapshot());
+                        let removals = (0..=MAX_REMOVED_LEVELS as u64).map(|i| level(&(1000 + i).to_string(), "0", 100 + i)).collect();
+                        book.apply(&delta(removals, vec![]));
+
+                        // The removal of 1000 at offset 100 is forgotten, but older updates stay stale.
+                        assert_eq!(book.apply(&delta(vec![level("1000", "1", 99)], vec![])), BookStatus::Unchanged);
+                        assert_eq!(prices(book.top_asks(5)), vec!["101", "102", "103"]);
+                        let newer = 101 + MAX_REMOVED_LEVELS as u64;
+                        assert_eq!(book.apply(&delta(vec![level("1000", "1", newer)], vec![])), BookStatus::Updated);
+                        assert_eq!(prices(book.top_asks(5)), vec!["101", "102", "103", "1000"]);
+                }
+
+                it "resyncsCrossedBook" {
+                        b!(async {
+                                let host = mock_api().await;
+                                let public = Public::new(&host, 5, None, DydxClient::get_no_backoff_getter());
+                                let mut book = LocalOrderBook::new("BTC-USD");
+                                book.apply(&snapshot());
+
+                                assert_eq!(book.apply(&delta(vec![], vec![level("101.5", "1", 11)])), BookStatus::NeedsResync);
+                                assert!(!book.is_synced());
+                                assert_eq!(book.apply(&delta(vec![level("101", "0", 12)], vec![])), BookStatus::Unchanged);
+                                assert_eq!(book.apply(&OrderbookUpdate::Snapshot { asks: vec![level("101", "x", 13)], bids: vec![] }), BookStatus::NeedsResync);
+
+                                let crossed = delta(vec![level("99", "1", 14)], vec![]);
+                                book.apply(&snapshot());
+                                assert_eq!(book.apply_or_resync(&crossed, &public).await.unwrap(), BookStatus::Updated);
+                                assert!(book.is_synced());
+                                assert!(!book.is_crossed());
+                                assert_eq!(book.to_response(), OrderbookResponse {
+                                        asks: vec![OrderbookResponseOrder { size: "5".into(), price: "105".into() }],
+                                        bids: vec![OrderbookResponseOrder { size: "4".into(), price: "95".into() }],
+                                });
+                                // REST levels have no offset, so any delta applies.
+                                assert_eq!(book.apply(&delta(vec![level("105", "6", 1)], vec![])), BookStatus::Updated);
+                        });
+                }
+
+                it "notifiesWatchers" {
+                        let mut book = LocalOrderBook::new("BTC-USD");
+                        let mut top = book.watch();
+                        assert!(!top.has_changed().unwrap());
+
+                        book.seed(&OrderbookResponse {
+                                asks: vec![OrderbookResponseOrder { size: "1".into(), price: "101".into() }],
+                                bids: vec![OrderbookResponseOrder { size: "1".into(), price: "100".into() }],
+                        });
+                        assert!(top.has_changed().unwrap());
+                        let seen = top.borrow_and_update().clone();
+                        assert!(seen.synced);
+                        assert_eq!(seen.best_ask.unwrap().price, decimal("101"));
+
+                        book.apply(&delta(vec![level("102", "0", 1)], vec![]));
+                        assert!(!top.has_changed().unwrap());
+
+                        book.apply(&delta(vec![], vec![level("100.5", "2", 2)]));
+                        let seen = top.borrow_and_update().clone();
+                        assert_eq!(seen.best_bid.unwrap().price, decimal("100.5"));
+                        assert_eq!(seen.version, 2);
+
+                        book.apply(&delta(vec![], vec![level("101", "1", 3)]));
+                        assert!(!top.borrow_and_update().synced);
+                }
+        }
+}
@@ -0,0 +1,150 @@
+macro_rules! b {
+        ($e:expr) => {
+                tokio_test::block_on($e)
+        };
+}
+
+mod common;
+
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::modules::public::Public;
+use dydx_v3_rust::starkex::market_registry::*;
+use dydx_v3_rust::starkex::order::SignableOrder;
+use dydx_v3_rust::starkex::signable::Signable;
+use dydx_v3_rust::types::*;
+use dydx_v3_rust::{ClientOptions, DydxClient, StarkError};
+use speculate::speculate;
+use std::sync::Arc;
+
+fn market_json(market: &str, base_asset: &str, synthetic_asset_id: &str, asset_resolution: &str) -> String {
+        format!(
+                r#""{market}":{{"market":"{market}","status":"ONLINE","baseAsset":"{base_asset}","quoteAsset":"USD","stepSize":"0.001","tickSize":"1","indexPrice":"1","oraclePrice":"1","priceChange24H":"0","nextFundingRate":"0","nextFundingAt":"2023-01-01T00:00:00.000Z","minOrderSize":"0.001","type":"PERPETUAL","initialMarginFraction":"0.05","maintenanceMarginFraction":"0.03","volume24H":"0","trades24H":"0","openInterest":"0","incrementalInitialMarginFraction":"0.01","incrementalPositionSize":"1","maxPositionSize":"100","baselinePositionSize":"10","assetResolution":"{asset_resolution}","syntheticAssetId":"{synthetic_asset_id}"}}"#
+        )
+}
+
+fn markets_body() -> String {
+        format!(
+                r#"{{"markets":{{{},{}}}}}"#,
+                market_json("BTC-USD", "BTC", "0x4254432d3130000000000000000000", "10000000000"),
+                market_json("NEW-USD", "NEW", "0x4e45572d3800000000000000000000", "100000000")
+        )
+}
+
+fn config_body() -> String {
+        format!(
+                r#"{{"collateralAssetId":"{}","collateralTokenAddress":"0x0","defaultMakerFee":"0.0005","defaultTakerFee":"0.001","exchangeAddress":"0x0","maxExpectedBatchLengthMinutes":"720","maxFastWithdrawalAmount":"200000","cancelOrderRateLimiting":{{"maxPointsMulti":3,"maxPointsSingle":9500,"windowSecMulti":10,"windowSecSingle":10}},"placeOrderRateLimiting":{{"maxPoints":1750,"windowSec":10,"targetNotional":40000,"minLimitConsumption":4,"minMarketConsumption":20,"minTriggerableConsumption":100,"maxOrderConsumption":100}}}}"#,
+                COLLATERAL_ASSET_ID_MAINNET
+        )
+}
+
+// Serves /v3/markets and /v3/config, one request per connection.
+async fn mock_api() -> String {
+        common::serve_api(|request| {
+                let body = if request.head.starts_with("GET /v3/markets") { markets_body() } else { config_body() };
+                async { body }
+        })
+        .await
+}
+
+fn public(host: &str) -> Public<'_> {
+        Public::new(host, 5, None, DydxClient::get_no_backoff_getter())
+}
+
+fn cache_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("dydx-markets-{}-{}.json", name, std::process::id()))
+}
+
+#[cfg(test)]
+speculate! {
+        describe "marketRegistryTest" {
+                it "fetchedRegistrySignsLikeConstants" {
+                        b!(async {
+                                let host = mock_api().await;
+                                let registry = MarketRegistry::fetch(&public(&host), NETWORK_ID_MAINNET).await.unwrap();
+                                let order = SignableOrder::with_registry(&registry, "BTC-USD", "SELL", "228", "0.01", "100000.123456789", "0.0015", "123", 1700000000).unwrap();
+                                let sig = order.sign(TEST_STARK_PRIVATE_KEY).unwrap();
+                                assert_eq!(sig, "0714e9e3ca6541f22e4160dc8fe007375b94d519984181d92379cf8b550875e20238b4a330f0cc89d042e13b52b9d33c55cdc516406cfbea04adb64746244ead");
+                        });
+                }
+
+                it "signsMarketMissingFromConstants" {
+                        b!(async {
+                                let host = mock_api().await;
+                                let registry = MarketRegistry::fetch(&public(&host), NETWORK_ID_MAINNET).await.unwrap();
+                                assert!(SignableOrder::new(NETWORK_ID_MAINNET, "NEW-USD", "BUY", "228", "1.5", "20", "0.001", "123", 1700000000).is_err());
+                                let order = SignableOrder::with_registry(&registry, "NEW-USD", "BUY", "228", "1.5", "20", "0.001", "123", 1700000000).unwrap();
+                                assert_eq!(order.message().quantums_amount_synthetic, 150000000);
+                                assert_eq!(registry.markets(), vec!["BTC-USD", "NEW-USD"]);
+                        });
+                }
+
+                it "unknownMarket" {
+                        let registry = MarketRegistry::from_constants(NETWORK_ID_MAINNET).unwrap();
+                        assert!(registry.market("NEW-USD").is_err());
+                        assert!(MarketRegistry::from_constants(42).is_err());
+                }
+
+                it "loadsFromCache" {
+                        let path = cache_path("load");
+                        let _ = std::fs::remove_file(&path);
+                        b!(async {
+                                let host = mock_api().await;
+                                let fetched = MarketRegistry::load_or_fetch(&public(&host), NETWORK_ID_MAINNET, &path).await.unwrap();
+                                assert!(path.exists());
+
+                                // Nothing listens on port 9, so this must come from the cache file.
+                                let cached = MarketRegistry::load_or_fetch(&public("http://127.0.0.1:9"), NETWORK_ID_MAINNET, &path).await.unwrap();
+                                assert_eq!(cached.snapshot(), fetched.snapshot());
+                        });
+                        let _ = std::fs::remove_file(&path);
+                }
+
+                it "refetchesCacheOfOtherNetwork" {
+                        let path = cache_path("network");
+                        let _ = std::fs::remove_file(&path);
+                        b!(async {
+                                let host = mock_api().await;
+                                MarketRegistry::from_constants(NETWORK_ID_GOERLI).unwrap().save(&path).unwrap();
+
+                                let registry = MarketRegistry::load_or_fetch(&public(&host), NETWORK_ID_MAINNET, &path).await.unwrap();
+                                assert_eq!(registry.network_id(), NETWORK_ID_MAINNET);
+                                assert!(registry.market("NEW-USD").is_ok());
+                                assert_eq!(MarketRegistry::load(&path).unwrap().network_id(), NETWORK_ID_MAINNET);
+                        });
+                        let _ = std::fs::remove_file(&path);
+                }
+
+                it "rejectsRegistryOfOtherNetwork" {
+                        b!(async {
+                                let client = DydxClient::new(TESTNET_API_URL, ClientOptions {
+                                        network_id: Some(NETWORK_ID_GOERLI),
+                                        api_key_credentials: Some(ApiKeyCredentials { key: "key", secret: "c2VjcmV0", passphrase: "passphrase" }),
+                                        stark_private_key: Some(TEST_STARK_PRIVATE_KEY),
+                                        market_registry: Some(Arc::new(MarketRegistry::from_constants(NETWORK_ID_MAINNET).unwrap())),
+                                        ..ClientOptions::default()
+                                });
+                                let params = ApiWithdrawParams { position_id: "228", amount: "10.5", asset: "USDC", client_id: None, expiration: 1700000000 };
+                                let error = client.private.unwrap().prepare_create_withdraw(params).await.unwrap_err();
+                                assert_eq!(
+                                        error.downcast_ref::<StarkError>(),
+                                        Some(&StarkError::NetworkMismatch { expected: NETWORK_ID_GOERLI, actual: NETWORK_ID_MAINNET })
+                                );
+                        });
+                }
+
+                it "refreshReplacesSnapshotAndCache" {
+                        let path = cache_path("refresh");
+                        let _ = std::fs::remove_file(&path);
+                        b!(async {
+                                let host = mock_api().await;
+                                let registry = MarketRegistry::from_constants(NETWORK_ID_MAINNET).unwrap().with_cache_path(&path);
+                                assert!(registry.market("NEW-USD").is_err());
+
+                                registry.refresh(&public(&host)).await.unwrap();
+                                assert_eq!(registry.market("NEW-USD").unwrap().asset_resolution, "100000000");
+                                assert_eq!(MarketRegistry::load(&path).unwrap().snapshot(), registry.snapshot());
+                        });
+                        let _ = std::fs::remove_file(&path);
+                }
+        }
+}
@@ -20,6 +20,14 @@ speculate! {
                                 api_key_credentials: None,
                                 stark_private_key: None,
                                 eth_private_key: Some(TEST_PRIVATE_KEY),
+                                eth_signer: None,
+                                stark_signer: None,
+                                market_registry: None,
+                                signing_pool: None,
+                                public_error_handler: None,
+                                private_error_handler: None,
+                                public_backoff_getter: None,
+                                private_backoff_getter: None,
                         };
                         DydxClient::new(TESTNET_API_URL, options)
 
@@ -42,7 +50,7 @@ speculate! {
                 }
                 it "deriveStarkKey" {
                         b!(async {
-                                let _response = DydxClient().onboarding.unwrap().derive_stark_key(TEST_ADDRESS);
+                                let _response = DydxClient().onboarding.unwrap().derive_stark_key(TEST_ADDRESS).await;
                                 // dbg!(&_response);
 
                         });
@@ -50,7 +58,7 @@ speculate! {
                 it "recoverDefaultApiCredentials" {
                         b!(async {
                                 let onboarding = DydxClient().onboarding.unwrap();
-                                let _response = onboarding.recover_default_api_key_credentials(TEST_ADDRESS);
+                                let _response = onboarding.recover_default_api_key_credentials(TEST_ADDRESS).await;
                                 // dbg!(&_response);
 
                         });
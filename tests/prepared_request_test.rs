@@ -0,0 +1,140 @@
+macro_rules! b {
+        ($e:expr) => {
+                tokio_test::block_on($e)
+        };
+}
+
+mod common;
+
+use common::API_SECRET;
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::modules::private::PreparedRequest;
+use dydx_v3_rust::modules::stark_sign;
+use dydx_v3_rust::types::*;
+use dydx_v3_rust::{ClientOptions, DydxClient};
+use hmac::{Hmac, Mac};
+use http::Method;
+use sha2::Sha256;
+use speculate::speculate;
+
+fn options() -> ClientOptions<'static> {
+        ClientOptions {
+                stark_private_key: Some(TEST_STARK_PRIVATE_KEY),
+                ..common::options()
+        }
+}
+
+fn order_params() -> ApiOrderParams<'static> {
+        ApiOrderParams {
+                position_id: "228",
+                market: "BTC-USD",
+                side: "SELL",
+                type_field: "LIMIT",
+                size: "0.01",
+                price: "100000.123456789",
+                time_in_force: "GTT",
+                post_only: false,
+                limit_fee: "0.0015",
+                client_id: Some("123"),
+                cancel_id: None,
+                trigger_price: None,
+                trailing_percent: None,
+                reduce_only: None,
+                expiration: 1700000000,
+        }
+}
+
+fn expected_api_signature(prepared: &PreparedRequest) -> String {
+        let message = format!(
+                "{}{}{}{}",
+                prepared.header("DYDX-TIMESTAMP").unwrap(),
+                prepared.method,
+                prepared.request_path,
+                prepared.body.as_deref().unwrap_or("")
+        );
+        let mut mac = Hmac::<Sha256>::new_from_slice(&base64::decode_config(API_SECRET, base64::URL_SAFE).unwrap()).unwrap();
+        mac.update(message.as_bytes());
+        base64::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+speculate! {
+        describe "preparedRequestTest" {
+                it "prepareCreateOrder" {
+                        b!(async {
+                                let client = DydxClient::new(TESTNET_API_URL, options());
+                                let prepared = client.private.unwrap().prepare_create_order(order_params()).await.unwrap();
+                                assert_eq!(prepared.method, Method::POST);
+                                assert_eq!(prepared.url, format!("{}/v3/orders", TESTNET_API_URL));
+                                assert_eq!(prepared.request_path, "/v3/orders");
+
+                                let stark_signature = stark_sign::sign_order(TESTNET_NETWORK_ID, "BTC-USD", "SELL", "228", "0.01", "100000.123456789", "0.0015", "123", 1700000000, TEST_STARK_PRIVATE_KEY).unwrap();
+                                assert_eq!(prepared.stark_signature.as_deref(), Some(stark_signature.as_str()));
+                                let body: serde_json::Value = serde_json::from_str(prepared.body.as_deref().unwrap()).unwrap();
+                                assert_eq!(body["signature"], stark_signature.as_str());
+                                assert_eq!(body["clientId"], "123");
+                                assert_eq!(body["expiration"], "2023-11-14T22:13:20.000Z");
+
+                                assert_eq!(prepared.header("DYDX-API-KEY"), Some("key"));
+                                assert_eq!(prepared.header("dydx-passphrase"), Some("passphrase"));
+                                assert_eq!(prepared.header("DYDX-SIGNATURE").unwrap(), expected_api_signature(&prepared));
+                        });
+                }
+
+                it "prepareCreateWithdraw" {
+                        b!(async {
+                                let client = DydxClient::new(TESTNET_API_URL, options());
+                                let params = ApiWithdrawParams { position_id: "228", amount: "10.5", asset: "USDC", client_id: None, expiration: 1700000000 };
+                                let prepared = client.private.unwrap().prepare_create_withdraw(params).await.unwrap();
+                                assert_eq!(prepared.request_path, "/v3/withdrawals");
+                                let body: serde_json::Value = serde_json::from_str(prepared.body.as_deref().unwrap()).unwrap();
+                                assert_eq!(body["signature"], prepared.stark_signature.clone().unwrap());
+                                assert_eq!(prepared.header("DYDX-SIGNATURE").unwrap(), expected_api_signature(&prepared));
+                        });
+                }
+
+                it "prepareCancels" {
+                        let client = DydxClient::new(TESTNET_API_URL, options());
+                        let private = client.private.unwrap();
+
+                        let prepared = private.prepare_cancel_order("order-id");
+                        assert_eq!(prepared.method, Method::DELETE);
+                        assert_eq!(prepared.request_path, "/v3/orders/order-id");
+                        assert_eq!(prepared.body, None);
+                        assert_eq!(prepared.stark_signature, None);
+
+                        let prepared = private.prepare_cancel_all_orders(Some("BTC-USD"));
+                        assert_eq!(prepared.request_path, "/v3/orders?market=BTC-USD");
+                        assert_eq!(prepared.url, format!("{}/v3/orders?market=BTC-USD", TESTNET_API_URL));
+                        assert_eq!(prepared.header("DYDX-SIGNATURE").unwrap(), expected_api_signature(&prepared));
+                }
+
+                it "sendsPreparedRequest" {
+                        b!(async {
+                                let (host, received) = common::serve_once(r#"{"cancelOrders":[]}"#).await;
+                                let client = DydxClient::new(&host, options());
+                                let private = client.private.unwrap();
+                                let prepared = private.prepare_cancel_all_orders(Some("BTC-USD"));
+                                let response: CancelOrdersResponse = private.send(&prepared).await.unwrap();
+                                assert!(response.cancel_orders.is_empty());
+
+                                let head = received.await.unwrap().head;
+                                assert!(head.starts_with("DELETE /v3/orders?market=BTC-USD HTTP/1.1"));
+                                let signature_header = format!("dydx-signature: {}\r\n", prepared.header("DYDX-SIGNATURE").unwrap());
+                                assert!(head.contains(&signature_header));
+                        });
+                }
+
+                it "sendsAnyMethod" {
+                        b!(async {
+                                let (host, received) = common::serve_once(r#"{"cancelOrders":[]}"#).await;
+                                let client = DydxClient::new(&host, options());
+                                let private = client.private.unwrap();
+                                let mut prepared = private.prepare_cancel_all_orders(Some("BTC-USD"));
+                                prepared.method = Method::PATCH;
+                                let _: CancelOrdersResponse = private.send(&prepared).await.unwrap();
+                                assert!(received.await.unwrap().head.starts_with("PATCH /v3/orders?market=BTC-USD HTTP/1.1"));
+                        });
+                }
+        }
+}
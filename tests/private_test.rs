@@ -27,7 +27,15 @@ speculate! {
                                 api_timeout: None,
                                 api_key_credentials: Some(api_key),
                                 stark_private_key: Some(TEST_STARK_PRIVATE_KEY),
-                                eth_private_key: None
+                                eth_private_key: None,
+                                eth_signer: None,
+                                stark_signer: None,
+                                market_registry: None,
+                                signing_pool: None,
+                                public_error_handler: None,
+                                private_error_handler: None,
+                                public_backoff_getter: None,
+                                private_backoff_getter: None,
                         };
                         DydxClient::new(TESTNET_API_URL, options)
                     }
@@ -110,7 +118,15 @@ speculate! {
                                                 api_timeout: None,
                                                 api_key_credentials: Some(api_key),
                                                 stark_private_key: Some("0657eaa201ba872f72c0e6e2db278d8cda1b60de4313f02213aaf2b3421bff56"),
-                                                eth_private_key: None
+                                                eth_private_key: None,
+                                                eth_signer: None,
+                                                stark_signer: None,
+                                                market_registry: None,
+                                                signing_pool: None,
+                                                public_error_handler: None,
+                                                private_error_handler: None,
+                                                public_backoff_getter: None,
+                                                private_backoff_getter: None,
                                         };
                                         // DydxClient::new("https://api.dydx.exchange", Some(options))
                                         DydxClient::new("https://api.stage.dydx.exchange", options)
@@ -170,6 +186,7 @@ speculate! {
                                         position_id: POSITION_ID,
                                         amount: "3",
                                         asset: "USDC",
+                                        client_id: None,
                                         expiration: expiration_unix,
                                 };
                                 let _response = DydxClient().private.unwrap().create_withdraw(withdraw_params).await.unwrap();
@@ -191,6 +208,7 @@ speculate! {
                                         to_address: TEST_ADDRESS,
                                         lp_position_id: "2",
                                         lp_stark_key: "04a9ecd28a67407c3cff8937f329ca24fd631b1d9ca2b9f2df47c7ebf72bf0b0",
+                                        client_id: None,
                                         expiration: expiration_unix,
                                 };
                                 let _response = DydxClient().private.unwrap().create_fast_withdraw(withdraw_params).await;
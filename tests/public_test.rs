@@ -20,7 +20,15 @@ speculate! {
                                 api_timeout: None,
                                 api_key_credentials: None,
                                 stark_private_key: None,
-                                eth_private_key: None
+                                eth_private_key: None,
+                                eth_signer: None,
+                                stark_signer: None,
+                                market_registry: None,
+                                signing_pool: None,
+                                public_error_handler: None,
+                                private_error_handler: None,
+                                public_backoff_getter: None,
+                                private_backoff_getter: None,
                         };
                         DydxClient::new(MAINNET_API_URL, options)
                     }
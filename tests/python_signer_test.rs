@@ -0,0 +1,46 @@
+#![cfg(feature = "python-signer")]
+
+use dydx_v3_rust::modules::python_signer::install_importer;
+use pyo3::prelude::*;
+use speculate::speculate;
+
+#[cfg(test)]
+speculate! {
+        describe "pythonSignerTest" {
+                it "importsEmbeddedPackages" {
+                        Python::with_gil(|py| {
+                                install_importer(py).unwrap();
+                                let constants = py.import("starkex.constants").unwrap();
+                                let prefix: u64 = constants.getattr("ORDER_PREFIX").unwrap().extract().unwrap();
+                                assert_eq!(prefix, 3);
+                                let file: String = constants.getattr("__file__").unwrap().extract().unwrap();
+                                assert_eq!(file, "<dydx-v3-rust>/stark/starkex/constants.py");
+                        });
+                }
+
+                it "readsEmbeddedPedersenParams" {
+                        Python::with_gil(|py| {
+                                install_importer(py).unwrap();
+                                let field_prime: String = py
+                                        .eval("hex(__import__('json').loads(__import__('pkgutil').get_data('starkex.starkex_resources', 'pedersen_params.json'))['FIELD_PRIME'])", None, None)
+                                        .unwrap()
+                                        .extract()
+                                        .unwrap();
+                                assert_eq!(field_prime, "0x800000000000011000000000000000000000000000000000000000000000001");
+                        });
+                }
+
+                it "installsOnce" {
+                        Python::with_gil(|py| {
+                                install_importer(py).unwrap();
+                                install_importer(py).unwrap();
+                                let count: usize = py
+                                        .eval("sum(getattr(f, 'is_dydx_v3_rust_importer', False) for f in __import__('sys').meta_path)", None, None)
+                                        .unwrap()
+                                        .extract()
+                                        .unwrap();
+                                assert_eq!(count, 1);
+                        });
+                }
+        }
+}
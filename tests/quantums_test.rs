@@ -0,0 +1,79 @@
+use bigdecimal::BigDecimal;
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::modules::stark_sign::sign_order;
+use dydx_v3_rust::starkex::market_registry::{MarketInfo, MarketRegistry};
+use dydx_v3_rust::starkex::order::SignableOrder;
+use dydx_v3_rust::starkex::quantums::*;
+use dydx_v3_rust::{QuantumsError, StarkError};
+use num_bigint::BigUint;
+use speculate::speculate;
+use std::str::FromStr;
+
+fn btc_usd(step_size: Option<&str>, tick_size: Option<&str>) -> MarketInfo {
+        MarketInfo {
+                market: "BTC-USD".into(),
+                base_asset: "BTC".into(),
+                synthetic_asset_id: "0x4254432d3130000000000000000000".into(),
+                asset_resolution: "1e10".into(),
+                step_size: step_size.map(String::from),
+                tick_size: tick_size.map(String::from),
+        }
+}
+
+fn decimal(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+}
+
+#[cfg(test)]
+speculate! {
+        describe "quantumsTest" {
+                it "rejectsSizeOffStep" {
+                        let error = check_order(&btc_usd(Some("0.001"), Some("1")), "0.0013", "20000").unwrap_err();
+                        assert_eq!(error, QuantumsError::NotMultipleOfStep { field: "size", value: "0.0013".into(), step: "0.001".into() });
+                        assert_eq!(error.to_string(), "size 0.0013 is not a multiple of step 0.001");
+                }
+
+                it "rejectsPriceOffTick" {
+                        let error = check_order(&btc_usd(Some("0.001"), Some("0.5")), "0.002", "20000.25").unwrap_err();
+                        assert_eq!(error.to_string(), "price 20000.25 is not a multiple of step 0.5");
+                        assert!(check_order(&btc_usd(Some("0.001"), Some("0.5")), "0.002", "20000.5").is_ok());
+                }
+
+                it "skipsUnknownSteps" {
+                        assert!(check_order(&btc_usd(None, None), "0.0013", "20000.25").is_ok());
+                }
+
+                it "roundsToQuantums" {
+                        let amount = decimal("0.12345678905");
+                        assert_eq!(to_quantums("size", &amount, "1e10", Rounding::Up).unwrap(), BigUint::from(1234567891u64));
+                        assert_eq!(to_quantums("size", &amount, "1e10", Rounding::Down).unwrap(), BigUint::from(1234567890u64));
+                        assert_eq!(to_quantums("size", &decimal("0.123"), "1e10", Rounding::Exact).unwrap(), BigUint::from(1230000000u64));
+                        let error = to_quantums("size", &amount, "1e10", Rounding::Exact).unwrap_err();
+                        assert_eq!(error.to_string(), "size 0.12345678905 is not a multiple of the quantum size 0.0000000001");
+                }
+
+                it "rejectsInvalidNumbers" {
+                        let error = OrderQuantums::new(&btc_usd(None, None), true, "0.01", "abc", "0.001").unwrap_err();
+                        assert_eq!(error, QuantumsError::InvalidNumber { field: "price", value: "abc".into() });
+                        let error = to_quantums("size", &decimal("-1"), "1e10", Rounding::Exact).unwrap_err();
+                        assert!(matches!(error, QuantumsError::OutOfRange { field: "size", .. }));
+                }
+
+                it "matchesSignedOrder" {
+                        let registry = MarketRegistry::from_constants(NETWORK_ID_MAINNET).unwrap();
+                        let order = SignableOrder::with_registry(&registry, "BTC-USD", "SELL", "228", "0.01", "100000.123456789", "0.0015", "123", 1700000000).unwrap();
+                        let quantums = OrderQuantums::new(&registry.market("BTC-USD").unwrap(), false, "0.01", "100000.123456789", "0.0015").unwrap();
+                        assert_eq!(quantums.synthetic, order.message().quantums_amount_synthetic);
+                        assert_eq!(quantums.collateral, order.message().quantums_amount_collateral);
+                        assert_eq!(quantums.fee, order.message().quantums_amount_fee);
+                }
+
+                it "signOrderReturnsTypedError" {
+                        let error = sign_order(NETWORK_ID_MAINNET, "ETH-USD", "BUY", "228", "0.0000000001", "1000", "0.001", "123", 1700000000, TEST_STARK_PRIVATE_KEY).unwrap_err();
+                        assert!(matches!(
+                                error.downcast_ref::<StarkError>(),
+                                Some(StarkError::Quantums(QuantumsError::NotMultipleOfQuantum { field: "size", .. }))
+                        ));
+                }
+        }
+}
@@ -0,0 +1,148 @@
+macro_rules! b {
+        ($e:expr) => {
+                tokio_test::block_on($e)
+        };
+}
+
+mod common;
+
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::modules::stark_sign::*;
+use dydx_v3_rust::types::*;
+use dydx_v3_rust::{ClientOptions, DydxClient};
+use speculate::speculate;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn order<'a>(market: &'a str, side: &'a str, human_size: &'a str, client_id: &'a str) -> OrderToSign<'a> {
+        OrderToSign {
+                market,
+                side,
+                position_id: "228",
+                human_size,
+                human_price: "100000.123456789",
+                limit_fee: "0.0015",
+                client_id,
+                expiration_epoch_seconds: 1700000000,
+        }
+}
+
+fn sign_one(network_id: usize, order: &OrderToSign) -> dydx_v3_rust::Result<String> {
+        sign_order(network_id, order.market, order.side, order.position_id, order.human_size, order.human_price, order.limit_fee, order.client_id, order.expiration_epoch_seconds, TEST_STARK_PRIVATE_KEY)
+}
+
+fn api_order(market: &'static str, client_id: &'static str) -> ApiOrderParams<'static> {
+        ApiOrderParams {
+                position_id: "228",
+                market,
+                side: "BUY",
+                type_field: "LIMIT",
+                size: "0.01",
+                price: "30000",
+                time_in_force: "GTT",
+                post_only: false,
+                limit_fee: "0.0015",
+                client_id: Some(client_id),
+                cancel_id: None,
+                trigger_price: None,
+                trailing_percent: None,
+                reduce_only: None,
+                expiration: 1700000000,
+        }
+}
+
+fn order_response_body(client_id: &str) -> String {
+        format!(
+                r#"{{"order":{{"id":"id-{client_id}","clientId":"{client_id}","accountId":"account","market":"BTC-USD","side":"BUY","price":"30000","triggerPrice":null,"trailingPercent":null,"size":"0.01","remainingSize":"0.01","type":"LIMIT","createdAt":"2023-01-01T00:00:00.000Z","unfillableAt":null,"expiresAt":"2023-11-14T22:13:20.000Z","status":"PENDING","timeInForce":"GTT","postOnly":false,"cancelReason":null}}}}"#
+        )
+}
+
+// Answers POST /v3/orders with the order of the request, after a delay, counting how
+// many requests are handled at once.
+async fn mock_api(max_active: Arc<AtomicUsize>) -> String {
+        let active = Arc::new(AtomicUsize::new(0));
+        common::serve_api(move |request| {
+                let order: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+                let (active, max_active) = (active.clone(), max_active.clone());
+                async move {
+                        let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_active.fetch_max(now, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        active.fetch_sub(1, Ordering::SeqCst);
+                        order_response_body(order["clientId"].as_str().unwrap())
+                }
+        })
+        .await
+}
+
+fn options() -> ClientOptions<'static> {
+        ClientOptions {
+                stark_private_key: Some(TEST_STARK_PRIVATE_KEY),
+                ..common::options()
+        }
+}
+
+#[cfg(test)]
+speculate! {
+        describe "signOrdersTest" {
+                it "matchesSignOrder" {
+                        let client_ids: Vec<String> = (0..20).map(|i| i.to_string()).collect();
+                        let orders: Vec<OrderToSign> = client_ids
+                                .iter()
+                                .enumerate()
+                                .map(|(i, client_id)| order(if i % 2 == 0 { "BTC-USD" } else { "ETH-USD" }, if i % 3 == 0 { "BUY" } else { "SELL" }, "0.01", client_id))
+                                .collect();
+                        let signatures = sign_orders(NETWORK_ID_MAINNET, &orders, TEST_STARK_PRIVATE_KEY);
+                        assert_eq!(signatures.len(), orders.len());
+                        for (order, signature) in orders.iter().zip(signatures) {
+                                assert_eq!(signature.unwrap(), sign_one(NETWORK_ID_MAINNET, order).unwrap());
+                        }
+                }
+
+                it "failsOnlyInvalidOrders" {
+                        let orders = vec![
+                                order("BTC-USD", "BUY", "0.01", "1"),
+                                order("NOPE-USD", "BUY", "0.01", "2"),
+                                order("BTC-USD", "BUY", "0.00000000001", "3"),
+                                order("BTC-USD", "SELL", "0.01", "4"),
+                        ];
+                        let signatures = sign_orders(TESTNET_NETWORK_ID, &orders, TEST_STARK_PRIVATE_KEY);
+                        assert_eq!(signatures[0].as_ref().unwrap(), &sign_one(TESTNET_NETWORK_ID, &orders[0]).unwrap());
+                        assert!(signatures[1].is_err());
+                        assert!(signatures[2].is_err());
+                        assert_eq!(signatures[3].as_ref().unwrap(), &sign_one(TESTNET_NETWORK_ID, &orders[3]).unwrap());
+                }
+
+                it "invalidKeyFailsEveryOrder" {
+                        let orders = vec![order("BTC-USD", "BUY", "0.01", "1"), order("BTC-USD", "SELL", "0.01", "2")];
+                        let signatures = sign_orders(TESTNET_NETWORK_ID, &orders, "not hex");
+                        assert!(signatures.iter().all(|signature| signature.is_err()));
+                        assert!(sign_orders(TESTNET_NETWORK_ID, &[], TEST_STARK_PRIVATE_KEY).is_empty());
+                }
+
+                it "createOrdersBoundsConcurrency" {
+                        b!(async {
+                                let max_active = Arc::new(AtomicUsize::new(0));
+                                let host = mock_api(max_active.clone()).await;
+                                let client = DydxClient::new(&host, options());
+                                let private = client.private.unwrap();
+                                let orders = vec![
+                                        api_order("BTC-USD", "1"),
+                                        api_order("BTC-USD", "2"),
+                                        api_order("NOPE-USD", "3"),
+                                        api_order("ETH-USD", "4"),
+                                        api_order("BTC-USD", "5"),
+                                        api_order("BTC-USD", "6"),
+                                ];
+                                let responses = private.create_orders(&orders, 2).await;
+                                assert_eq!(responses.len(), 6);
+                                assert!(responses[2].is_err());
+                                for i in [0, 1, 3, 4, 5] {
+                                        assert_eq!(responses[i].as_ref().unwrap().order.client_id, orders[i].client_id.unwrap());
+                                }
+                                assert_eq!(max_active.load(Ordering::SeqCst), 2);
+                        });
+                }
+        }
+}
@@ -0,0 +1,168 @@
+macro_rules! b {
+        ($e:expr) => {
+                tokio_test::block_on($e)
+        };
+}
+
+mod common;
+
+use common::API_SECRET;
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::modules::signed_request::*;
+use dydx_v3_rust::modules::stark_sign;
+use dydx_v3_rust::starkex::helpers::private_key_to_public_hex;
+use dydx_v3_rust::starkex::signers::StarkKeySigner;
+use dydx_v3_rust::types::*;
+use dydx_v3_rust::DydxClient;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use speculate::speculate;
+use std::sync::Arc;
+
+const RECEIVER_PUBLIC_KEY: &str = "04a9ecd28a67407c3cff8937f329ca24fd631b1d9ca2b9f2df47c7ebf72bf0b0";
+
+fn signer() -> RequestSigner {
+        RequestSigner::new(TESTNET_NETWORK_ID, Arc::new(StarkKeySigner::new(TEST_STARK_PRIVATE_KEY).unwrap()))
+}
+
+fn withdraw_params() -> ApiWithdrawParams<'static> {
+        ApiWithdrawParams {
+                position_id: "228",
+                amount: "10.5",
+                asset: "USDC",
+                client_id: None,
+                expiration: 1700000000,
+        }
+}
+
+const WITHDRAWAL_BODY: &str = r#"{"withdrawal":{"id":"id","type":"WITHDRAWAL","debitAsset":"USDC","creditAsset":"USDC","debitAmount":"10.5","creditAmount":"10.5","transactionHash":null,"status":"PENDING","createdAt":"2023-01-01T00:00:00.000Z","confirmedAt":null,"clientId":"1","fromAddress":null,"toAddress":null}}"#;
+
+fn header<'a>(head: &'a str, name: &str) -> &'a str {
+        head.lines()
+                .find_map(|line| line.strip_prefix(&format!("{}: ", name)))
+                .unwrap()
+}
+
+
+#[cfg(test)]
+speculate! {
+        describe "signedRequestTest" {
+                it "signsWithdrawOffline" {
+                        b!(async {
+                                let request = signer().withdraw(&withdraw_params()).await.unwrap();
+                                assert_eq!(request.kind, SignedRequestKind::Withdraw);
+                                let client_id = request.client_id().unwrap();
+                                let public_key = private_key_to_public_hex(TEST_STARK_PRIVATE_KEY).unwrap();
+                                assert!(stark_sign::verify_withdraw(TESTNET_NETWORK_ID, "228", "10.5", &client_id, 1700000000, &request.signature().unwrap(), &public_key).unwrap());
+                                let body: serde_json::Value = serde_json::from_str(request.body.get()).unwrap();
+                                assert_eq!(body["expiration"], "2023-11-14T22:13:20.000Z");
+                                assert_eq!(body["asset"], "USDC");
+                        });
+                }
+
+                it "usesGivenClientIds" {
+                        b!(async {
+                                let params = ApiWithdrawParams { client_id: Some("withdraw-1"), ..withdraw_params() };
+                                let request = signer().withdraw(&params).await.unwrap();
+                                assert_eq!(request.client_id().unwrap(), "withdraw-1");
+
+                                let params = TransferParams {
+                                        amount: "25.5",
+                                        position_id: "228",
+                                        receiver_account_id: "account",
+                                        receiver_public_key: RECEIVER_PUBLIC_KEY,
+                                        receiver_position_id: "229",
+                                        client_id: Some("transfer-1"),
+                                        expiration: 1700000000,
+                                };
+                                let request = signer().transfer(&params).await.unwrap();
+                                assert_eq!(request.client_id().unwrap(), "transfer-1");
+                                assert_eq!(request.signature().unwrap(), stark_sign::sign_transfer(TESTNET_NETWORK_ID, "228", "229", RECEIVER_PUBLIC_KEY, "25.5", "transfer-1", 1700000000, TEST_STARK_PRIVATE_KEY).unwrap());
+                        });
+                }
+
+                it "signsFastWithdrawWithNetworkContracts" {
+                        b!(async {
+                                let params = ApiFastWithdrawalParams {
+                                        position_id: "228",
+                                        credit_asset: "USDC",
+                                        credit_amount: "10",
+                                        debit_amount: "11",
+                                        to_address: TEST_ADDRESS,
+                                        lp_position_id: "2",
+                                        lp_stark_key: RECEIVER_PUBLIC_KEY,
+                                        client_id: Some("fast-1"),
+                                        expiration: 1700000000,
+                                };
+                                let request = signer().fast_withdraw(&params).await.unwrap();
+                                assert_eq!(request.client_id().unwrap(), "fast-1");
+                                assert_eq!(
+                                        request.signature().unwrap(),
+                                        stark_sign::sign_fast_withdraw(TESTNET_NETWORK_ID, "228", "2", RECEIVER_PUBLIC_KEY, FACT_REGISTRY_CONTRACT_TESTNET, TEST_ADDRESS, COLLATERAL_TOKEN_DECIMALS, "10", ASSET_USDC_CONTRACT_TESTNET, "fast-1", 1700000000, TEST_STARK_PRIVATE_KEY).unwrap()
+                                );
+
+                                let unknown = RequestSigner::new(3, Arc::new(StarkKeySigner::new(TEST_STARK_PRIVATE_KEY).unwrap()));
+                                assert!(unknown.fast_withdraw(&params).await.is_err());
+                        });
+                }
+
+                it "signsOrderLikeSignOrder" {
+                        b!(async {
+                                let params = ApiOrderParams {
+                                        position_id: "228",
+                                        market: "BTC-USD",
+                                        side: "SELL",
+                                        type_field: "LIMIT",
+                                        size: "0.01",
+                                        price: "100000.123456789",
+                                        time_in_force: "GTT",
+                                        post_only: false,
+                                        limit_fee: "0.0015",
+                                        client_id: Some("123"),
+                                        cancel_id: None,
+                                        trigger_price: None,
+                                        trailing_percent: None,
+                                        reduce_only: None,
+                                        expiration: 1700000000,
+                                };
+                                let request = signer().order(&params).await.unwrap();
+                                assert_eq!(request.kind.path(), "orders");
+                                assert_eq!(request.client_id().unwrap(), "123");
+                                assert_eq!(request.signature().unwrap(), stark_sign::sign_order(TESTNET_NETWORK_ID, "BTC-USD", "SELL", "228", "0.01", "100000.123456789", "0.0015", "123", 1700000000, TEST_STARK_PRIVATE_KEY).unwrap());
+                        });
+                }
+
+                it "roundTripsJson" {
+                        b!(async {
+                                let request = signer().withdraw(&withdraw_params()).await.unwrap();
+                                let json = serde_json::to_string(&request).unwrap();
+                                assert!(json.starts_with(r#"{"kind":"withdraw","body":{"amount":"10.5","asset":"USDC","#));
+                                let parsed: SignedRequest = serde_json::from_str(&json).unwrap();
+                                assert_eq!(parsed.kind, request.kind);
+                                assert_eq!(parsed.body.get(), request.body.get());
+                        });
+                }
+
+                it "submitSignedAddsApiKeyHeaders" {
+                        b!(async {
+                                let request = signer().withdraw(&withdraw_params()).await.unwrap();
+                                let expected_body = request.body.get().to_string();
+                                let (host, received) = common::serve_once(WITHDRAWAL_BODY).await;
+                                // No STARK key on the submitting client.
+                                let client = DydxClient::new(&host, common::options());
+                                let response: WithdrawalResponse = client.private.unwrap().submit_signed(request).await.unwrap();
+                                assert_eq!(response.withdrawal.id, "id");
+
+                                let common::Request { head, body } = received.await.unwrap();
+                                assert!(head.starts_with("POST /v3/withdrawals HTTP/1.1"));
+                                assert_eq!(body, expected_body);
+                                assert_eq!(header(&head, "dydx-api-key"), "key");
+                                assert_eq!(header(&head, "dydx-passphrase"), "passphrase");
+                                let timestamp = header(&head, "dydx-timestamp");
+                                let mut mac = Hmac::<Sha256>::new_from_slice(&base64::decode_config(API_SECRET, base64::URL_SAFE).unwrap()).unwrap();
+                                mac.update(format!("{}POST/v3/withdrawals{}", timestamp, body).as_bytes());
+                                assert_eq!(header(&head, "dydx-signature"), base64::encode(mac.finalize().into_bytes()));
+                        });
+                }
+        }
+}
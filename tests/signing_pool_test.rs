@@ -0,0 +1,109 @@
+macro_rules! b {
+        ($e:expr) => {
+                tokio_test::block_on($e)
+        };
+}
+
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::starkex::signers::*;
+use dydx_v3_rust::starkex::signing_pool::SigningPool;
+use num_bigint::BigUint;
+use speculate::speculate;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(test)]
+speculate! {
+        describe "signingPoolTest" {
+                it "doesNotBlockExecutor" {
+                        b!(async {
+                                let pool = Arc::new(SigningPool::new(1));
+                                let done = Arc::new(AtomicBool::new(false));
+                                let job = {
+                                        let (pool, done) = (pool.clone(), done.clone());
+                                        tokio::spawn(async move {
+                                                pool.run(move || {
+                                                        std::thread::sleep(Duration::from_millis(300));
+                                                        done.store(true, Ordering::SeqCst);
+                                                }).await.map_err(|e| e.to_string())
+                                        })
+                                };
+                                // The runtime is single-threaded, so this timer only fires
+                                // while the job runs if the job is off the executor.
+                                tokio::time::sleep(Duration::from_millis(20)).await;
+                                assert!(!done.load(Ordering::SeqCst));
+                                job.await.unwrap().unwrap();
+                                assert!(done.load(Ordering::SeqCst));
+                        });
+                }
+
+                it "boundsParallelism" {
+                        b!(async {
+                                let pool = Arc::new(SigningPool::new(2));
+                                let active = Arc::new(AtomicUsize::new(0));
+                                let max_active = Arc::new(AtomicUsize::new(0));
+                                let jobs: Vec<_> = (0..6).map(|_| {
+                                        let (pool, active, max_active) = (pool.clone(), active.clone(), max_active.clone());
+                                        tokio::spawn(async move {
+                                                pool.run(move || {
+                                                        let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                                                        max_active.fetch_max(now, Ordering::SeqCst);
+                                                        std::thread::sleep(Duration::from_millis(50));
+                                                        active.fetch_sub(1, Ordering::SeqCst);
+                                                }).await.map_err(|e| e.to_string())
+                                        })
+                                }).collect();
+                                for job in jobs {
+                                        job.await.unwrap().unwrap();
+                                }
+                                assert_eq!(max_active.load(Ordering::SeqCst), 2);
+                        });
+                }
+
+                it "returnsPanicAsError" {
+                        b!(async {
+                                let pool = SigningPool::new(1);
+                                assert!(pool.run(|| panic!("boom")).await.is_err());
+                                assert_eq!(pool.run(|| 1 + 1).await.unwrap(), 2);
+                        });
+                }
+
+                it "keySignerUsesGivenPool" {
+                        b!(async {
+                                let pool = Arc::new(SigningPool::new(1));
+                                let signer = StarkKeySigner::with_pool(TEST_STARK_PRIVATE_KEY, pool.clone()).unwrap();
+                                let shared = StarkKeySigner::new(TEST_STARK_PRIVATE_KEY).unwrap();
+                                let message_hash = BigUint::from(12345u32);
+
+                                // While another job occupies the only slot of pool, the signer must wait.
+                                let busy = tokio::spawn(async move {
+                                        pool.run(|| std::thread::sleep(Duration::from_millis(300))).await.map_err(|e| e.to_string())
+                                });
+                                tokio::time::sleep(Duration::from_millis(20)).await;
+                                assert!(tokio::time::timeout(Duration::from_millis(100), signer.sign(&message_hash)).await.is_err());
+
+                                assert_eq!(signer.sign(&message_hash).await.unwrap(), shared.sign(&message_hash).await.unwrap());
+                                busy.await.unwrap().unwrap();
+                        });
+                }
+
+                it "keepsSlotOfDroppedRun" {
+                        b!(async {
+                                let pool = SigningPool::new(1);
+                                let done = Arc::new(AtomicBool::new(false));
+                                let job = {
+                                        let done = done.clone();
+                                        pool.run(move || {
+                                                std::thread::sleep(Duration::from_millis(200));
+                                                done.store(true, Ordering::SeqCst);
+                                        })
+                                };
+                                // Dropping the future does not stop the blocking job, so its slot stays taken.
+                                assert!(tokio::time::timeout(Duration::from_millis(20), job).await.is_err());
+                                let finished_first = done.clone();
+                                pool.run(move || assert!(finished_first.load(Ordering::SeqCst))).await.unwrap();
+                        });
+                }
+        }
+}
@@ -0,0 +1,189 @@
+macro_rules! b {
+        ($e:expr) => {
+                tokio_test::block_on($e)
+        };
+}
+
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::starkex::remote_signer::*;
+use dydx_v3_rust::starkex::signature::sign;
+use dydx_v3_rust::starkex::signers::*;
+use dydx_v3_rust::starkex::signing_pool::SigningPool;
+use dydx_v3_rust::StarkError;
+use num_bigint::BigUint;
+use num_traits::Num;
+use speculate::speculate;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, UnixListener, UnixStream};
+
+fn public_key_hex(signer: &StarkKeySigner) -> String {
+        format!("0x{}", signer.public_key().to_str_radix(16))
+}
+
+fn message_hash() -> BigUint {
+        BigUint::from_str_radix("0397e76d1667c4454bfb83514e120583af836f8e32a516765497823eabe16a3f", 16).unwrap()
+}
+
+// Answers a single HTTP request with the given status and JSON body.
+async fn serve_http_once(listener: TcpListener, status: &'static str, body: String) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !String::from_utf8_lossy(&request).contains("messageHash") {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+        }
+        let response = format!(
+                "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+}
+
+#[cfg(test)]
+speculate! {
+        describe "starkSignerTest" {
+                it "keySignerMatchesSign" {
+                        let signer = StarkKeySigner::new(TEST_STARK_PRIVATE_KEY).unwrap();
+                        let expected = sign(&message_hash(), &BigUint::from_str_radix(TEST_STARK_PRIVATE_KEY, 16).unwrap(), None).unwrap();
+                        let signature = b!(signer.sign(&message_hash())).unwrap();
+                        assert_eq!(signature, expected);
+                }
+
+                it "keySignerDebugHidesPrivateKey" {
+                        let signer = StarkKeySigner::new(TEST_STARK_PRIVATE_KEY).unwrap();
+                        let debug = format!("{:?}", signer);
+                        assert!(!debug.contains(TEST_STARK_PRIVATE_KEY));
+                        assert!(debug.contains(&signer.public_key().to_str_radix(16)));
+                }
+
+                it "remoteSignerOverUnixSocket" {
+                        let path = std::env::temp_dir().join(format!("dydx-stark-signer-{}.sock", std::process::id()));
+                        let _ = std::fs::remove_file(&path);
+                        b!(async {
+                                let key_signer = Arc::new(StarkKeySigner::new(TEST_STARK_PRIVATE_KEY).unwrap());
+                                let listener = UnixListener::bind(&path).unwrap();
+                                tokio::spawn(serve_unix_socket(listener, key_signer.clone()));
+
+                                let remote = RemoteStarkSigner::unix_socket(&path, &public_key_hex(&key_signer)).unwrap();
+                                let expected = key_signer.sign(&message_hash()).await.unwrap();
+                                assert_eq!(remote.sign(&message_hash()).await.unwrap(), expected);
+                        });
+                        let _ = std::fs::remove_file(&path);
+                }
+
+                it "remoteSignerVerifiesOnGivenPool" {
+                        let path = std::env::temp_dir().join(format!("dydx-stark-signer-pool-{}.sock", std::process::id()));
+                        let _ = std::fs::remove_file(&path);
+                        b!(async {
+                                let key_signer = Arc::new(StarkKeySigner::new(TEST_STARK_PRIVATE_KEY).unwrap());
+                                let listener = UnixListener::bind(&path).unwrap();
+                                tokio::spawn(serve_unix_socket(listener, key_signer.clone()));
+                                let pool = Arc::new(SigningPool::new(1));
+                                let remote = RemoteStarkSigner::unix_socket(&path, &public_key_hex(&key_signer)).unwrap().with_pool(pool.clone());
+
+                                // While another job occupies the only slot of pool, verification must wait.
+                                let busy = tokio::spawn(async move {
+                                        pool.run(|| std::thread::sleep(Duration::from_millis(300))).await.map_err(|e| e.to_string())
+                                });
+                                tokio::time::sleep(Duration::from_millis(20)).await;
+                                assert!(tokio::time::timeout(Duration::from_millis(100), remote.sign(&message_hash())).await.is_err());
+
+                                assert_eq!(remote.sign(&message_hash()).await.unwrap(), key_signer.sign(&message_hash()).await.unwrap());
+                                busy.await.unwrap().unwrap();
+                        });
+                        let _ = std::fs::remove_file(&path);
+                }
+
+                it "unixSocketRejectsLongLines" {
+                        let path = std::env::temp_dir().join(format!("dydx-stark-signer-long-{}.sock", std::process::id()));
+                        let _ = std::fs::remove_file(&path);
+                        b!(async {
+                                let listener = UnixListener::bind(&path).unwrap();
+                                tokio::spawn(serve_unix_socket(listener, Arc::new(StarkKeySigner::new(TEST_STARK_PRIVATE_KEY).unwrap())));
+
+                                let mut stream = UnixStream::connect(&path).await.unwrap();
+                                stream.write_all(&vec![b' '; 2 * MAX_REMOTE_LINE_LEN as usize]).await.unwrap();
+                                let mut response = String::new();
+                                stream.read_to_string(&mut response).await.unwrap();
+                                let response: RemoteSignResponse = serde_json::from_str(&response).unwrap();
+                                assert!(response.error.unwrap().contains("longer than"));
+                        });
+                        let _ = std::fs::remove_file(&path);
+                }
+
+                it "remoteSignerOverHttp" {
+                        b!(async {
+                                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                                let url = format!("http://{}/sign", listener.local_addr().unwrap());
+                                let key_signer = StarkKeySigner::new(TEST_STARK_PRIVATE_KEY).unwrap();
+                                let (r, s) = key_signer.sign(&message_hash()).await.unwrap();
+                                let body = format!(r#"{{"r":"0x{}","s":"0x{}"}}"#, r.to_str_radix(16), s.to_str_radix(16));
+                                tokio::spawn(serve_http_once(listener, "200 OK", body));
+
+                                let remote = RemoteStarkSigner::http(&url, &public_key_hex(&key_signer)).unwrap();
+                                assert_eq!(remote.sign(&message_hash()).await.unwrap(), (r, s));
+                        });
+                }
+
+                it "remoteSignerRejectsWrongSignature" {
+                        b!(async {
+                                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                                let url = format!("http://{}/sign", listener.local_addr().unwrap());
+                                tokio::spawn(serve_http_once(listener, "200 OK", r#"{"r":"0x1234","s":"0xabcd"}"#.to_string()));
+
+                                let key_signer = StarkKeySigner::new(TEST_STARK_PRIVATE_KEY).unwrap();
+                                let remote = RemoteStarkSigner::http(&url, &public_key_hex(&key_signer)).unwrap();
+                                let error = remote.sign(&message_hash()).await.unwrap_err();
+                                assert!(matches!(error.downcast_ref::<StarkError>(), Some(StarkError::InvalidSignature(_))));
+                        });
+                }
+
+                it "remoteSignerRejectsHttpErrors" {
+                        b!(async {
+                                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                                let url = format!("http://{}/sign", listener.local_addr().unwrap());
+                                tokio::spawn(serve_http_once(listener, "503 Service Unavailable", "{}".to_string()));
+
+                                let remote = RemoteStarkSigner::http(&url, "0x1").unwrap();
+                                let error = remote.sign(&message_hash()).await.unwrap_err();
+                                assert!(error.to_string().contains("503"));
+                        });
+                }
+
+                it "remoteSignerTimesOut" {
+                        let path = std::env::temp_dir().join(format!("dydx-stark-signer-silent-{}.sock", std::process::id()));
+                        let _ = std::fs::remove_file(&path);
+                        b!(async {
+                                // Accepts connections but never answers.
+                                let listener = UnixListener::bind(&path).unwrap();
+                                tokio::spawn(async move {
+                                        let mut streams = Vec::new();
+                                        loop {
+                                                streams.push(listener.accept().await.unwrap().0);
+                                        }
+                                });
+
+                                let remote = RemoteStarkSigner::unix_socket(&path, "0x1").unwrap().with_timeout(Duration::from_millis(100));
+                                assert!(remote.sign(&message_hash()).await.is_err());
+                        });
+                        let _ = std::fs::remove_file(&path);
+                }
+
+                it "remoteSignerRejection" {
+                        b!(async {
+                                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                                let url = format!("http://{}/sign", listener.local_addr().unwrap());
+                                tokio::spawn(serve_http_once(listener, "200 OK", r#"{"error":"locked"}"#.to_string()));
+
+                                let remote = RemoteStarkSigner::http(&url, "0x1").unwrap();
+                                let error = remote.sign(&message_hash()).await.unwrap_err();
+                                assert!(error.to_string().contains("locked"));
+                        });
+                }
+        }
+}
@@ -1,13 +1,76 @@
 use dydx_v3_rust::constants::*;
 use dydx_v3_rust::modules::stark_sign::*;
+use dydx_v3_rust::starkex::helpers::get_transfer_erc20_fact;
+use dydx_v3_rust::starkex::signature::pedersen_hash;
+use num_bigint::BigUint;
+use dydx_v3_rust::{QuantumsError, StarkError};
 use speculate::speculate;
 
+// Expected signatures were produced by src/stark/stark_sign.py with the same inputs.
 #[cfg(test)]
 speculate! {
         describe "starkTest" {
                 it "signOrder" {
                         let sig = sign_order(TESTNET_NETWORK_ID, "ETH-USD", "BUY", "62392","145.0005","350.00067","0.125","This is an ID that the client came up with to describe this order", 1600316155, TEST_STARK_PRIVATE_KEY).unwrap();
-                        println!("{}", sig);
+                        assert_eq!(sig, "074ced04ac759560a1a74bf9a1069064121a7803a06c6475b1edd767b91a6bb9039a1dece032141bd195fdaf943c2041d2867a9b33f36dfa78a6cb48d884b9cc");
+                }
+
+                it "signSellOrder" {
+                        let sig = sign_order(NETWORK_ID_MAINNET, "BTC-USD", "SELL", "228", "0.01", "100000.123456789", "0.0015", "123", 1700000000, TEST_STARK_PRIVATE_KEY).unwrap();
+                        assert_eq!(sig, "0714e9e3ca6541f22e4160dc8fe007375b94d519984181d92379cf8b550875e20238b4a330f0cc89d042e13b52b9d33c55cdc516406cfbea04adb64746244ead");
+                }
+
+                it "signWithdraw" {
+                        let sig = sign_withdraw(NETWORK_ID_MAINNET, "228", "3", "456", 1700000001, TEST_STARK_PRIVATE_KEY).unwrap();
+                        assert_eq!(sig, "07b43d3cb577505f81d33cf98d7ac490da62612857538c9da4c5c0b9eb7117fe0335f4b4e727dd81158f7b46343a6260b5626b6322829dd39711b9bbaca1fa0a");
+                }
+
+                it "signTransfer" {
+                        let sig = sign_transfer(NETWORK_ID_GOERLI, "228", "2", "04a9ecd28a67407c3cff8937f329ca24fd631b1d9ca2b9f2df47c7ebf72bf0b0", "10.5", "789", 1700003600, TEST_STARK_PRIVATE_KEY).unwrap();
+                        assert_eq!(sig, "02e74bf2cc7c1843371f9c2ab4207a8a9f5120f2c78863ff2431b77746953bf20221dc52a74b55aae5121dbd0ef1ab5bcbd7bb6fb7ca75d2e89e7326ecd66606");
+                }
+
+                it "signFastWithdraw" {
+                        let sig = sign_fast_withdraw(NETWORK_ID_MAINNET, "228", "2", "04a9ecd28a67407c3cff8937f329ca24fd631b1d9ca2b9f2df47c7ebf72bf0b0", FACT_REGISTRY_CONTRACT_MAINNET, TEST_ADDRESS, COLLATERAL_TOKEN_DECIMALS, "10", ASSET_USDC_CONTRACT_MAINNET, "999", 1700000000, TEST_STARK_PRIVATE_KEY).unwrap();
+                        assert_eq!(sig, "03c7bfb54807dc4ba1045da0aaef546df4bfaf0b5d1d89bbc19b8ac306a55c9000d615bf03c4e1374f938ea0093d1fa2e7e5ca965cb560b28034bd9c746857e1");
+                }
+
+                it "pedersenHashTakesAtMostTwoElements" {
+                        let one = BigUint::from(1u32);
+                        assert!(pedersen_hash(&[&one, &one]).is_ok());
+                        assert!(matches!(pedersen_hash(&[&one, &one, &one]), Err(StarkError::OutOfRange { .. })));
+                }
+
+                it "rejectsInexactSize" {
+                        let result = sign_order(NETWORK_ID_MAINNET, "ETH-USD", "BUY", "228", "0.0000000001", "1000", "0.001", "123", 1700000000, TEST_STARK_PRIVATE_KEY);
+                        assert!(result.is_err());
+                }
+
+                it "rejectsUnknownMarket" {
+                        let result = sign_order(NETWORK_ID_MAINNET, "FOO-USD", "BUY", "228", "1", "1000", "0.001", "123", 1700000000, TEST_STARK_PRIVATE_KEY);
+                        assert!(result.is_err());
+                }
+
+                it "rejectsReceiverKeyOutsideField" {
+                        let key = "f".repeat(64);
+                        let error = sign_transfer(NETWORK_ID_GOERLI, "228", "2", &key, "10", "789", 1700003600, TEST_STARK_PRIVATE_KEY).unwrap_err();
+                        assert!(matches!(error.downcast_ref::<StarkError>(), Some(StarkError::OutOfRange { field: "receiver_public_key", .. })));
+                        let error = sign_fast_withdraw(NETWORK_ID_MAINNET, "228", "2", &key, FACT_REGISTRY_CONTRACT_MAINNET, TEST_ADDRESS, COLLATERAL_TOKEN_DECIMALS, "10", ASSET_USDC_CONTRACT_MAINNET, "999", 1700000000, TEST_STARK_PRIVATE_KEY).unwrap_err();
+                        assert!(matches!(error.downcast_ref::<StarkError>(), Some(StarkError::OutOfRange { field: "receiver_public_key", .. })));
+                }
+
+                it "rejectsFactOfMalformedAddresses" {
+                        let short = &TEST_ADDRESS[..40];
+                        let error = get_transfer_erc20_fact(short, COLLATERAL_TOKEN_DECIMALS, "10", ASSET_USDC_CONTRACT_MAINNET, 1).unwrap_err();
+                        assert_eq!(error, StarkError::InvalidAddress(short.to_string()));
+                        let long = format!("{}00", ASSET_USDC_CONTRACT_MAINNET);
+                        let error = get_transfer_erc20_fact(TEST_ADDRESS, COLLATERAL_TOKEN_DECIMALS, "10", &long, 1).unwrap_err();
+                        assert_eq!(error, StarkError::InvalidAddress(long));
+                }
+
+                it "rejectsFactAmountOver256Bits" {
+                        let error = get_transfer_erc20_fact(TEST_ADDRESS, COLLATERAL_TOKEN_DECIMALS, "1e300", ASSET_USDC_CONTRACT_MAINNET, 1).unwrap_err();
+                        assert!(matches!(error, StarkError::Quantums(QuantumsError::OutOfRange { field: "amount", .. })));
                 }
         }
 
@@ -0,0 +1,134 @@
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::modules::stark_sign;
+use dydx_v3_rust::starkex::conditional_transfer::SignableConditionalTransfer;
+use dydx_v3_rust::starkex::helpers::{get_transfer_erc20_fact, nonce_from_client_id};
+use dydx_v3_rust::starkex::order::SignableOrder;
+use dydx_v3_rust::starkex::signable::Signable;
+use dydx_v3_rust::starkex::signers::{StarkKeySigner, StarkSigner};
+use dydx_v3_rust::starkex::transfer::SignableTransfer;
+use dydx_v3_rust::starkex::withdrawal::SignableWithdrawal;
+use num_bigint::BigUint;
+use speculate::speculate;
+
+// Hashes and signatures were produced by src/stark with StarkWare's sign(msg_hash,
+// priv_key, seed), without a seed and with seed 42.
+const RECEIVER_PUBLIC_KEY: &str = "04a9ecd28a67407c3cff8937f329ca24fd631b1d9ca2b9f2df47c7ebf72bf0b0";
+const SEED: u32 = 42;
+
+struct Vector {
+        hash: &'static str,
+        signature: &'static str,
+        seeded_signature: &'static str,
+}
+
+fn check(message: &impl Signable, vector: &Vector) {
+        let seed = BigUint::from(SEED);
+        assert_eq!(format!("{:#x}", message.calculate_hash().unwrap()), vector.hash);
+        assert_eq!(message.sign(TEST_STARK_PRIVATE_KEY).unwrap(), vector.signature);
+        assert_eq!(message.sign_with_seed(TEST_STARK_PRIVATE_KEY, Some(&seed)).unwrap(), vector.seeded_signature);
+}
+
+fn order(network_id: usize) -> SignableOrder {
+        SignableOrder::new(network_id, "BTC-USD", "BUY", "228", "0.5", "29000.5", "0.0005", "vector-order", 1700000000).unwrap()
+}
+
+fn transfer(network_id: usize) -> SignableTransfer {
+        SignableTransfer::new(network_id, "228", "229", RECEIVER_PUBLIC_KEY, "25.5", "vector-transfer", 1700003600).unwrap()
+}
+
+fn withdrawal(network_id: usize) -> SignableWithdrawal {
+        SignableWithdrawal::new(network_id, "228", "100", "vector-withdrawal", 1700007200).unwrap()
+}
+
+fn conditional_transfer(network_id: usize) -> SignableConditionalTransfer {
+        let fact = get_transfer_erc20_fact(TEST_ADDRESS, COLLATERAL_TOKEN_DECIMALS, "10", ASSET_USDC_CONTRACT_MAINNET, nonce_from_client_id("vector-fast-withdraw")).unwrap();
+        SignableConditionalTransfer::new(network_id, "228", "2", RECEIVER_PUBLIC_KEY, FACT_REGISTRY_CONTRACT_MAINNET, &fact, "10", "vector-fast-withdraw", 1700010800).unwrap()
+}
+
+#[cfg(test)]
+speculate! {
+        describe "starkVectorsTest" {
+                it "order" {
+                        check(&order(NETWORK_ID_MAINNET), &Vector {
+                                hash: "0x4ced75823439dd282d22491c4bceca81747f0f08f84ad371c8a7bbbc1413c53",
+                                signature: "05afd020139b6edbcab2f0c25dab26612b891475fec15a282215596ceccec78a07fa501983bf1de85515f6dbb74945f685f4519eb282c7caeba51eceae4f83d4",
+                                seeded_signature: "00291446bcbd111b40ae55a88d044061dfe38274ccc88888b9c70d3759737301022d7e30e11391986951b375a33ddefe6c0b929941953d27ebd5ea183e5f4b23",
+                        });
+                        check(&order(TESTNET_NETWORK_ID), &Vector {
+                                hash: "0x5e59e815f5a2330b81e0347e427e0d050e89dc3bf4524c32ae45ad56694dfa3",
+                                signature: "069d428402a0ac7d95f1c359244aa095ba937c90ffb49e40982b1d687fb4c5a3066f881901f4a31ca1a14ce17a593f4a65d4be6501943a60a3a00f4976448d4d",
+                                seeded_signature: "067b75fa933720f5d269c52e7fa91bfe47b7ff3599ce16a6690636327d238d2704c9261deab7fc36307eb3f9a0a9024f739cbaec154896960f56094950bf0699",
+                        });
+                }
+
+                it "transfer" {
+                        check(&transfer(NETWORK_ID_MAINNET), &Vector {
+                                hash: "0xecd28fc5196aae7ad2fdff9cd3529bc4626312a693f52aa149fe99867b1664",
+                                signature: "03431a75329e83929f350d3db9f0726d2a37adefa11bff7fa94d49262dced8240031f8cfebcc661d6d0d6228bee136afbe2990604bbba15a2477793763c7ecc4",
+                                seeded_signature: "0738bb49a70acd0174daa531312e5f5eaec0031d0b08e1c2de01a76bf47acad400279c0e894d9386641405c2491e2d00f805fd346152b8f6706d84f6d4377aca",
+                        });
+                        check(&transfer(TESTNET_NETWORK_ID), &Vector {
+                                hash: "0x30dd47f21f8575602db8d439d6489506aea24b21102aa09cbb8f5de34f3f1",
+                                signature: "011f86d027c5f40ce7c54ca5ba5dbfd62b8324029e7599c04d7b24b54e7617db054bf90366e80af563a76fe69a39006023f1d0886585baaa269554954caf947d",
+                                seeded_signature: "046fd7955686662a1d3b6f949384fba5227399a8f57d467134b83824e038510a03cc497bd7e19d3064f9c7c2843b126fc9a0f1c72c6e1922c64c5ef0b6534ea0",
+                        });
+                }
+
+                it "withdrawal" {
+                        check(&withdrawal(NETWORK_ID_MAINNET), &Vector {
+                                hash: "0x21444f87f1d7a127810ef3f913edfe691d15da5a4bf076d3b7be0e650290b63",
+                                signature: "0173a8792327b2e4e6458444a70ea819978ea4380c94db819db979c695b1d8e70204c3ed6a241860608dc51abd10872352b9169c3bde023879854da0027faf49",
+                                seeded_signature: "0533a157ee95a00176486fb1cf07baac21753a6c3d00930fc26c3b7b85556a61007e3d7d4b3c47d477c0e5db3522a8b620fc950c1b5bc6181d1fb02e745487a5",
+                        });
+                        check(&withdrawal(TESTNET_NETWORK_ID), &Vector {
+                                hash: "0x616970b2aaed6d2315e557fd55f2f03fbf184d2156274c5c2e4e1fa855cbafb",
+                                signature: "01e4678e421ea53dd54ce2a492edc8dd2831a2e5ab63609d9dfeefbd4ac9fffd03c671549c7bb6f4af7e16f86d580e0685502ff220f958df4940f83deead4151",
+                                seeded_signature: "005c13690653ff2871228c544d03d99dd56a1141efdc7ddcd8a4a959b67065dc064beb76e01a19a9518468c2eaa15759b113d21e39dda6046cb0c072cb04224b",
+                        });
+                }
+
+                it "conditionalTransfer" {
+                        check(&conditional_transfer(NETWORK_ID_MAINNET), &Vector {
+                                hash: "0x1b8466bbeb6cb0ed1fbbe633a757076fa0ae97455aa80ff09e71a48de948fd3",
+                                signature: "067bc51d0eabd0f352ce41558d6e6b241d83d40f816a8d7c7c30b828acab9530005c6e674dfd53019376cc0141eb651ffceca93ba0e8a77f765bcf2199e4c5fc",
+                                seeded_signature: "0734ee8d738f074565db5be685217331b9a9af1fde5e529a51530916c89c4213029f008457abf9f58193592d12bb046f1542131b8a0c742d4621462689f35600",
+                        });
+                        check(&conditional_transfer(TESTNET_NETWORK_ID), &Vector {
+                                hash: "0x3e4b40b9845700a39f831e59bf289f8397c84ed6f94b5c96cf47538abe29824",
+                                signature: "05a420bace436a86793bbd23ff4edb3c98a59eb5602491172c0e55abab37a0490726a9a0e2af17add508ce1f899a838e2cbbaed4ee7b2aed154922d251d797a7",
+                                seeded_signature: "00714e06788fa81cdb044f97ae5763c9eac79f81c7a472b40ab96e48ca2c484c019929b8a16207635ac04585eeb0a7ed90ba3a5f8f3c7ba8b52467d8be74b960",
+                        });
+                }
+
+                it "signWrappersWithSeed" {
+                        let seed = BigUint::from(SEED);
+                        assert_eq!(
+                                stark_sign::sign_order_with_seed(NETWORK_ID_MAINNET, "BTC-USD", "BUY", "228", "0.5", "29000.5", "0.0005", "vector-order", 1700000000, TEST_STARK_PRIVATE_KEY, Some(&seed)).unwrap(),
+                                "00291446bcbd111b40ae55a88d044061dfe38274ccc88888b9c70d3759737301022d7e30e11391986951b375a33ddefe6c0b929941953d27ebd5ea183e5f4b23"
+                        );
+                        assert_eq!(
+                                stark_sign::sign_transfer_with_seed(NETWORK_ID_MAINNET, "228", "229", RECEIVER_PUBLIC_KEY, "25.5", "vector-transfer", 1700003600, TEST_STARK_PRIVATE_KEY, Some(&seed)).unwrap(),
+                                "0738bb49a70acd0174daa531312e5f5eaec0031d0b08e1c2de01a76bf47acad400279c0e894d9386641405c2491e2d00f805fd346152b8f6706d84f6d4377aca"
+                        );
+                        assert_eq!(
+                                stark_sign::sign_withdraw_with_seed(NETWORK_ID_MAINNET, "228", "100", "vector-withdrawal", 1700007200, TEST_STARK_PRIVATE_KEY, Some(&seed)).unwrap(),
+                                "0533a157ee95a00176486fb1cf07baac21753a6c3d00930fc26c3b7b85556a61007e3d7d4b3c47d477c0e5db3522a8b620fc950c1b5bc6181d1fb02e745487a5"
+                        );
+                        assert_eq!(
+                                stark_sign::sign_fast_withdraw_with_seed(NETWORK_ID_MAINNET, "228", "2", RECEIVER_PUBLIC_KEY, FACT_REGISTRY_CONTRACT_MAINNET, TEST_ADDRESS, COLLATERAL_TOKEN_DECIMALS, "10", ASSET_USDC_CONTRACT_MAINNET, "vector-fast-withdraw", 1700010800, TEST_STARK_PRIVATE_KEY, Some(&seed)).unwrap(),
+                                "0734ee8d738f074565db5be685217331b9a9af1fde5e529a51530916c89c4213029f008457abf9f58193592d12bb046f1542131b8a0c742d4621462689f35600"
+                        );
+                        assert_eq!(
+                                stark_sign::sign_withdraw_with_seed(NETWORK_ID_MAINNET, "228", "100", "vector-withdrawal", 1700007200, TEST_STARK_PRIVATE_KEY, None).unwrap(),
+                                stark_sign::sign_withdraw(NETWORK_ID_MAINNET, "228", "100", "vector-withdrawal", 1700007200, TEST_STARK_PRIVATE_KEY).unwrap()
+                        );
+                }
+
+                it "keySignerWithSeed" {
+                        let message = withdrawal(NETWORK_ID_MAINNET);
+                        let signer = StarkKeySigner::new(TEST_STARK_PRIVATE_KEY).unwrap().with_seed(BigUint::from(SEED));
+                        let (r, s) = tokio_test::block_on(signer.sign(&message.calculate_hash().unwrap())).unwrap();
+                        assert_eq!(format!("{:064x}{:064x}", r, s), message.sign_with_seed(TEST_STARK_PRIVATE_KEY, Some(&BigUint::from(SEED))).unwrap());
+                }
+        }
+}
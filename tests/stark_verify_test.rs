@@ -0,0 +1,130 @@
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::modules::stark_sign::*;
+use dydx_v3_rust::starkex::helpers::private_key_to_public_hex;
+use speculate::speculate;
+
+// Produced by src/stark/stark_sign.py, i.e. StarkWare's python_signature.py rather than
+// the Rust port, with this key; REFERENCE_ORDER_WITH_SEED used seed 42.
+const REFERENCE_PRIVATE_KEY: &str = "3c1e9550e66958296d11b60f8e8e7a7ad990d07fa65d5f7652c4a6c87d4e3cc";
+const REFERENCE_PUBLIC_KEY: &str = "0x77a3b314db07c45076d11f62b6f9e748a39790441823307743cf00d6597ea43";
+const REFERENCE_RECEIVER: &str = "0x5b1ef1a0a7d9e1e7f0b8a5f6c8e1d3b2a4c6e8f0a1b3c5d7e9f1a3b5c7d9e1f";
+const REFERENCE_RECIPIENT: &str = "0x0000000000000000000000000000000000000abc";
+const REFERENCE_ORDER: &str = "07d5f6d0f9b619df27cd3ea00927b039315ce4be1ab1550d96548047f7570062020a81a4e9b6f35ef4fc1a0ff6cddc1fe898541358885be66d7ba13e92b0ef5b";
+const REFERENCE_ORDER_WITH_SEED: &str = "02b0ba18a8ed3c0fc8707d240425f58cb7f7a42a71a9d41a05aafe82f85e444800cb1f17cbd48ce845d1ebb59b3dd8de0bcc388d903e1ab311843b149ae3ffd0";
+const REFERENCE_WITHDRAW: &str = "076ec13a3d34e79082dc671d5b4a7b359faa942a64d49c298280292ba3ec4a750636c837d6e3a2d707959e6d31d91d606e0a229e818499a6b14cbb43b4ceecb8";
+const REFERENCE_TRANSFER: &str = "016850e5234974b334b0bbf7fa56b94a5113875454a98d908347203131309bf200880d1124fc3c023697e9c56308761b02e813db617ccec8a70d92aa62d8c7fb";
+const REFERENCE_FAST_WITHDRAW: &str = "07bbbcb4b96e00c68a671783f79c3da698899c645f1f5613eb3e3d6ee7bf0ec907a6fe88cabae7459230cda685876205fb0be7677ee5b47adbc3e2814e3d28ee";
+
+// The other signatures are the sign_* vectors of stark_test.rs.
+#[cfg(test)]
+speculate! {
+        describe "starkVerifyTest" {
+                before {
+                        let public_key = private_key_to_public_hex(TEST_STARK_PRIVATE_KEY).unwrap();
+                }
+
+                it "verifyOrder" {
+                        let sig = "0714e9e3ca6541f22e4160dc8fe007375b94d519984181d92379cf8b550875e20238b4a330f0cc89d042e13b52b9d33c55cdc516406cfbea04adb64746244ead";
+                        assert!(verify_order(NETWORK_ID_MAINNET, "BTC-USD", "SELL", "228", "0.01", "100000.123456789", "0.0015", "123", 1700000000, sig, &public_key).unwrap());
+                        assert!(!verify_order(NETWORK_ID_MAINNET, "BTC-USD", "SELL", "228", "0.02", "100000.123456789", "0.0015", "123", 1700000000, sig, &public_key).unwrap());
+                }
+
+                it "verifyWithdraw" {
+                        let sig = "07b43d3cb577505f81d33cf98d7ac490da62612857538c9da4c5c0b9eb7117fe0335f4b4e727dd81158f7b46343a6260b5626b6322829dd39711b9bbaca1fa0a";
+                        assert!(verify_withdraw(NETWORK_ID_MAINNET, "228", "3", "456", 1700000001, sig, &public_key).unwrap());
+                        assert!(!verify_withdraw(NETWORK_ID_MAINNET, "228", "3", "457", 1700000001, sig, &public_key).unwrap());
+                }
+
+                it "verifyTransfer" {
+                        let sig = "02e74bf2cc7c1843371f9c2ab4207a8a9f5120f2c78863ff2431b77746953bf20221dc52a74b55aae5121dbd0ef1ab5bcbd7bb6fb7ca75d2e89e7326ecd66606";
+                        assert!(verify_transfer(NETWORK_ID_GOERLI, "228", "2", "04a9ecd28a67407c3cff8937f329ca24fd631b1d9ca2b9f2df47c7ebf72bf0b0", "10.5", "789", 1700003600, sig, &public_key).unwrap());
+                }
+
+                it "verifyFastWithdraw" {
+                        let sig = "03c7bfb54807dc4ba1045da0aaef546df4bfaf0b5d1d89bbc19b8ac306a55c9000d615bf03c4e1374f938ea0093d1fa2e7e5ca965cb560b28034bd9c746857e1";
+                        assert!(verify_fast_withdraw(NETWORK_ID_MAINNET, "228", "2", "04a9ecd28a67407c3cff8937f329ca24fd631b1d9ca2b9f2df47c7ebf72bf0b0", FACT_REGISTRY_CONTRACT_MAINNET, TEST_ADDRESS, COLLATERAL_TOKEN_DECIMALS, "10", ASSET_USDC_CONTRACT_MAINNET, "999", 1700000000, sig, &public_key).unwrap());
+                }
+
+                it "rejectsWrongPublicKey" {
+                        let sig = "07b43d3cb577505f81d33cf98d7ac490da62612857538c9da4c5c0b9eb7117fe0335f4b4e727dd81158f7b46343a6260b5626b6322829dd39711b9bbaca1fa0a";
+                        let other_key = private_key_to_public_hex("0x1234").unwrap();
+                        assert_ne!(other_key, public_key);
+                        assert!(!verify_withdraw(NETWORK_ID_MAINNET, "228", "3", "456", 1700000001, sig, &other_key).unwrap());
+                }
+
+                it "verifiesFreshSignature" {
+                        let sig = sign_order(TESTNET_NETWORK_ID, "ETH-USD", "BUY", "62392", "145.0005", "350.00067", "0.125", "client id", 1600316155, TEST_STARK_PRIVATE_KEY).unwrap();
+                        assert!(verify_order(TESTNET_NETWORK_ID, "ETH-USD", "BUY", "62392", "145.0005", "350.00067", "0.125", "client id", 1600316155, &sig, &public_key).unwrap());
+                }
+
+                it "rejectsMalformedSignature" {
+                        let result = verify_withdraw(NETWORK_ID_MAINNET, "228", "3", "456", 1700000001, "0x1234", &public_key);
+                        assert!(result.is_err());
+                }
+        }
+
+        describe "starkVerifyReferenceTest" {
+                it "verifiesReferenceSignatures" {
+                        assert_eq!(private_key_to_public_hex(REFERENCE_PRIVATE_KEY).unwrap(), REFERENCE_PUBLIC_KEY);
+                        for sig in [REFERENCE_ORDER, REFERENCE_ORDER_WITH_SEED] {
+                                assert!(verify_order(NETWORK_ID_MAINNET, "ETH-USD", "BUY", "12345", "2.5", "1850.75", "0.0005", "external-order", 1735689600, sig, REFERENCE_PUBLIC_KEY).unwrap());
+                        }
+                        assert!(verify_withdraw(NETWORK_ID_GOERLI, "12345", "250.25", "external-withdraw", 1735689600, REFERENCE_WITHDRAW, REFERENCE_PUBLIC_KEY).unwrap());
+                        assert!(verify_transfer(NETWORK_ID_GOERLI, "12345", "67890", REFERENCE_RECEIVER, "75", "external-transfer", 1735689600, REFERENCE_TRANSFER, REFERENCE_PUBLIC_KEY).unwrap());
+                        assert!(verify_fast_withdraw(NETWORK_ID_MAINNET, "12345", "67890", REFERENCE_RECEIVER, FACT_REGISTRY_CONTRACT_MAINNET, REFERENCE_RECIPIENT, COLLATERAL_TOKEN_DECIMALS, "75", ASSET_USDC_CONTRACT_MAINNET, "external-fast", 1735689600, REFERENCE_FAST_WITHDRAW, REFERENCE_PUBLIC_KEY).unwrap());
+
+                        let sig = sign_order(NETWORK_ID_MAINNET, "ETH-USD", "BUY", "12345", "2.5", "1850.75", "0.0005", "external-order", 1735689600, REFERENCE_PRIVATE_KEY).unwrap();
+                        assert_eq!(sig, REFERENCE_ORDER);
+                }
+
+                it "rejectsTamperedReferenceOrder" {
+                        let verify = |side, position_id, size, price, limit_fee, client_id, expiration| {
+                                verify_order(NETWORK_ID_MAINNET, "ETH-USD", side, position_id, size, price, limit_fee, client_id, expiration, REFERENCE_ORDER, REFERENCE_PUBLIC_KEY).unwrap()
+                        };
+                        assert!(!verify("SELL", "12345", "2.5", "1850.75", "0.0005", "external-order", 1735689600));
+                        assert!(!verify("BUY", "12346", "2.5", "1850.75", "0.0005", "external-order", 1735689600));
+                        assert!(!verify("BUY", "12345", "2.6", "1850.75", "0.0005", "external-order", 1735689600));
+                        assert!(!verify("BUY", "12345", "2.5", "1850.8", "0.0005", "external-order", 1735689600));
+                        assert!(!verify("BUY", "12345", "2.5", "1850.75", "0.001", "external-order", 1735689600));
+                        assert!(!verify("BUY", "12345", "2.5", "1850.75", "0.0005", "other-order", 1735689600));
+                        assert!(!verify("BUY", "12345", "2.5", "1850.75", "0.0005", "external-order", 1735776000));
+                        assert!(!verify_order(NETWORK_ID_MAINNET, "BTC-USD", "BUY", "12345", "2.5", "1850.75", "0.0005", "external-order", 1735689600, REFERENCE_ORDER, REFERENCE_PUBLIC_KEY).unwrap());
+                }
+
+                it "rejectsTamperedReferenceWithdraw" {
+                        let verify = |position_id, amount, client_id, expiration| {
+                                verify_withdraw(NETWORK_ID_GOERLI, position_id, amount, client_id, expiration, REFERENCE_WITHDRAW, REFERENCE_PUBLIC_KEY).unwrap()
+                        };
+                        assert!(!verify("12346", "250.25", "external-withdraw", 1735689600));
+                        assert!(!verify("12345", "250.26", "external-withdraw", 1735689600));
+                        assert!(!verify("12345", "250.25", "other-withdraw", 1735689600));
+                        assert!(!verify("12345", "250.25", "external-withdraw", 1735776000));
+                        assert!(!verify_withdraw(NETWORK_ID_MAINNET, "12345", "250.25", "external-withdraw", 1735689600, REFERENCE_WITHDRAW, REFERENCE_PUBLIC_KEY).unwrap());
+                }
+
+                it "rejectsTamperedReferenceTransfer" {
+                        let other_receiver = "0x5b1ef1a0a7d9e1e7f0b8a5f6c8e1d3b2a4c6e8f0a1b3c5d7e9f1a3b5c7d9e20";
+                        let verify = |sender, receiver, receiver_key, amount, client_id, expiration| {
+                                verify_transfer(NETWORK_ID_GOERLI, sender, receiver, receiver_key, amount, client_id, expiration, REFERENCE_TRANSFER, REFERENCE_PUBLIC_KEY).unwrap()
+                        };
+                        assert!(!verify("12346", "67890", REFERENCE_RECEIVER, "75", "external-transfer", 1735689600));
+                        assert!(!verify("12345", "67891", REFERENCE_RECEIVER, "75", "external-transfer", 1735689600));
+                        assert!(!verify("12345", "67890", other_receiver, "75", "external-transfer", 1735689600));
+                        assert!(!verify("12345", "67890", REFERENCE_RECEIVER, "76", "external-transfer", 1735689600));
+                        assert!(!verify("12345", "67890", REFERENCE_RECEIVER, "75", "other-transfer", 1735689600));
+                        assert!(!verify("12345", "67890", REFERENCE_RECEIVER, "75", "external-transfer", 1735776000));
+                }
+
+                it "rejectsTamperedReferenceFastWithdraw" {
+                        let verify = |receiver, recipient, amount, token, client_id| {
+                                verify_fast_withdraw(NETWORK_ID_MAINNET, "12345", receiver, REFERENCE_RECEIVER, FACT_REGISTRY_CONTRACT_MAINNET, recipient, COLLATERAL_TOKEN_DECIMALS, amount, token, client_id, 1735689600, REFERENCE_FAST_WITHDRAW, REFERENCE_PUBLIC_KEY).unwrap()
+                        };
+                        assert!(!verify("67891", REFERENCE_RECIPIENT, "75", ASSET_USDC_CONTRACT_MAINNET, "external-fast"));
+                        assert!(!verify("67890", "0x0000000000000000000000000000000000000abd", "75", ASSET_USDC_CONTRACT_MAINNET, "external-fast"));
+                        assert!(!verify("67890", REFERENCE_RECIPIENT, "76", ASSET_USDC_CONTRACT_MAINNET, "external-fast"));
+                        assert!(!verify("67890", REFERENCE_RECIPIENT, "75", ASSET_USDC_CONTRACT_ROPSTEN, "external-fast"));
+                        assert!(!verify("67890", REFERENCE_RECIPIENT, "75", ASSET_USDC_CONTRACT_MAINNET, "other-fast"));
+                        assert!(!verify_fast_withdraw(NETWORK_ID_MAINNET, "12345", "67890", REFERENCE_RECEIVER, FACT_REGISTRY_CONTRACT_ROPSTEN, REFERENCE_RECIPIENT, COLLATERAL_TOKEN_DECIMALS, "75", ASSET_USDC_CONTRACT_MAINNET, "external-fast", 1735689600, REFERENCE_FAST_WITHDRAW, REFERENCE_PUBLIC_KEY).unwrap());
+                }
+        }
+}
@@ -0,0 +1,71 @@
+use dydx_v3_rust::constants::*;
+use dydx_v3_rust::starkex::order::{SignableOrder, StarkwareOrder};
+use dydx_v3_rust::starkex::transfer::SignableTransfer;
+use dydx_v3_rust::starkex::withdrawal::SignableWithdrawal;
+use dydx_v3_rust::starkex::helpers::hex_to_biguint;
+use dydx_v3_rust::StarkError;
+use speculate::speculate;
+
+// Expected hashes were produced by the Signable* classes of src/stark/starkex with the same inputs.
+#[cfg(test)]
+speculate! {
+        describe "starkwareMessageTest" {
+                it "orderHash" {
+                        let order = SignableOrder::new(NETWORK_ID_MAINNET, "BTC-USD", "SELL", "228", "0.01", "100000.123456789", "0.0015", "123", 1700000000).unwrap();
+                        let message = order.message();
+                        assert_eq!(message.quantums_amount_synthetic, 100000000);
+                        assert_eq!(message.quantums_amount_collateral, 1000001234);
+                        assert_eq!(message.quantums_amount_fee, 1500002);
+                        assert_eq!(message.position_id, 228);
+                        assert_eq!(message.expiration_epoch_hours, 472391);
+                        assert_eq!(format!("{:#x}", message.hash().unwrap()), "0x74146f277f8c9a03728a1060de1f2e19590dceee55805762f08ffb8df96f6b4");
+                }
+
+                it "orderFromFields" {
+                        let order = StarkwareOrder {
+                                asset_id_synthetic: hex_to_biguint("0x4254432d3130000000000000000000").unwrap(),
+                                asset_id_collateral: hex_to_biguint(COLLATERAL_ASSET_ID_MAINNET).unwrap(),
+                                asset_id_fee: hex_to_biguint(COLLATERAL_ASSET_ID_MAINNET).unwrap(),
+                                quantums_amount_synthetic: 100000000,
+                                quantums_amount_collateral: 1000001234,
+                                quantums_amount_fee: 1500002,
+                                is_buying_synthetic: false,
+                                position_id: 228,
+                                nonce: 4154620643,
+                                expiration_epoch_hours: 472391,
+                        };
+                        let signable = SignableOrder::new(NETWORK_ID_MAINNET, "BTC-USD", "SELL", "228", "0.01", "100000.123456789", "0.0015", "123", 1700000000).unwrap();
+                        assert_eq!(&order, signable.message());
+                }
+
+                it "orderBreakdown" {
+                        let order = SignableOrder::new(NETWORK_ID_MAINNET, "BTC-USD", "SELL", "228", "0.01", "100000.123456789", "0.0015", "123", 1700000000).unwrap();
+                        let breakdown = order.message().to_string();
+                        assert!(breakdown.contains("side: SELL"));
+                        assert!(breakdown.contains("asset_id_synthetic: 0x4254432d3130000000000000000000"));
+                        assert!(breakdown.contains("quantums_amount_fee: 1500002"));
+                        assert!(breakdown.contains("expiration_epoch_hours: 472391 (2023-11-21T23:00:00Z)"));
+                }
+
+                it "rejectsExpirationOutside32Bits" {
+                        for expiration in [-(1i64 << 40), (1i64 << 32) * 3600] {
+                                let withdrawal = SignableWithdrawal::new(NETWORK_ID_MAINNET, "228", "3", "456", expiration).unwrap();
+                                assert!(matches!(withdrawal.message().hash(), Err(StarkError::OutOfRange { field: "expiration_epoch_hours", .. })));
+                                let order = SignableOrder::new(NETWORK_ID_MAINNET, "BTC-USD", "SELL", "228", "0.01", "100000.123456789", "0.0015", "123", expiration).unwrap();
+                                assert!(order.message().hash().is_err());
+                        }
+                }
+
+                it "withdrawalHash" {
+                        let withdrawal = SignableWithdrawal::new(NETWORK_ID_MAINNET, "228", "3", "456", 1700000001).unwrap();
+                        assert_eq!(withdrawal.message().quantums_amount, 3000000);
+                        assert_eq!(format!("{:#x}", withdrawal.message().hash().unwrap()), "0x15d0987d5f86f66745439ddb35356a8dd7f71f10e5d1ae8e1ab55de168fc4b5");
+                }
+
+                it "transferHash" {
+                        let transfer = SignableTransfer::new(NETWORK_ID_GOERLI, "228", "2", "04a9ecd28a67407c3cff8937f329ca24fd631b1d9ca2b9f2df47c7ebf72bf0b0", "10.5", "789", 1700003600).unwrap();
+                        assert_eq!(transfer.message().quantums_amount, 10500000);
+                        assert_eq!(format!("{:#x}", transfer.message().hash().unwrap()), "0x7787e23b06d1ba5b648e2f401ed2a4ff170a623656fae402c748fe34807aaf0");
+                }
+        }
+}
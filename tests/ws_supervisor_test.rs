@@ -0,0 +1,251 @@
+macro_rules! b {
+        ($e:expr) => {
+                tokio_test::block_on($e)
+        };
+}
+
+mod common;
+
+use bigdecimal::BigDecimal;
+use dydx_v3_rust::modules::public::Public;
+use dydx_v3_rust::retry::FallbackBackoffGetter;
+use dydx_v3_rust::ws::account_state::AccountState;
+use dydx_v3_rust::ws::connection::WsConnection;
+use dydx_v3_rust::ws::messages::*;
+use dydx_v3_rust::ws::supervisor::*;
+use dydx_v3_rust::{DydxClient, WsError};
+use serde_json::{json, Value};
+use speculate::speculate;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+const SNAPSHOT: &str = r#"{"type":"subscribed","message_id":1,"channel":"v3_orderbook","id":"BTC-USD","contents":{"asks":[{"size":"1","price":"101","offset":"10"}],"bids":[{"size":"1","price":"100","offset":"10"}]}}"#;
+
+// A stand-in for the dYdX server: runs serve on each connection, numbered from 0.
+async fn stand_in<F, Fut>(serve: F) -> String
+where
+        F: Fn(usize, WsConnection) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+{
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/v3/ws", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+                for index in 0.. {
+                        let (stream, _) = listener.accept().await.unwrap();
+                        tokio::spawn(serve(index, WsConnection::accept(stream).await.unwrap()));
+                }
+        });
+        url
+}
+
+fn order_json(id: &str) -> Value {
+        json!({"id":id,"clientId":id,"accountId":"a1","market":"BTC-USD","side":"BUY","price":"100","triggerPrice":null,"trailingPercent":null,"size":"1","remainingSize":"1","type":"LIMIT","createdAt":"2023-01-01T00:00:00.000Z","unfillableAt":null,"expiresAt":"2023-02-01T00:00:00.000Z","status":"OPEN","timeInForce":"GTT","postOnly":false,"cancelReason":null})
+}
+
+fn account_json() -> Value {
+        json!({"starkKey":"0x1","positionId":"1","equity":"1000","freeCollateral":"900","pendingDeposits":"0","pendingWithdrawals":"0","openPositions":{},"accountNumber":"0","id":"a1","quoteBalance":"1000"})
+}
+
+// Serves GET /v3/orderbook/BTC-USD, or with accounts the account, its open order o2 and
+// no fills, one request per connection.
+async fn mock_api() -> String {
+        common::serve_api(|request| {
+                let head = request.head;
+                let body = if head.starts_with("GET /v3/accounts/") {
+                        json!({"account": account_json()}).to_string()
+                } else if head.starts_with("GET /v3/orders?status=OPEN&") {
+                        json!({"orders": [order_json("o2")]}).to_string()
+                } else if head.starts_with("GET /v3/orders?") {
+                        json!({"orders": []}).to_string()
+                } else if head.starts_with("GET /v3/fills?") {
+                        json!({"fills": []}).to_string()
+                } else {
+                        r#"{"asks":[{"size":"5","price":"105"}],"bids":[{"size":"4","price":"95"}]}"#.to_string()
+                };
+                async { body }
+        })
+        .await
+}
+
+fn backoff() -> Arc<FallbackBackoffGetter> {
+        Arc::new(FallbackBackoffGetter::new(2.0, 10, 50, 3))
+}
+
+async fn expect_channel(server: &mut WsConnection) -> String {
+        let message: Value = serde_json::from_str(&server.receive().await.unwrap().unwrap()).unwrap();
+        assert_eq!(message["type"], "subscribe");
+        message["channel"].as_str().unwrap().to_string()
+}
+
+fn decimal(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+}
+
+async fn next(supervisor: &mut WsSupervisor<'_>) -> SupervisorEvent {
+        supervisor.next_event().await.unwrap().unwrap()
+}
+
+#[cfg(test)]
+speculate! {
+        describe "wsSupervisorTest" {
+                it "reconnectsResubscribesAndReseeds" {
+                        b!(async {
+                                let url = stand_in(|index, mut server| async move {
+                                        server.send_text(r#"{"type":"connected","connection_id":"c","message_id":0}"#).await.unwrap();
+                                        assert_eq!(expect_channel(&mut server).await, "v3_orderbook");
+                                        assert_eq!(expect_channel(&mut server).await, "v3_trades");
+                                        if index == 0 {
+                                                server.send_text(SNAPSHOT).await.unwrap();
+                                                server.close().await.unwrap();
+                                        } else {
+                                                tokio::time::sleep(Duration::from_secs(5)).await;
+                                        }
+                                }).await;
+                                let host = mock_api().await;
+                                let public = Public::new(&host, 5, None, DydxClient::get_no_backoff_getter());
+                                let mut supervisor = WsSupervisor::new(&url, backoff()).with_public(public);
+                                supervisor.subscribe(Subscription::orderbook("BTC-USD")).await.unwrap();
+                                supervisor.subscribe(Subscription::trades("BTC-USD")).await.unwrap();
+
+                                assert_eq!(next(&mut supervisor).await, SupervisorEvent::Event(WsEvent::Connected { connection_id: "c".into() }));
+                                assert!(matches!(next(&mut supervisor).await, SupervisorEvent::Event(WsEvent::Orderbook { .. })));
+                                assert_eq!(supervisor.book("BTC-USD").unwrap().best_ask().unwrap().price, decimal("101"));
+
+                                assert_eq!(next(&mut supervisor).await, SupervisorEvent::Reconnected { attempts: 1, cause: "closed by the server".into() });
+                                assert_eq!(next(&mut supervisor).await, SupervisorEvent::GapDetected {
+                                        channel: Channel::Orderbook("BTC-USD".into()),
+                                        reason: GapReason::Reconnected,
+                                        resynced: true,
+                                });
+                                assert_eq!(next(&mut supervisor).await, SupervisorEvent::GapDetected {
+                                        channel: Channel::Trades("BTC-USD".into()),
+                                        reason: GapReason::Reconnected,
+                                        resynced: false,
+                                });
+                                let book = supervisor.book("BTC-USD").unwrap();
+                                assert!(book.is_synced());
+                                assert_eq!(book.best_ask().unwrap().price, decimal("105"));
+                                assert_eq!(next(&mut supervisor).await, SupervisorEvent::Event(WsEvent::Connected { connection_id: "c".into() }));
+                                assert_eq!(supervisor.subscriptions().len(), 2);
+                        });
+                }
+
+                it "reseedsAccountStateOnReconnect" {
+                        b!(async {
+                                let url = stand_in(|index, mut server| async move {
+                                        assert_eq!(expect_channel(&mut server).await, "v3_accounts");
+                                        if index == 0 {
+                                                let snapshot = json!({"type":"subscribed","message_id":1,"channel":"v3_accounts","id":"a1","contents":{"account":account_json(),"orders":[order_json("o1")]}});
+                                                server.send_text(&snapshot.to_string()).await.unwrap();
+                                                server.close().await.unwrap();
+                                        } else {
+                                                tokio::time::sleep(Duration::from_secs(5)).await;
+                                        }
+                                }).await;
+                                let host = mock_api().await;
+                                let client = DydxClient::new(&host, common::options());
+                                let address = "0x0000000000000000000000000000000000000001";
+                                let mut supervisor = WsSupervisor::new(&url, backoff()).with_account_state(AccountState::new(), client.private.unwrap(), address);
+                                supervisor.subscribe(Subscription::accounts("0", common::credentials())).await.unwrap();
+
+                                assert!(matches!(next(&mut supervisor).await, SupervisorEvent::Event(WsEvent::Accounts { snapshot: true, .. })));
+                                assert!(supervisor.account_state().unwrap().snapshot().orders.contains_key("o1"));
+
+                                assert!(matches!(next(&mut supervisor).await, SupervisorEvent::Reconnected { .. }));
+                                assert_eq!(next(&mut supervisor).await, SupervisorEvent::GapDetected {
+                                        channel: Channel::Accounts("0".into()),
+                                        reason: GapReason::Reconnected,
+                                        resynced: true,
+                                });
+                                let orders = &supervisor.account_state().unwrap().snapshot().orders;
+                                assert_eq!(orders.keys().collect::<Vec<_>>(), vec!["o2"]);
+                        });
+                }
+
+                it "reconnectsWhenHeartbeatStops" {
+                        b!(async {
+                                let url = stand_in(|index, mut server| async move {
+                                        expect_channel(&mut server).await;
+                                        if index == 0 {
+                                                server.send_text(SNAPSHOT).await.unwrap();
+                                        } else {
+                                                server.send_text(r#"{"type":"connected","connection_id":"c2","message_id":0}"#).await.unwrap();
+                                        }
+                                        tokio::time::sleep(Duration::from_secs(5)).await;
+                                }).await;
+                                let mut supervisor = WsSupervisor::new(&url, backoff()).with_heartbeat_timeout(Some(Duration::from_millis(200)));
+                                supervisor.subscribe(Subscription::orderbook("BTC-USD")).await.unwrap();
+
+                                next(&mut supervisor).await;
+                                let mut top = supervisor.book("BTC-USD").unwrap().watch();
+                                assert!(top.borrow_and_update().synced);
+                                match next(&mut supervisor).await {
+                                        SupervisorEvent::Reconnected { attempts, cause } => {
+                                                assert_eq!(attempts, 1);
+                                                assert_eq!(cause, WsError::HeartbeatTimeout(Duration::from_millis(200)).to_string());
+                                        }
+                                        event => panic!("unexpected {:?}", event),
+                                }
+                                // Without a Public, the book waits for the snapshot of the new subscription.
+                                assert_eq!(next(&mut supervisor).await, SupervisorEvent::GapDetected {
+                                        channel: Channel::Orderbook("BTC-USD".into()),
+                                        reason: GapReason::Reconnected,
+                                        resynced: false,
+                                });
+                                assert!(!supervisor.book("BTC-USD").unwrap().is_synced());
+                                assert!(!top.borrow_and_update().synced);
+                                assert_eq!(next(&mut supervisor).await, SupervisorEvent::Event(WsEvent::Connected { connection_id: "c2".into() }));
+                        });
+                }
+
+                it "detectsMissedMessagesAndCrossedBooks" {
+                        b!(async {
+                                let url = stand_in(|_, mut server| async move {
+                                        expect_channel(&mut server).await;
+                                        server.send_text(SNAPSHOT).await.unwrap();
+                                        server.send_text(r#"{"type":"channel_data","message_id":3,"channel":"v3_orderbook","id":"BTC-USD","contents":{"offset":"11","asks":[],"bids":[["100.5","1"]]}}"#).await.unwrap();
+                                        server.send_text(r#"{"type":"channel_data","message_id":4,"channel":"v3_orderbook","id":"BTC-USD","contents":{"offset":"12","asks":[],"bids":[["106","1"]]}}"#).await.unwrap();
+                                        tokio::time::sleep(Duration::from_secs(5)).await;
+                                }).await;
+                                let host = mock_api().await;
+                                let public = Public::new(&host, 5, None, DydxClient::get_no_backoff_getter());
+                                let mut supervisor = WsSupervisor::new(&url, backoff()).with_public(public);
+                                supervisor.subscribe(Subscription::orderbook("BTC-USD")).await.unwrap();
+                                let channel = Channel::Orderbook("BTC-USD".into());
+
+                                next(&mut supervisor).await;
+                                assert_eq!(next(&mut supervisor).await, SupervisorEvent::GapDetected {
+                                        channel: channel.clone(),
+                                        reason: GapReason::MissedMessages { expected: 2, received: 3 },
+                                        resynced: true,
+                                });
+                                assert!(matches!(next(&mut supervisor).await, SupervisorEvent::Event(WsEvent::Orderbook { .. })));
+                                assert_eq!(supervisor.book("BTC-USD").unwrap().best_bid().unwrap().price, decimal("100.5"));
+
+                                assert!(matches!(next(&mut supervisor).await, SupervisorEvent::Event(WsEvent::Orderbook { .. })));
+                                assert_eq!(next(&mut supervisor).await, SupervisorEvent::GapDetected {
+                                        channel,
+                                        reason: GapReason::CrossedBook,
+                                        resynced: true,
+                                });
+                                assert_eq!(supervisor.book("BTC-USD").unwrap().best_bid().unwrap().price, decimal("95"));
+                        });
+                }
+
+                it "givesUpWhenBackoffIsExhausted" {
+                        b!(async {
+                                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                                let url = format!("ws://{}/v3/ws", listener.local_addr().unwrap());
+                                drop(listener);
+                                let mut supervisor = WsSupervisor::new(&url, backoff());
+                                assert!(matches!(supervisor.next_event().await, Err(WsError::Io(_))));
+
+                                supervisor.close().await.unwrap();
+                                assert_eq!(supervisor.next_event().await.unwrap(), None);
+                        });
+                }
+        }
+}
@@ -0,0 +1,327 @@
+macro_rules! b {
+        ($e:expr) => {
+                tokio_test::block_on($e)
+        };
+}
+
+use dydx_v3_rust::modules::api_key_signer::ApiKeySigner;
+use dydx_v3_rust::types::{ApiKeyCredentials, OrderbookResponseOrder};
+use dydx_v3_rust::ws::client::WsClient;
+use dydx_v3_rust::ws::connection::WsConnection;
+use dydx_v3_rust::ws::messages::*;
+use dydx_v3_rust::WsError;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use speculate::speculate;
+use std::collections::HashMap;
+use std::future::Future;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::{Data, OpCode};
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::frame::Frame as RawFrame;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+type Server = WebSocketStream<TcpStream>;
+
+// A stand-in for the dYdX server: accepts one connection and runs serve on it. The
+// server side is tungstenite, so the client is checked against an independent peer.
+async fn stand_in<F, Fut>(serve: F) -> String
+where
+        F: FnOnce(Server) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+{
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/v3/ws", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                serve(tokio_tungstenite::accept_async(stream).await.unwrap()).await;
+        });
+        url
+}
+
+async fn send_text(server: &mut Server, text: &str) {
+        server.send(Message::Text(text.to_string())).await.unwrap();
+}
+
+async fn expect_json(server: &mut Server) -> Value {
+        match server.next().await.unwrap().unwrap() {
+                Message::Text(text) => serde_json::from_str(&text).unwrap(),
+                message => panic!("unexpected {:?}", message),
+        }
+}
+
+fn fragment(opcode: Data, payload: &[u8], fin: bool) -> Message {
+        Message::Frame(RawFrame::message(payload.to_vec(), OpCode::Data(opcode), fin))
+}
+
+fn level(price: &str, size: &str, offset: u64) -> OrderbookLevel {
+        OrderbookLevel {
+                order: OrderbookResponseOrder { size: size.into(), price: price.into() },
+                offset: Some(offset),
+        }
+}
+
+fn market_json(index_price: &str) -> Value {
+        json!({"market":"BTC-USD","status":"ONLINE","baseAsset":"BTC","quoteAsset":"USD","stepSize":"0.001","tickSize":"1","indexPrice":index_price,"oraclePrice":"1","priceChange24H":"0","nextFundingRate":"0","nextFundingAt":"2023-01-01T00:00:00.000Z","minOrderSize":"0.001","type":"PERPETUAL","initialMarginFraction":"0.05","maintenanceMarginFraction":"0.03","volume24H":"0","trades24H":"0","openInterest":"0","incrementalInitialMarginFraction":"0.01","incrementalPositionSize":"1","maxPositionSize":"100","baselinePositionSize":"10","assetResolution":"10000000000","syntheticAssetId":"0x4254432d3130000000000000000000"})
+}
+
+fn order_json(status: &str, remaining_size: &str) -> Value {
+        json!({"id":"o1","clientId":"123","accountId":"a1","market":"BTC-USD","side":"BUY","price":"100","triggerPrice":null,"trailingPercent":null,"size":"1","remainingSize":remaining_size,"type":"LIMIT","createdAt":"2023-01-01T00:00:00.000Z","unfillableAt":null,"expiresAt":"2023-02-01T00:00:00.000Z","status":status,"timeInForce":"GTT","postOnly":false,"cancelReason":null,"limitFee":"0.001","reduceOnly":false})
+}
+
+const CREDENTIALS: ApiKeyCredentials = ApiKeyCredentials {
+        key: "key",
+        secret: "c2VjcmV0",
+        passphrase: "passphrase",
+};
+
+#[cfg(test)]
+speculate! {
+        describe "wsTest" {
+                it "buildsSubscribeMessages" {
+                        let message: Value = serde_json::from_str(&Subscription::orderbook("BTC-USD").batched().subscribe_message()).unwrap();
+                        assert_eq!(message, json!({"type":"subscribe","channel":"v3_orderbook","id":"BTC-USD","includeOffsets":true,"batched":true}));
+                        let message: Value = serde_json::from_str(&Subscription::markets().subscribe_message()).unwrap();
+                        assert_eq!(message, json!({"type":"subscribe","channel":"v3_markets"}));
+                        let message: Value = serde_json::from_str(&Subscription::trades("ETH-USD").unsubscribe_message()).unwrap();
+                        assert_eq!(message, json!({"type":"unsubscribe","channel":"v3_trades","id":"ETH-USD"}));
+                }
+
+                it "receivesOrderbookEvents" {
+                        b!(async {
+                                let url = stand_in(|mut server| async move {
+                                        send_text(&mut server, r#"{"type":"connected","connection_id":"c1","message_id":0}"#).await;
+                                        let subscribe = expect_json(&mut server).await;
+                                        assert_eq!(subscribe["channel"], "v3_orderbook");
+                                        send_text(&mut server, r#"{"type":"subscribed","connection_id":"c1","message_id":1,"channel":"v3_orderbook","id":"BTC-USD","contents":{"asks":[{"size":"1.5","price":"101","offset":"10"}],"bids":[{"size":"2","price":"100","offset":"11"}]}}"#).await;
+                                        send_text(&mut server, r#"{"type":"channel_data","connection_id":"c1","message_id":2,"channel":"v3_orderbook","id":"BTC-USD","contents":{"offset":"12","bids":[["100","0"]],"asks":[]}}"#).await;
+                                        send_text(&mut server, r#"{"type":"channel_batch_data","connection_id":"c1","message_id":3,"channel":"v3_orderbook","id":"BTC-USD","contents":[{"offset":"13","asks":[["102","1","13"]],"bids":[]},{"offset":"14","asks":[],"bids":[["99","3","14"]]}]}"#).await;
+                                        server.close(None).await.unwrap();
+                                }).await;
+
+                                let mut client = WsClient::connect(&url).await.unwrap();
+                                client.subscribe(&Subscription::orderbook("BTC-USD").batched()).await.unwrap();
+                                let mut events = Vec::new();
+                                while let Some(event) = client.next_event().await.unwrap() {
+                                        events.push(event);
+                                }
+                                let market = "BTC-USD".to_string();
+                                assert_eq!(events, vec![
+                                        WsEvent::Connected { connection_id: "c1".into() },
+                                        WsEvent::Orderbook { market: market.clone(), update: OrderbookUpdate::Snapshot { asks: vec![level("101", "1.5", 10)], bids: vec![level("100", "2", 11)] } },
+                                        WsEvent::Orderbook { market: market.clone(), update: OrderbookUpdate::Delta { offset: Some(12), asks: vec![], bids: vec![level("100", "0", 12)] } },
+                                        WsEvent::Orderbook { market: market.clone(), update: OrderbookUpdate::Delta { offset: Some(13), asks: vec![level("102", "1", 13)], bids: vec![] } },
+                                        WsEvent::Orderbook { market, update: OrderbookUpdate::Delta { offset: Some(14), asks: vec![], bids: vec![level("99", "3", 14)] } },
+                                ]);
+                                assert_eq!(client.last_message_id(), Some(3));
+                        });
+                }
+
+                it "receivesTradesAndMarkets" {
+                        b!(async {
+                                let url = stand_in(|mut server| async move {
+                                        expect_json(&mut server).await;
+                                        expect_json(&mut server).await;
+                                        send_text(&mut server, r#"{"type":"subscribed","message_id":1,"channel":"v3_trades","id":"BTC-USD","contents":{"trades":[{"side":"BUY","size":"0.1","price":"100","createdAt":"2023-01-01T00:00:00.000Z","liquidation":false}]}}"#).await;
+                                        send_text(&mut server, &json!({"type":"subscribed","message_id":2,"channel":"v3_markets","contents":{"markets":{"BTC-USD":market_json("100")}}}).to_string()).await;
+                                        send_text(&mut server, r#"{"type":"channel_data","message_id":3,"channel":"v3_markets","contents":{"BTC-USD":{"indexPrice":"105"}}}"#).await;
+                                        send_text(&mut server, r#"{"type":"error","message":"Invalid subscription id"}"#).await;
+                                        server.close(None).await.unwrap();
+                                }).await;
+
+                                let mut client = WsClient::connect(&url).await.unwrap();
+                                client.subscribe(&Subscription::trades("BTC-USD")).await.unwrap();
+                                client.subscribe(&Subscription::markets()).await.unwrap();
+
+                                match client.next_event().await.unwrap().unwrap() {
+                                        WsEvent::Trades { market, snapshot, trades } => {
+                                                assert_eq!(market, "BTC-USD");
+                                                assert!(snapshot);
+                                                assert_eq!(trades[0].price, "100");
+                                        }
+                                        event => panic!("unexpected {:?}", event),
+                                }
+                                let mut markets = HashMap::new();
+                                for _ in 0..2 {
+                                        match client.next_event().await.unwrap().unwrap() {
+                                                WsEvent::Markets(update) => assert!(update.apply(&mut markets).is_empty()),
+                                                event => panic!("unexpected {:?}", event),
+                                        }
+                                }
+                                assert_eq!(markets["BTC-USD"].index_price, "105");
+                                assert_eq!(markets["BTC-USD"].tick_size, "1");
+                                assert_eq!(client.next_event().await.unwrap(), Some(WsEvent::Error { message: "Invalid subscription id".into() }));
+                                assert_eq!(client.next_event().await.unwrap(), None);
+                        });
+                }
+
+                it "skipsMarketsDeltaOfUnknownMarkets" {
+                        let mut markets = HashMap::new();
+                        MarketsUpdate::Snapshot(HashMap::from([("BTC-USD".to_string(), serde_json::from_value(market_json("100")).unwrap())])).apply(&mut markets);
+                        let delta: HashMap<String, serde_json::Map<String, Value>> = serde_json::from_value(json!({
+                                "BTC-USD": {"indexPrice": "105"},
+                                "ETH-USD": {"indexPrice": "2000"},
+                                "LINK-USD": {"indexPrice": "7"},
+                        })).unwrap();
+                        assert_eq!(MarketsUpdate::Delta(delta).apply(&mut markets), vec!["ETH-USD", "LINK-USD"]);
+                        assert_eq!(markets.len(), 1);
+                        assert_eq!(markets["BTC-USD"].index_price, "105");
+
+                        let delta = serde_json::from_value(json!({"BTC-USD": {"indexPrice": 106}})).unwrap();
+                        assert_eq!(MarketsUpdate::Delta(delta).apply(&mut markets), vec!["BTC-USD"]);
+                        assert_eq!(markets["BTC-USD"].index_price, "105");
+                }
+
+                it "receivesSignedAccountsUpdates" {
+                        b!(async {
+                                let url = stand_in(|mut server| async move {
+                                        let subscribe = expect_json(&mut server).await;
+                                        assert_eq!(subscribe["channel"], "v3_accounts");
+                                        assert_eq!(subscribe["accountNumber"], "0");
+                                        assert_eq!(subscribe["apiKey"], "key");
+                                        assert_eq!(subscribe["passphrase"], "passphrase");
+                                        let timestamp = subscribe["timestamp"].as_str().unwrap();
+                                        assert_eq!(subscribe["signature"], ApiKeySigner::new(CREDENTIALS).sign("/ws/accounts", "GET", timestamp, None));
+
+                                        let account = json!({"starkKey":"0x1","positionId":"1","equity":"1000","freeCollateral":"900","pendingDeposits":"0","pendingWithdrawals":"0","openPositions":{},"accountNumber":"0","id":"a1","quoteBalance":"1000"});
+                                        send_text(&mut server, &json!({"type":"subscribed","message_id":1,"channel":"v3_accounts","id":"a1","contents":{"account":account,"orders":[order_json("OPEN", "1")]}}).to_string()).await;
+                                        send_text(&mut server, &json!({"type":"channel_data","message_id":2,"channel":"v3_accounts","id":"a1","contents":{
+                                                "orders":[order_json("FILLED", "0")],
+                                                "fills":[{"id":"f1","side":"BUY","liquidity":"TAKER","type":"LIMIT","market":"BTC-USD","orderId":"o1","price":"100","size":"1","fee":"0.1","createdAt":"2023-01-01T00:00:01.000Z","accountId":"a1"}],
+                                                "positions":[{"market":"BTC-USD","status":"OPEN","side":"LONG","size":"1","maxSize":"1","entryPrice":"100","exitPrice":null,"realizedPnl":"0","createdAt":"2023-01-01T00:00:01.000Z","closedAt":null,"sumOpen":"1","sumClose":"0","netFunding":"0"}],
+                                                "fundingPayments":[{"market":"BTC-USD","payment":"-0.01","rate":"0.0001","positionSize":"1","price":"100","effectiveAt":"2023-01-01T01:00:00.000Z"}],
+                                                "accounts":[{"id":"a1","quoteBalance":"899.9"}]
+                                        }}).to_string()).await;
+                                        send_text(&mut server, r#"{"type":"unsubscribed","message_id":3,"channel":"v3_accounts","id":"a1"}"#).await;
+                                        server.close(None).await.unwrap();
+                                }).await;
+
+                                let subscription = Subscription::accounts("0", CREDENTIALS);
+                                let debug = format!("{:?}", subscription);
+                                assert!(debug.contains(r#"key: "key""#));
+                                assert!(!debug.contains(CREDENTIALS.secret) && !debug.contains(r#""passphrase""#));
+
+                                let mut client = WsClient::connect(&url).await.unwrap();
+                                client.subscribe(&subscription).await.unwrap();
+                                match client.next_event().await.unwrap().unwrap() {
+                                        WsEvent::Accounts { account_id, snapshot, update } => {
+                                                assert_eq!(account_id, "a1");
+                                                assert!(snapshot);
+                                                assert_eq!(update.account.unwrap().free_collateral, "900");
+                                                assert_eq!(update.orders[0].status, "OPEN");
+                                        }
+                                        event => panic!("unexpected {:?}", event),
+                                }
+                                match client.next_event().await.unwrap().unwrap() {
+                                        WsEvent::Accounts { snapshot, update, .. } => {
+                                                assert!(!snapshot);
+                                                assert!(update.account.is_none());
+                                                assert_eq!(update.orders[0].remaining_size, "0");
+                                                assert_eq!(update.fills[0].order_id.as_deref(), Some("o1"));
+                                                assert_eq!(update.positions[0].entry_price, "100");
+                                                assert!(update.transfers.is_empty());
+                                                assert_eq!(update.funding_payments[0].payment, "-0.01");
+                                                assert_eq!(update.accounts[0]["quoteBalance"], "899.9");
+                                        }
+                                        event => panic!("unexpected {:?}", event),
+                                }
+                                // The server names the account by id; the client by the number it subscribed to.
+                                assert_eq!(client.next_event().await.unwrap(), Some(WsEvent::Unsubscribed(Channel::Accounts("0".into()))));
+                                assert_eq!(client.next_event().await.unwrap(), None);
+                        });
+                }
+
+                it "answersPingsAndJoinsFragments" {
+                        b!(async {
+                                let url = stand_in(|mut server| async move {
+                                        server.send(Message::Ping(b"heartbeat".to_vec())).await.unwrap();
+                                        assert_eq!(server.next().await.unwrap().unwrap(), Message::Pong(b"heartbeat".to_vec()));
+                                        server.send(fragment(Data::Text, br#"{"type":"conn"#, false)).await.unwrap();
+                                        server.send(fragment(Data::Continue, br#"ected","connection_id":"c2"}"#, true)).await.unwrap();
+                                        server.close(None).await.unwrap();
+                                }).await;
+
+                                let mut client = WsClient::connect(&url).await.unwrap();
+                                assert_eq!(client.next_event().await.unwrap(), Some(WsEvent::Connected { connection_id: "c2".into() }));
+                                assert_eq!(client.next_event().await.unwrap(), None);
+                        });
+                }
+
+                it "capsFragmentedMessages" {
+                        b!(async {
+                                let url = stand_in(|mut server| async move {
+                                        server.send(fragment(Data::Text, &[b' '; 60], false)).await.unwrap();
+                                        server.send(fragment(Data::Continue, &[b' '; 60], false)).await.unwrap();
+                                        let _ = server.send(fragment(Data::Continue, b"{}", true)).await;
+                                }).await;
+
+                                let mut client = WsClient::connect(&url).await.unwrap();
+                                client.set_max_message_len(100);
+                                assert!(matches!(client.next_event().await, Err(WsError::Protocol(_))));
+                                assert_eq!(client.next_event().await.unwrap(), None);
+                        });
+                }
+
+                it "rejectsMaskedFrames" {
+                        b!(async {
+                                // A server that answers the handshake by hand, then sends a masked frame.
+                                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                                let url = format!("ws://{}/v3/ws", listener.local_addr().unwrap());
+                                tokio::spawn(async move {
+                                        let (mut stream, _) = listener.accept().await.unwrap();
+                                        let mut request = Vec::new();
+                                        let mut buf = [0u8; 1024];
+                                        while !String::from_utf8_lossy(&request).contains("\r\n\r\n") {
+                                                let n = stream.read(&mut buf).await.unwrap();
+                                                request.extend_from_slice(&buf[..n]);
+                                        }
+                                        let request = String::from_utf8_lossy(&request).to_string();
+                                        let key = request
+                                                .lines()
+                                                .find_map(|line| line.strip_prefix("sec-websocket-key: ").or_else(|| line.strip_prefix("Sec-WebSocket-Key: ")))
+                                                .unwrap();
+                                        let response = format!(
+                                                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                                                derive_accept_key(key.as_bytes())
+                                        );
+                                        stream.write_all(response.as_bytes()).await.unwrap();
+                                        // "{}" masked with 1, 2, 3, 4.
+                                        stream.write_all(&[0x81, 0x82, 1, 2, 3, 4, b'{' ^ 1, b'}' ^ 2]).await.unwrap();
+                                        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                                });
+
+                                let mut client = WsClient::connect(&url).await.unwrap();
+                                assert!(matches!(client.next_event().await, Err(WsError::Protocol(_))));
+                                assert_eq!(client.next_event().await.unwrap(), None);
+                        });
+                }
+
+                it "acceptsTungsteniteClient" {
+                        b!(async {
+                                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                                let url = format!("ws://{}/v3/ws", listener.local_addr().unwrap());
+                                let server = tokio::spawn(async move {
+                                        let (stream, _) = listener.accept().await.unwrap();
+                                        let mut server = WsConnection::accept(stream).await.unwrap();
+                                        server.send_text("hello").await.unwrap();
+                                        let reply = server.receive().await.unwrap();
+                                        server.close().await.unwrap();
+                                        reply
+                                });
+
+                                let (mut client, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+                                assert_eq!(client.next().await.unwrap().unwrap(), Message::Text("hello".into()));
+                                client.send(Message::Text("world".into())).await.unwrap();
+                                assert_eq!(server.await.unwrap().as_deref(), Some("world"));
+                        });
+                }
+
+                it "rejectsInvalidUrls" {
+                        b!(async {
+                                assert!(matches!(WsClient::connect("http://localhost/v3/ws").await, Err(WsError::InvalidUrl(_))));
+                        });
+                }
+        }
+}